@@ -0,0 +1,214 @@
+//! Accessibility checks for rendered views, run in debug builds.
+//!
+//! Every control should have a name a screen reader can announce, whether that comes from its
+//! text, an `aria-label`, or a `<label>`. After rendering, the view is walked and each control
+//! without one is logged as a warning.
+
+use euca::vdom::{DomIter, DomItem};
+use log::warn;
+
+/// Roles for controls that get their name from their contents.
+const NAMED_BY_CONTENT: &[&str] = &[
+    "button", "checkbox", "link", "menuitem", "option", "radio", "switch", "tab",
+];
+
+/// Roles for controls that need a name from somewhere else.
+const NAMED_BY_LABEL: &[&str] = &["combobox", "searchbox", "slider", "spinbutton", "textbox"];
+
+/// An element we haven't seen the end of yet.
+struct Open<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, &'a str)>,
+    text: String,
+    in_label: bool,
+}
+
+impl<'a> Open<'a> {
+    fn attr(&self, name: &str) -> Option<&'a str> {
+        self.attributes.iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| *value)
+            .filter(|value| !value.trim().is_empty())
+    }
+
+    /// The element as it'd appear in html, with enough attributes to find it.
+    fn describe(&self) -> String {
+        let mut s = format!("<{}", self.name);
+        for name in &["id", "name", "type", "role"] {
+            if let Some(value) = self.attr(name) {
+                s.push_str(&format!(" {}={:?}", name, value));
+            }
+        }
+        s.push('>');
+        s
+    }
+}
+
+/// A control missing a name, unless a `<label for>` we haven't seen yet names it.
+struct Problem {
+    id: Option<String>,
+    text: String,
+}
+
+/// Find the controls in `dom` without an accessible name, describing each one.
+pub fn audit<'a, Message, Command, K>(dom: &'a impl DomIter<Message, Command, K>) -> Vec<String>
+where
+    Message: Clone + 'a,
+    K: 'a,
+{
+    let mut stack: Vec<Option<Open<'a>>> = vec![];
+    let mut label_for = vec![];
+    let mut problems = vec![];
+
+    for item in dom.dom_iter() {
+        match item {
+            DomItem::Element { name, .. } => {
+                let in_label = stack.iter().flatten().any(|open| open.name == "label");
+                stack.push(Some(Open { name, attributes: vec![], text: String::new(), in_label }));
+            }
+            DomItem::Attr { name, value } => {
+                if let Some(Some(open)) = stack.last_mut() {
+                    open.attributes.push((name, value));
+                }
+            }
+            DomItem::Text(text) => {
+                for open in stack.iter_mut().flatten() {
+                    open.text.push_str(text);
+                }
+                stack.push(None);
+            }
+            DomItem::Component { .. } => stack.push(None),
+            DomItem::Up => {
+                if let Some(Some(open)) = stack.pop() {
+                    if open.name == "label" {
+                        label_for.extend(open.attr("for"));
+                    }
+                    problems.extend(check(&open));
+                }
+            }
+            DomItem::UnsafeInnerHtml(_) | DomItem::Event { .. } | DomItem::Key(_) => {}
+        }
+    }
+
+    problems.into_iter()
+        .filter(|problem| problem.id.as_deref().is_none_or(|id| !label_for.contains(&id)))
+        .map(|problem| problem.text)
+        .collect()
+}
+
+/// Log a warning for each control in `dom` without an accessible name.
+pub fn warn<Message: Clone, Command, K>(dom: &impl DomIter<Message, Command, K>) {
+    for problem in audit(dom) {
+        warn!("accessibility: {}", problem);
+    }
+}
+
+fn check(open: &Open) -> Option<Problem> {
+    let role = open.attr("role");
+    let input_type = open.attr("type").unwrap_or("text");
+
+    let field = match open.name {
+        "input" => !["hidden", "submit", "reset", "button", "image"].contains(&input_type),
+        "select" | "textarea" => true,
+        _ => role.is_some_and(|role| NAMED_BY_LABEL.contains(&role)),
+    };
+    let by_content = match open.name {
+        "button" => true,
+        "a" => open.attr("href").is_some(),
+        "input" => input_type != "hidden" && !field,
+        _ => role.is_some_and(|role| NAMED_BY_CONTENT.contains(&role)),
+    };
+    let focusable = open.attr("tabindex").is_some_and(|tabindex| tabindex.trim() != "-1");
+
+    if !field && !by_content && !focusable {
+        return None;
+    }
+
+    if ["aria-label", "aria-labelledby", "title"].iter().any(|name| open.attr(name).is_some()) {
+        return None;
+    }
+
+    if by_content {
+        let named = match (open.name, input_type) {
+            ("input", "image") => open.attr("alt").is_some(),
+            // the browser supplies names for submit and reset buttons without a value
+            ("input", "submit") | ("input", "reset") => true,
+            ("input", _) => open.attr("value").is_some(),
+            _ => !open.text.trim().is_empty(),
+        };
+
+        return if named {
+            None
+        }
+        else {
+            Some(Problem {
+                id: None,
+                text: format!("{} has no accessible name, give it some text or an aria-label",
+                    open.describe()),
+            })
+        };
+    }
+
+    if field {
+        if open.in_label {
+            return None;
+        }
+
+        let text = match open.attr("placeholder") {
+            Some(_) => format!("{} has no label, a placeholder isn't one", open.describe()),
+            None => format!("{} has no label, put it in a <label> or give it an aria-label",
+                open.describe()),
+        };
+        return Some(Problem { id: open.attr("id").map(str::to_owned), text });
+    }
+
+    Some(Problem {
+        id: None,
+        text: format!("{} is focusable but has no accessible name, give it a role and an \
+            aria-label", open.describe()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use euca::dom::{Dom, DomVec};
+
+    fn problems(dom: Vec<Dom<()>>) -> Vec<String> {
+        let dom: DomVec<()> = dom.into();
+        audit(&dom)
+    }
+
+    #[test]
+    fn named_controls() {
+        assert_eq!(problems(vec![
+            Dom::elem("button").push(Dom::text("+")),
+            Dom::elem("button").attr("aria-label", "Close"),
+            Dom::elem("label")
+                .push(Dom::text("Count"))
+                .push(Dom::elem("input")),
+            Dom::elem("input").attr("id", "name"),
+            Dom::elem("label").attr("for", "name").push(Dom::text("Name")),
+            Dom::elem("input").attr("type", "submit"),
+            Dom::elem("div").attr("role", "spinbutton").attr("aria-label", "Count"),
+            Dom::elem("a").push(Dom::text("not a link without an href")),
+        ]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unnamed_controls() {
+        assert_eq!(problems(vec![
+            Dom::elem("button").push(Dom::text(" ")),
+            Dom::elem("input").attr("name", "username").attr("placeholder", "Username"),
+            Dom::elem("select"),
+            Dom::elem("div").attr("role", "spinbutton").push(Dom::text("3")),
+            Dom::elem("div").attr("tabindex", "0"),
+        ]), vec![
+            "<button> has no accessible name, give it some text or an aria-label",
+            "<input name=\"username\"> has no label, a placeholder isn't one",
+            "<select> has no label, put it in a <label> or give it an aria-label",
+            "<div role=\"spinbutton\"> has no label, put it in a <label> or give it an aria-label",
+            "<div> is focusable but has no accessible name, give it a role and an aria-label",
+        ]);
+    }
+}
@@ -0,0 +1,183 @@
+//! A counter backed by shared memory, incremented in the background by a worker.
+//!
+//! The page and a worker share a `SharedArrayBuffer` holding the count. The worker increments it
+//! with `Atomics` on a timer, and the page checks it every frame, so both see the same count
+//! without sending messages back and forth. The buffer itself is sent to the worker once, when
+//! starting up.
+//!
+//! Shared memory is only available on cross-origin isolated pages, served with these headers:
+//!
+//! ```text
+//! Cross-Origin-Opener-Policy: same-origin
+//! Cross-Origin-Embedder-Policy: require-corp
+//! ```
+//!
+//! ```js
+//! // worker.js
+//! import { worker_increment } from "counter";
+//! worker_increment(1000);
+//!
+//! // main.js
+//! import { run_shared } from "counter";
+//! run_shared("main", new Worker(new URL("./worker.js", import.meta.url), { type: "module" }));
+//! ```
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use js_sys::{Atomics, Int32Array, SharedArrayBuffer};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use crate::{AppConfig, CounterHandle, Msg};
+
+/// A frame callback that schedules itself again.
+type Frame = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+/// What the page should do after comparing its count with the shared one.
+#[derive(Debug, PartialEq)]
+enum Reconcile {
+    /// Nothing changed.
+    Keep,
+    /// The worker changed the shared count, show it.
+    Adopt(i32),
+    /// The page changed its count, share it.
+    Share(i32),
+}
+
+/// Compare the shared and local counts with the count both had when last checked.
+///
+/// Changes to the shared count win, it's the one that can change behind our back.
+fn reconcile(last: i32, shared: i32, local: i32) -> Reconcile {
+    if shared != last {
+        Reconcile::Adopt(shared)
+    }
+    else if local != last {
+        Reconcile::Share(local)
+    }
+    else {
+        Reconcile::Keep
+    }
+}
+
+fn cross_origin_isolated() -> bool {
+    js_sys::Reflect::get(&js_sys::global(), &"crossOriginIsolated".into())
+        .map(|isolated| isolated.is_truthy())
+        .unwrap_or(false)
+}
+
+/// Increment the shared count every `interval` milliseconds, call this from inside a worker.
+///
+/// The shared buffer arrives from the page in the first message the worker gets.
+#[wasm_bindgen]
+pub fn worker_increment(interval: i32) -> Result<(), JsValue> {
+    crate::set_panic_hook();
+    crate::init_log();
+
+    let scope: web_sys::DedicatedWorkerGlobalScope = js_sys::global().dyn_into()?;
+
+    let timer = scope.clone();
+    let on_message = Closure::once(move |event: web_sys::MessageEvent| {
+        let count = Int32Array::new(&event.data());
+        let tick = Closure::wrap(Box::new(move || {
+            if let Err(e) = Atomics::add(&count, 0, 1) {
+                log::error!("error incrementing shared count: {:?}", e);
+            }
+        }) as Box<dyn FnMut()>);
+
+        let result = timer.set_interval_with_callback_and_timeout_and_arguments_0(
+            tick.as_ref().unchecked_ref(),
+            interval,
+        );
+        if let Err(e) = result {
+            log::error!("error starting background increments: {:?}", e);
+        }
+
+        // the worker increments for as long as it lives
+        tick.forget();
+    });
+
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+
+    Ok(())
+}
+
+/// Attach a counter sharing its count with `worker` to the first element matching the given CSS
+/// selector. The worker should call `worker_increment()`.
+#[wasm_bindgen]
+pub fn run_shared(selector: &str, worker: web_sys::Worker) -> Result<CounterHandle, JsValue> {
+    if !cross_origin_isolated() {
+        return Err("shared memory needs a cross-origin isolated page".into());
+    }
+
+    let handle = crate::run_with_config(selector, &AppConfig::default())?;
+    let shared = handle.shared();
+
+    let count = Int32Array::new(&SharedArrayBuffer::new(4));
+    Atomics::store(&count, 0, shared.count())?;
+    worker.post_message(&count.buffer())?;
+
+    // check the shared count every frame
+    let last = Cell::new(shared.count());
+    let frame: Frame = Rc::new(RefCell::new(None));
+    let next = frame.clone();
+    *frame.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        let value = match Atomics::load(&count, 0) {
+            Ok(value) => value,
+            Err(e) => {
+                log::error!("error loading shared count: {:?}", e);
+                return;
+            }
+        };
+
+        match reconcile(last.get(), value, shared.count()) {
+            Reconcile::Keep => {}
+            Reconcile::Adopt(value) => {
+                last.set(value);
+                shared.dispatch(Msg::SetCount(value));
+            }
+            Reconcile::Share(local) => {
+                // if the worker got there first we'll adopt its count next frame
+                if let Ok(previous) = Atomics::compare_exchange(&count, 0, last.get(), local) {
+                    if previous == last.get() {
+                        last.set(local);
+                    }
+                }
+            }
+        }
+
+        if let Some(ref callback) = *next.borrow() {
+            request_frame(callback);
+        }
+    }) as Box<dyn FnMut()>));
+
+    if let Some(ref callback) = *frame.borrow() {
+        request_frame(callback);
+    }
+
+    Ok(handle)
+}
+
+fn request_frame(callback: &Closure<dyn FnMut()>) {
+    let result = web_sys::window()
+        .ok_or_else(|| JsValue::from("no window"))
+        .and_then(|window| window.request_animation_frame(callback.as_ref().unchecked_ref()));
+
+    if let Err(e) = result {
+        log::error!("error scheduling shared count check: {:?}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconcile_counts() {
+        assert_eq!(reconcile(3, 3, 3), Reconcile::Keep);
+        assert_eq!(reconcile(3, 4, 3), Reconcile::Adopt(4));
+        assert_eq!(reconcile(3, 3, 5), Reconcile::Share(5));
+
+        // the worker and the page both changed it, the worker wins
+        assert_eq!(reconcile(3, 4, 5), Reconcile::Adopt(4));
+    }
+}
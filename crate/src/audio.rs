@@ -0,0 +1,51 @@
+//! Short tones played through the Web Audio API.
+
+use wasm_bindgen::prelude::*;
+use web_sys::{AudioContext, AudioContextState};
+use log::error;
+
+/// An audio context to play tones with, closed when dropped.
+///
+/// Browsers only let a page start playing audio in response to the user, so create this when
+/// handling a click rather than up front.
+pub struct Speaker {
+    context: AudioContext,
+}
+
+impl Speaker {
+    pub fn new() -> Result<Self, JsValue> {
+        Ok(Speaker { context: AudioContext::new()? })
+    }
+
+    /// Play a sine wave at `freq` hertz for `ms` milliseconds.
+    pub fn play(&self, freq: f32, ms: u32) -> Result<(), JsValue> {
+        // a context created without the user's say so starts out suspended
+        if self.context.state() == AudioContextState::Suspended {
+            let _ = self.context.resume()?;
+        }
+
+        let oscillator = self.context.create_oscillator()?;
+        oscillator.frequency().set_value(freq);
+
+        // keep it quiet, full volume is startling
+        let gain = self.context.create_gain()?;
+        gain.gain().set_value(0.1);
+
+        oscillator.connect_with_audio_node(&gain)?;
+        gain.connect_with_audio_node(&self.context.destination())?;
+
+        let now = self.context.current_time();
+        oscillator.start_with_when(now)?;
+        oscillator.stop_with_when(now + f64::from(ms) / 1000.0)?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        if let Err(e) = self.context.close() {
+            error!("error closing audio context: {:?}", e);
+        }
+    }
+}
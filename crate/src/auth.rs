@@ -0,0 +1,96 @@
+//! Logging in to the server the count is synced with.
+//!
+//! Credentials are posted as a JSON object with `username` and `password` fields, and the server
+//! responds with an object with a `token` field. The token is sent as a bearer token with every
+//! request to load or save the count after that.
+
+use serde::{Deserialize, Serialize};
+use crate::Msg;
+use crate::handle::Shared;
+use crate::locale::Locale;
+use crate::net::{self, Request};
+
+#[derive(Serialize)]
+struct Credentials<'a> {
+    username: &'a str,
+    password: &'a str,
+}
+
+#[derive(Deserialize)]
+struct TokenBody {
+    token: String,
+}
+
+/// Why logging in failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoginError {
+    /// The server didn't accept the username and password.
+    Rejected,
+    /// The server couldn't be asked, or its answer couldn't be understood.
+    Failed(String),
+}
+
+impl LoginError {
+    /// What went wrong, for showing under the login form.
+    pub fn message(&self, locale: &Locale) -> String {
+        match self {
+            LoginError::Rejected => locale.t("wrong_credentials").to_owned(),
+            LoginError::Failed(e) => e.clone(),
+        }
+    }
+}
+
+/// Parse the token out of a login response.
+pub fn parse_token(body: &str) -> Result<String, String> {
+    serde_json::from_str::<TokenBody>(body)
+        .map(|body| body.token)
+        .map_err(|e| format!("invalid login response: {}", e))
+}
+
+/// Add the token to a request, if there is one.
+pub fn authorize(request: Request, token: Option<&str>) -> Request {
+    match token {
+        Some(token) => request.header("Authorization", format!("Bearer {}", token)),
+        None => request,
+    }
+}
+
+/// Exchange credentials for a token at `url`, sending `Msg::LoggedIn` or `Msg::LoginFailed` to the
+/// app.
+pub fn login(shared: Shared, url: String, username: String, password: String) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let body = serde_json::to_string(&Credentials { username: &username, password: &password })
+            .expect("error serializing credentials");
+
+        let msg = match net::fetch(Request::post_json(url, body)).await {
+            Ok(body) => match parse_token(&body) {
+                Ok(token) => Msg::LoggedIn(token),
+                Err(e) => Msg::LoginFailed(LoginError::Failed(e)),
+            },
+            Err(net::Error::Status(401, _)) | Err(net::Error::Status(403, _)) => {
+                Msg::LoginFailed(LoginError::Rejected)
+            }
+            Err(e) => Msg::LoginFailed(LoginError::Failed(e.to_string())),
+        };
+
+        shared.dispatch(msg);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token() {
+        assert_eq!(parse_token(r#"{ "token": "abc" }"#), Ok("abc".to_owned()));
+        assert!(parse_token(r#"{ "error": "nope" }"#).is_err());
+
+        let request = authorize(Request::get("/count"), Some("abc"));
+        assert_eq!(request.headers, vec![("Authorization".to_owned(), "Bearer abc".to_owned())]);
+
+        let de = Locale::find("de-DE").unwrap();
+        let rejected = LoginError::Rejected.message(de);
+        assert_eq!(rejected, "Falscher Benutzername oder falsches Passwort");
+    }
+}
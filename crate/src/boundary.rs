@@ -0,0 +1,91 @@
+//! An error boundary for the app.
+//!
+//! A panic in `update` or `render` leaves the app half patched with its internals still borrowed,
+//! so it can't be recovered in place. Instead the panic hook marks the app as poisoned and swaps
+//! whatever was rendered for a fallback panel with a button to reset the app.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use log::error;
+use std::cell::RefCell;
+use std::panic;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::locale;
+
+static POISONED: AtomicBool = AtomicBool::new(false);
+static INSTALL: Once = Once::new();
+
+thread_local! {
+    static MOUNTS: RefCell<Vec<web_sys::Element>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Install the boundary's panic hook.
+///
+/// The previously installed hook is still called first so panics get reported as usual. `reset`
+/// is called when the reset button in the fallback panel is clicked. Only the first call
+/// has any effect.
+pub fn install(reset: fn()) {
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            previous(info);
+
+            // only show the fallback for the first panic
+            if POISONED.swap(true, Ordering::SeqCst) {
+                return;
+            }
+
+            MOUNTS.with(|mounts| {
+                for parent in mounts.borrow().iter() {
+                    if let Err(e) = show_fallback(parent, reset) {
+                        error!("error rendering fallback: {:?}", e);
+                    }
+                }
+            });
+        }));
+    });
+}
+
+/// Protect the given mount point, its contents will be replaced if the app panics.
+pub fn watch(parent: &web_sys::Element) {
+    MOUNTS.with(|mounts| mounts.borrow_mut().push(parent.clone()));
+}
+
+/// Stop protecting the given mount point.
+pub fn unwatch(parent: &web_sys::Element) {
+    MOUNTS.with(|mounts| mounts.borrow_mut().retain(|mount| mount != parent));
+}
+
+/// Check if the app has panicked.
+pub fn is_poisoned() -> bool {
+    POISONED.load(Ordering::SeqCst)
+}
+
+/// Replace the contents of `parent` with the fallback panel.
+///
+/// This works directly on the browser's dom, the app's virtual dom can't be trusted anymore. Nor
+/// can the locale picked in its settings, the panel is in the browser's preferred language.
+fn show_fallback(parent: &web_sys::Element, reset: fn()) -> Result<(), JsValue> {
+    let document = parent.owner_document()
+        .ok_or("mount point has no document")?;
+    let locale = locale::preferred();
+
+    let panel = document.create_element("div")?;
+    panel.set_class_name("error");
+
+    let message = document.create_element("p")?;
+    message.set_text_content(Some(locale.t("something_went_wrong")));
+    panel.append_child(&message)?;
+
+    let button = document.create_element("button")?;
+    button.set_text_content(Some(locale.t("reset_app")));
+    let onclick = Closure::once_into_js(reset);
+    button.add_event_listener_with_callback("click", onclick.unchecked_ref())?;
+    panel.append_child(&button)?;
+
+    parent.set_inner_html("");
+    parent.append_child(&panel)?;
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+//! A line chart of the count over time, drawn from the history.
+//!
+//! euca only creates elements in the HTML namespace, where `<svg>` and its children don't draw
+//! anything. So the chart is built as SVG markup and set as the inner HTML of an element, the
+//! browser's parser puts it in the right namespace. Everything in the markup is either a number
+//! or escaped.
+
+use std::collections::VecDeque;
+use crate::Count;
+use crate::history::Entry;
+use crate::locale::Locale;
+
+const WIDTH: f64 = 300.0;
+const HEIGHT: f64 = 150.0;
+
+/// The plot area, leaving room for the axis labels on the left and bottom.
+const LEFT: f64 = 40.0;
+const RIGHT: f64 = 290.0;
+const TOP: f64 = 10.0;
+const BOTTOM: f64 = 130.0;
+
+/// The most points plotted, longer histories are sampled down to about this many.
+const MAX_POINTS: usize = 200;
+
+/// Escape text for SVG markup.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Where on the plot area `value` goes, between `low` and `high`, or halfway if they're the same.
+fn scale(value: f64, (low, high): (f64, f64), (from, to): (f64, f64)) -> f64 {
+    if high > low {
+        from + (value - low) / (high - low) * (to - from)
+    }
+    else {
+        (from + to) / 2.0
+    }
+}
+
+/// The chart of `entries` as SVG markup, with a hover tooltip for each point. The counts are at
+/// `places` decimal places.
+pub fn svg(entries: &VecDeque<Entry>, places: u32, locale: &Locale) -> String {
+    let stride = entries.len().div_ceil(MAX_POINTS).max(1);
+    let mut points: Vec<&Entry> = entries.iter().step_by(stride).collect();
+    // always end on the count as it is now
+    if let (Some(last), Some(&plotted)) = (entries.back(), points.last()) {
+        if !std::ptr::eq(last, plotted) {
+            points.push(last);
+        }
+    }
+
+    let bounds = |value: fn(&Entry) -> f64| {
+        points.iter()
+            .map(|&entry| value(entry))
+            .fold(None, |bounds: Option<(f64, f64)>, value| match bounds {
+                Some((low, high)) => Some((low.min(value), high.max(value))),
+                None => Some((value, value)),
+            })
+            .unwrap_or((0.0, 0.0))
+    };
+    let times = bounds(|entry| entry.time);
+    let counts = bounds(|entry| entry.count as f64);
+
+    // changes can come faster than the clock ticks, spread those out by their order instead
+    let x = |i: usize, entry: &Entry| match times {
+        (first, last) if last > first => scale(entry.time, times, (LEFT, RIGHT)),
+        _ => scale(i as f64, (0.0, points.len() as f64 - 1.0), (LEFT, RIGHT)),
+    };
+    let y = |entry: &Entry| scale(entry.count as f64, counts, (BOTTOM, TOP));
+
+    let line = points.iter()
+        .enumerate()
+        .map(|(i, entry)| format!("{:.1},{:.1}", x(i, entry), y(entry)))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" role=\"img\" \
+        aria-label=\"{}\">",
+        WIDTH, HEIGHT, escape(locale.t("chart")),
+    );
+    svg.push_str(&format!(
+        "<line x1=\"{l}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"currentColor\"/>\
+        <line x1=\"{l}\" y1=\"{t}\" x2=\"{l}\" y2=\"{b}\" stroke=\"currentColor\"/>",
+        l = LEFT, r = RIGHT, t = TOP, b = BOTTOM,
+    ));
+
+    let label = |x: f64, y: f64, anchor: &str, text: String| {
+        format!("<text x=\"{}\" y=\"{}\" text-anchor=\"{}\" font-size=\"10\" \
+            fill=\"currentColor\">{}</text>", x, y, anchor, escape(&text))
+    };
+    if let (Some(first), Some(last)) = (points.first(), points.last()) {
+        let count = |count: f64| locale.decimal(count as Count, places);
+        svg.push_str(&label(LEFT - 4.0, TOP + 4.0, "end", count(counts.1)));
+        svg.push_str(&label(LEFT - 4.0, BOTTOM, "end", count(counts.0)));
+        svg.push_str(&label(LEFT, HEIGHT - 5.0, "start", locale.time(first.time)));
+        svg.push_str(&label(RIGHT, HEIGHT - 5.0, "end", locale.time(last.time)));
+    }
+
+    svg.push_str(&format!(
+        "<polyline class=\"line\" points=\"{}\" fill=\"none\" stroke=\"currentColor\"/>",
+        line,
+    ));
+
+    for (i, entry) in points.iter().enumerate() {
+        let count = locale.decimal(entry.count, places);
+        let tooltip = format!("{} {}", locale.time(entry.time), count);
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"currentColor\"><title>{}</title>\
+            </circle>",
+            x(i, entry), y(entry), escape(&tooltip),
+        ));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Action;
+    use crate::locale::LOCALES;
+
+    fn entry(time: f64, count: Count) -> Entry {
+        Entry { time, action: Action::Set, count }
+    }
+
+    #[test]
+    fn escaped() {
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[test]
+    fn snapshot() {
+        let entries = VecDeque::from(vec![entry(0.0, 0), entry(1000.0, 10), entry(2000.0, 5)]);
+        assert_eq!(svg(&entries, 0, &LOCALES[0]), "\
+            <svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 300 150\" role=\"img\" \
+            aria-label=\"Count over time\">\
+            <line x1=\"40\" y1=\"130\" x2=\"290\" y2=\"130\" stroke=\"currentColor\"/>\
+            <line x1=\"40\" y1=\"10\" x2=\"40\" y2=\"130\" stroke=\"currentColor\"/>\
+            <text x=\"36\" y=\"14\" text-anchor=\"end\" font-size=\"10\" \
+            fill=\"currentColor\">10</text>\
+            <text x=\"36\" y=\"130\" text-anchor=\"end\" font-size=\"10\" \
+            fill=\"currentColor\">0</text>\
+            <text x=\"40\" y=\"145\" text-anchor=\"start\" font-size=\"10\" \
+            fill=\"currentColor\">00:00:00</text>\
+            <text x=\"290\" y=\"145\" text-anchor=\"end\" font-size=\"10\" \
+            fill=\"currentColor\">00:00:02</text>\
+            <polyline class=\"line\" points=\"40.0,130.0 165.0,10.0 290.0,70.0\" fill=\"none\" \
+            stroke=\"currentColor\"/>\
+            <circle cx=\"40.0\" cy=\"130.0\" r=\"3\" fill=\"currentColor\">\
+            <title>00:00:00 0</title></circle>\
+            <circle cx=\"165.0\" cy=\"10.0\" r=\"3\" fill=\"currentColor\">\
+            <title>00:00:01 10</title></circle>\
+            <circle cx=\"290.0\" cy=\"70.0\" r=\"3\" fill=\"currentColor\">\
+            <title>00:00:02 5</title></circle>\
+            </svg>");
+    }
+
+    #[test]
+    fn same_time_and_count() {
+        let entries = VecDeque::from(vec![entry(0.0, 3), entry(0.0, 3)]);
+        assert!(svg(&entries, 0, &LOCALES[0]).contains("points=\"40.0,70.0 290.0,70.0\""));
+    }
+
+    #[test]
+    fn sampled() {
+        let entries: VecDeque<_> = (0..1001).map(|i| entry(i as f64, i)).collect();
+        let svg = svg(&entries, 0, &LOCALES[0]);
+        assert_eq!(svg.matches("<circle").count(), 168);
+        assert!(svg.contains("<title>00:00:01 1,000</title>"));
+    }
+
+    #[test]
+    fn decimals() {
+        let entries = VecDeque::from(vec![entry(0.0, 5), entry(1000.0, -250)]);
+        let svg = svg(&entries, 2, &LOCALES[0]);
+        assert!(svg.contains(">0.05</text>"));
+        assert!(svg.contains("<title>00:00:01 -2.50</title>"));
+    }
+}
@@ -0,0 +1,79 @@
+//! Rendering a child node for each item in a list.
+//!
+//! Lists re-render often, the history on every change to the count. Keyed children let the diff
+//! follow an item to its new place in the list instead of rewriting every node after an insert.
+
+use euca::dom::Dom;
+use crate::{Cmd, Msg};
+
+/// Adding a child node for each item of an iterator.
+pub trait Children: Sized {
+    /// Add the node `render` makes of each item, in order.
+    fn children_from<T>(
+        self,
+        items: impl IntoIterator<Item = T>,
+        render: impl FnMut(T) -> Dom<Msg, Cmd>,
+    ) -> Self;
+
+    /// Add the node `render` makes of each item, keyed by what `key` gives for it.
+    ///
+    /// Keys have to be unique among the items.
+    fn keyed_children_from<T>(
+        self,
+        items: impl IntoIterator<Item = T>,
+        key: impl Fn(&T) -> u64,
+        mut render: impl FnMut(T) -> Dom<Msg, Cmd>,
+    ) -> Self {
+        self.children_from(items, move |item| {
+            let item_key = key(&item);
+            render(item).key(item_key)
+        })
+    }
+}
+
+impl Children for Dom<Msg, Cmd> {
+    fn children_from<T>(
+        self,
+        items: impl IntoIterator<Item = T>,
+        render: impl FnMut(T) -> Dom<Msg, Cmd>,
+    ) -> Self {
+        self.extend(items.into_iter().map(render))
+    }
+}
+
+/// For views made of several sibling nodes.
+impl Children for Vec<Dom<Msg, Cmd>> {
+    fn children_from<T>(
+        mut self,
+        items: impl IntoIterator<Item = T>,
+        render: impl FnMut(T) -> Dom<Msg, Cmd>,
+    ) -> Self {
+        self.extend(items.into_iter().map(render));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use euca::vdom::{DomIter, DomItem};
+
+    #[test]
+    fn one_for_each() {
+        let list = Dom::elem("ol").keyed_children_from(vec![3, 5], |&n| n, |n| {
+            Dom::elem("li").push(text!("{}", n))
+        });
+        let items: Vec<_> = list.dom_iter()
+            .filter(|item| matches!(item, DomItem::Element { name: "li", .. } | DomItem::Text(_)))
+            .collect();
+        assert_eq!(items, vec![
+            DomItem::Element { name: "li", key: Some(3) },
+            DomItem::Text("3"),
+            DomItem::Element { name: "li", key: Some(5) },
+            DomItem::Text("5"),
+        ]);
+
+        let nodes = vec![].children_from(0..3, |n: u64| text!("{}", n));
+        assert_eq!(nodes.len(), 3);
+    }
+}
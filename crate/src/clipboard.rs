@@ -0,0 +1,26 @@
+//! Copying to the clipboard with `navigator.clipboard`.
+
+use wasm_bindgen_futures::JsFuture;
+use crate::Msg;
+use crate::handle::Shared;
+
+/// Copy `text` to the clipboard, sending `Msg::Copied` or `Msg::CopyFailed` to the app.
+pub fn copy(shared: Shared, text: String) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => {
+            shared.dispatch(Msg::CopyFailed("couldn't get window handle".to_owned()));
+            return;
+        }
+    };
+
+    let promise = window.navigator().clipboard().write_text(&text);
+    wasm_bindgen_futures::spawn_local(async move {
+        let msg = match JsFuture::from(promise).await {
+            Ok(_) => Msg::Copied,
+            Err(e) => Msg::CopyFailed(format!("{:?}", e)),
+        };
+
+        shared.dispatch(msg);
+    });
+}
@@ -0,0 +1,107 @@
+//! The time, in milliseconds since the epoch.
+
+/// The time from `Date.now()`.
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+/// The time from the system clock.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+/// The time as an ISO 8601 date and time in UTC, from `Date.toISOString()`.
+#[cfg(target_arch = "wasm32")]
+pub fn iso(time: f64) -> String {
+    js_sys::Date::new(&time.into()).to_iso_string().into()
+}
+
+/// The time as an ISO 8601 date and time in UTC.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn iso(time: f64) -> String {
+    const DAY: i64 = 24 * 60 * 60 * 1000;
+    let time = time.floor() as i64;
+    let (days, ms) = (time.div_euclid(DAY), time.rem_euclid(DAY));
+
+    // the days since the epoch as a date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+        - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day, ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000,
+    )
+}
+
+/// Midnight at the start of the day `time` is in, in the browser's time zone.
+#[cfg(target_arch = "wasm32")]
+pub fn start_of_day(time: f64) -> f64 {
+    let date = js_sys::Date::new(&time.into());
+    date.set_hours(0);
+    date.set_minutes(0);
+    date.set_seconds(0);
+    date.set_milliseconds(0)
+}
+
+/// Midnight at the start of the day `time` is in, in UTC.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn start_of_day(time: f64) -> f64 {
+    const DAY: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+    (time / DAY).floor() * DAY
+}
+
+/// Whether midnight has passed between `since` and `now`, in whichever time zone `start_of_day`
+/// finds midnight in.
+pub fn new_day(since: f64, now: f64, start_of_day: fn(f64) -> f64) -> bool {
+    start_of_day(now) > start_of_day(since)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso_dates() {
+        assert_eq!(iso(0.0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso(1_000_000_000_123.0), "2001-09-09T01:46:40.123Z");
+        assert_eq!(iso(951_782_400_000.0), "2000-02-29T00:00:00.000Z");
+        assert_eq!(iso(-1.0), "1969-12-31T23:59:59.999Z");
+    }
+
+    #[test]
+    fn midnight() {
+        assert_eq!(start_of_day(0.0), 0.0);
+        assert_eq!(start_of_day(1_000_000_000_000.0), 999_993_600_000.0);
+        assert_eq!(start_of_day(-1.0), -86_400_000.0);
+    }
+
+    #[test]
+    fn days() {
+        const HOUR: f64 = 60.0 * 60.0 * 1000.0;
+        // midnight in UTC+9 is at 15:00 UTC
+        fn tokyo(time: f64) -> f64 {
+            start_of_day(time + 9.0 * HOUR) - 9.0 * HOUR
+        }
+
+        let evening = 1_000_000_000_000.0 + 12.0 * HOUR;
+        assert!(!new_day(evening, evening + HOUR, start_of_day));
+        assert!(new_day(evening, evening + 12.0 * HOUR, start_of_day));
+        assert!(new_day(evening, evening + 2.0 * HOUR, tokyo));
+        assert!(!new_day(evening + 2.0 * HOUR, evening + 12.0 * HOUR, tokyo));
+        // the clock went back
+        assert!(!new_day(evening + 48.0 * HOUR, evening, start_of_day));
+    }
+}
@@ -0,0 +1,149 @@
+//! Wire formats for the messages counters sync with each other.
+//!
+//! JSON is readable and easy to handle on a server, the binary format (postcard) is much smaller.
+//! Whichever format is used for sending, messages received in either format are understood.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// An encoded message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl Message {
+    /// Convert the message to a value that can be sent from JavaScript.
+    pub fn to_js(&self) -> JsValue {
+        match self {
+            Message::Text(text) => JsValue::from_str(text),
+            Message::Binary(bytes) => js_sys::Uint8Array::from(&bytes[..]).into(),
+        }
+    }
+
+    /// Convert a value received from JavaScript to a message, if it is a string or bytes.
+    pub fn from_js(value: &JsValue) -> Option<Message> {
+        if let Some(text) = value.as_string() {
+            return Some(Message::Text(text));
+        }
+
+        if let Some(buffer) = value.dyn_ref::<js_sys::ArrayBuffer>() {
+            return Some(Message::Binary(js_sys::Uint8Array::new(buffer).to_vec()));
+        }
+
+        value.dyn_ref::<js_sys::Uint8Array>()
+            .map(|bytes| Message::Binary(bytes.to_vec()))
+    }
+}
+
+/// A way of encoding messages.
+pub trait Codec {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Message, String>;
+    fn decode<T: DeserializeOwned>(&self, message: &Message) -> Result<T, String>;
+}
+
+/// Messages as JSON text.
+pub struct Json;
+
+impl Codec for Json {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Message, String> {
+        serde_json::to_string(value)
+            .map(Message::Text)
+            .map_err(|e| format!("error encoding json: {}", e))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, message: &Message) -> Result<T, String> {
+        match message {
+            Message::Text(text) => serde_json::from_str(text)
+                .map_err(|e| format!("invalid json: {}", e)),
+            Message::Binary(_) => Err("expected text, got binary".to_owned()),
+        }
+    }
+}
+
+/// Messages as compact binary with postcard.
+pub struct Binary;
+
+impl Codec for Binary {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Message, String> {
+        postcard::to_allocvec(value)
+            .map(Message::Binary)
+            .map_err(|e| format!("error encoding binary: {}", e))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, message: &Message) -> Result<T, String> {
+        match message {
+            Message::Binary(bytes) => postcard::from_bytes(bytes)
+                .map_err(|e| format!("invalid binary: {}", e)),
+            Message::Text(_) => Err("expected binary, got text".to_owned()),
+        }
+    }
+}
+
+/// The format used to send messages, chosen by the host page.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    Binary,
+}
+
+impl Codec for Format {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Message, String> {
+        match self {
+            Format::Json => Json.encode(value),
+            Format::Binary => Binary.encode(value),
+        }
+    }
+
+    /// Decode a message in whichever format it was sent.
+    fn decode<T: DeserializeOwned>(&self, message: &Message) -> Result<T, String> {
+        match message {
+            Message::Text(_) => Json.decode(message),
+            Message::Binary(_) => Binary.decode(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crdt::PnCounter;
+
+    fn round_trip<C: Codec>(codec: C) {
+        let mut tally = PnCounter::default();
+        tally.add("a", 3);
+        tally.add("b", -2);
+
+        let message = codec.encode(&tally).unwrap();
+        assert_eq!(codec.decode::<PnCounter>(&message), Ok(tally));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        round_trip(Json);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        round_trip(Binary);
+    }
+
+    #[test]
+    fn binary_is_smaller() {
+        let json = Json.encode(&-12345i32).unwrap();
+        let binary = Binary.encode(&-12345i32).unwrap();
+        assert_eq!(binary, Message::Binary(vec![0xf1, 0xc0, 0x01]));
+        assert!(matches!(json, Message::Text(ref text) if text.len() > 3));
+    }
+
+    #[test]
+    fn format_decodes_either() {
+        let json = Json.encode(&7i32).unwrap();
+        let binary = Binary.encode(&7i32).unwrap();
+        assert_eq!(Format::Binary.decode::<i32>(&json), Ok(7));
+        assert_eq!(Format::Json.decode::<i32>(&binary), Ok(7));
+    }
+}
@@ -0,0 +1,89 @@
+//! Rendering parts of the view only when they're wanted.
+//!
+//! A part that isn't wanted is left out of its parent's children altogether, rather than standing
+//! in as an empty element. Euca diffs children in order, so the children after a part that comes
+//! or goes are compared with the ones that were next to them before. Where that would replace
+//! nodes worth keeping, like a text box that could have focus, `Dom::empty()` holds the part's
+//! place while it's away.
+
+use euca::dom::Dom;
+use crate::{Cmd, Msg};
+
+/// A node showing nothing.
+pub trait Empty {
+    /// An empty text node, it takes up no room on the page.
+    fn empty() -> Self;
+}
+
+impl Empty for Dom<Msg, Cmd> {
+    fn empty() -> Self {
+        Dom::text("")
+    }
+}
+
+/// Adding a child node only when it's wanted.
+pub trait Conditional: Sized {
+    /// Add a child node of its own.
+    fn add_child(self, node: Dom<Msg, Cmd>) -> Self;
+
+    /// Add the node `render` makes if `condition` holds, and nothing otherwise.
+    fn if_then(self, condition: bool, render: impl FnOnce() -> Dom<Msg, Cmd>) -> Self {
+        match condition {
+            true => self.add_child(render()),
+            false => self,
+        }
+    }
+
+    /// Add the node `render` makes of `value` if there is one, and nothing otherwise.
+    fn maybe<T>(self, value: Option<T>, render: impl FnOnce(T) -> Dom<Msg, Cmd>) -> Self {
+        match value {
+            Some(value) => self.add_child(render(value)),
+            None => self,
+        }
+    }
+}
+
+impl Conditional for Dom<Msg, Cmd> {
+    fn add_child(self, node: Dom<Msg, Cmd>) -> Self {
+        self.push(node)
+    }
+}
+
+/// For views made of several sibling nodes.
+impl Conditional for Vec<Dom<Msg, Cmd>> {
+    fn add_child(mut self, node: Dom<Msg, Cmd>) -> Self {
+        self.push(node);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use euca::vdom::{DomIter, DomItem};
+
+    #[test]
+    fn left_out() {
+        let dom = Dom::elem("div")
+            .if_then(false, || Dom::text("hidden"))
+            .maybe(Some("shown"), |text| Dom::text(text))
+            .maybe(None::<&str>, |text| Dom::text(text))
+            .if_then(true, || Dom::text("also shown"));
+        let texts: Vec<_> = dom.dom_iter()
+            .filter(|item| matches!(item, DomItem::Text(_)))
+            .collect();
+        assert_eq!(texts, vec![DomItem::Text("shown"), DomItem::Text("also shown")]);
+
+        let nodes = vec![Dom::text("first")].if_then(false, || Dom::text("second"));
+        assert_eq!(nodes.len(), 1);
+    }
+
+    #[test]
+    fn empty() {
+        let dom = Dom::elem("div").push(Dom::empty());
+        let texts: Vec<_> = dom.dom_iter()
+            .filter(|item| matches!(item, DomItem::Text(_)))
+            .collect();
+        assert_eq!(texts, vec![DomItem::Text("")]);
+    }
+}
@@ -0,0 +1,120 @@
+//! Confetti thrown when the count reaches a milestone.
+//!
+//! Each piece is an absolutely positioned div in a layer over the page. Animation frames move the
+//! pieces along until `DURATION` has passed, then the layer is removed.
+
+/// Milliseconds confetti is shown for.
+pub const DURATION: f64 = 1500.0;
+
+/// How many pieces are thrown.
+const PIECES: usize = 40;
+
+const COLORS: &[&str] = &["#e8590c", "#2e7d32", "#0366d6", "#f5c518", "#d6336c"];
+
+/// How fast pieces fall, in viewport heights per second per second.
+const GRAVITY: f64 = 120.0;
+
+/// Numbers from a seed, scattered enough for confetti.
+struct Random(u32);
+
+impl Random {
+    /// A number between `low` and `high`.
+    fn between(&mut self, low: f64, high: f64) -> f64 {
+        // xorshift
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        low + (high - low) * f64::from(x) / f64::from(u32::MAX)
+    }
+}
+
+/// A piece of confetti, thrown from the middle of the page.
+#[derive(Clone, Debug, PartialEq)]
+struct Piece {
+    /// Viewport widths per second to the right.
+    dx: f64,
+    /// Viewport heights per second up.
+    dy: f64,
+    /// Degrees per second.
+    spin: f64,
+    color: &'static str,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Confetti {
+    pieces: Vec<Piece>,
+    /// The time of the first frame, from `requestAnimationFrame`.
+    start: Option<f64>,
+    /// Milliseconds since the first frame.
+    elapsed: f64,
+}
+
+impl Confetti {
+    /// Confetti scattered by `seed`, the same seed scatters it the same way.
+    pub fn new(seed: u32) -> Self {
+        let mut random = Random(seed | 1);
+        let pieces = (0..PIECES)
+            .map(|i| Piece {
+                dx: random.between(-40.0, 40.0),
+                dy: random.between(30.0, 80.0),
+                spin: random.between(-720.0, 720.0),
+                color: COLORS[i % COLORS.len()],
+            })
+            .collect();
+
+        Confetti { pieces, start: None, elapsed: 0.0 }
+    }
+
+    /// Move the pieces along for a frame at `time`, false once they're done.
+    pub fn frame(&mut self, time: f64) -> bool {
+        let start = *self.start.get_or_insert(time);
+        self.elapsed = (time - start).clamp(0.0, DURATION);
+        self.elapsed < DURATION
+    }
+
+    /// The style of each piece where it is now.
+    pub fn pieces(&self) -> Vec<String> {
+        let t = self.elapsed / 1000.0;
+        // fade out over the last third
+        let opacity = (3.0 * (1.0 - self.elapsed / DURATION)).min(1.0);
+
+        self.pieces.iter()
+            .map(|piece| format!(
+                "position: absolute; left: {:.1}vw; top: {:.1}vh; width: 8px; height: 12px; \
+                background: {}; opacity: {:.2}; transform: rotate({:.0}deg)",
+                50.0 + piece.dx * t,
+                30.0 - piece.dy * t + GRAVITY * t * t / 2.0,
+                piece.color,
+                opacity,
+                piece.spin * t,
+            ))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scattered() {
+        let confetti = Confetti::new(7);
+        assert_eq!(confetti.pieces.len(), PIECES);
+        assert_eq!(confetti, Confetti::new(7));
+        assert_ne!(confetti, Confetti::new(8));
+        assert!(confetti.pieces.iter().all(|piece| piece.dx.abs() <= 40.0 && piece.dy >= 30.0));
+        assert!(confetti.pieces().iter().all(|style| style.contains("left: 50.0vw; top: 30.0vh")));
+    }
+
+    #[test]
+    fn frames() {
+        let mut confetti = Confetti::new(0);
+        assert!(confetti.frame(1000.0));
+        assert!(confetti.frame(1000.0 + DURATION / 2.0));
+        assert!(confetti.pieces()[0].contains("opacity: 1.00"));
+        assert!(!confetti.frame(1000.0 + DURATION));
+        assert!(confetti.pieces()[0].contains("opacity: 0.00"));
+    }
+}
@@ -0,0 +1,483 @@
+//! Configuration supplied by the host page.
+
+use wasm_bindgen::prelude::*;
+use log::warn;
+use crate::Count;
+use crate::initial_state::InitialState;
+use std::str::FromStr;
+use std::fmt;
+
+/// A milestone as it crosses to JavaScript. There's no array of `i128`s, so wide builds take a
+/// `BigInt64Array`.
+#[cfg(feature = "wide")]
+pub type Milestone = i64;
+#[cfg(not(feature = "wide"))]
+pub type Milestone = i32;
+
+/// The `data-*` attributes read from the mount element.
+const DATA_ATTRIBUTES: &[&str] = &[
+    "data-initial-count",
+    "data-step",
+    "data-storage-key",
+    "data-auto-increment",
+    "data-count-url",
+    "data-save-url",
+    "data-max-retries",
+    "data-socket-url",
+    "data-events-url",
+    "data-sync-tabs",
+    "data-binary-sync",
+    "data-login-url",
+    "data-remember-login",
+    "data-milestones",
+    "data-sounds",
+    "data-haptics",
+    "data-speech",
+    "data-history-page-size",
+    "data-countdown",
+    "data-hash-routing",
+    "data-idle-timeout",
+    "data-diagnostics",
+];
+
+/// Settings for a counter, constructed from JavaScript and passed to `run_with_config()`.
+///
+/// ```js
+/// const config = new AppConfig();
+/// config.initial_count = 10;
+/// config.step = 5;
+/// run_with_config("#counter", config);
+/// ```
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppConfig {
+    initial_count: Count,
+    step: i32,
+    storage_key: Option<String>,
+    auto_increment: Option<u32>,
+    count_url: Option<String>,
+    save_url: Option<String>,
+    max_retries: u32,
+    socket_url: Option<String>,
+    events_url: Option<String>,
+    sync_tabs: bool,
+    binary_sync: bool,
+    login_url: Option<String>,
+    remember_login: bool,
+    milestones: Vec<Milestone>,
+    sounds: bool,
+    haptics: bool,
+    speech: bool,
+    history_page_size: Option<u32>,
+    countdown: Option<u32>,
+    hash_routing: bool,
+    idle_timeout: Option<u32>,
+    diagnostics: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            initial_count: 0,
+            step: 1,
+            storage_key: None,
+            auto_increment: None,
+            count_url: None,
+            save_url: None,
+            max_retries: crate::MAX_RETRIES,
+            socket_url: None,
+            events_url: None,
+            sync_tabs: false,
+            binary_sync: false,
+            login_url: None,
+            remember_login: false,
+            milestones: vec![],
+            sounds: false,
+            haptics: false,
+            speech: false,
+            history_page_size: None,
+            countdown: None,
+            hash_routing: false,
+            idle_timeout: None,
+            diagnostics: false,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl AppConfig {
+    /// Create a config with the default settings.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> AppConfig {
+        AppConfig::default()
+    }
+
+    /// The count to start with if there is no stashed count to restore.
+    #[wasm_bindgen(getter)]
+    pub fn initial_count(&self) -> Count {
+        self.initial_count
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_initial_count(&mut self, count: Count) {
+        self.initial_count = count;
+    }
+
+    /// How much each click changes the count.
+    #[wasm_bindgen(getter)]
+    pub fn step(&self) -> i32 {
+        self.step
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_step(&mut self, step: i32) {
+        self.step = step;
+    }
+
+    /// The session storage key to stash the count under, instead of one derived from the mount
+    /// point.
+    #[wasm_bindgen(getter)]
+    pub fn storage_key(&self) -> Option<String> {
+        self.storage_key.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_storage_key(&mut self, key: Option<String>) {
+        self.storage_key = key;
+    }
+
+    /// If set, increment the count automatically every this many milliseconds.
+    #[wasm_bindgen(getter)]
+    pub fn auto_increment(&self) -> Option<u32> {
+        self.auto_increment
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_auto_increment(&mut self, interval: Option<u32>) {
+        self.auto_increment = interval;
+    }
+
+    /// If set, load the initial count from this url.
+    ///
+    /// The response should be a JSON number or an object with a `count` field.
+    #[wasm_bindgen(getter)]
+    pub fn count_url(&self) -> Option<String> {
+        self.count_url.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_count_url(&mut self, url: Option<String>) {
+        self.count_url = url;
+    }
+
+    /// If set, save the count to this url once it stops changing.
+    ///
+    /// The count is sent in a `POST` request as a JSON object with a `count` field.
+    #[wasm_bindgen(getter)]
+    pub fn save_url(&self) -> Option<String> {
+        self.save_url.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_save_url(&mut self, url: Option<String>) {
+        self.save_url = url;
+    }
+
+    /// How many times to retry loading or saving the count before giving up.
+    #[wasm_bindgen(getter)]
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_max_retries(&mut self, retries: u32) {
+        self.max_retries = retries;
+    }
+
+    /// If set, keep the count in sync with other windows through a WebSocket at this url.
+    ///
+    /// Changes are sent as JSON objects with a `count` field, and counts received in the same
+    /// format (or as bare numbers) are set on the counter.
+    #[wasm_bindgen(getter)]
+    pub fn socket_url(&self) -> Option<String> {
+        self.socket_url.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_socket_url(&mut self, url: Option<String>) {
+        self.socket_url = url;
+    }
+
+    /// If set, set the count to the counts received from a server-sent event stream at this url.
+    ///
+    /// Each event's data should be a JSON number or an object with a `count` field.
+    #[wasm_bindgen(getter)]
+    pub fn events_url(&self) -> Option<String> {
+        self.events_url.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_events_url(&mut self, url: Option<String>) {
+        self.events_url = url;
+    }
+
+    /// Whether to keep the count in sync with the same counter in other tabs.
+    ///
+    /// Changes made in different tabs at the same time are merged, not overwritten. This is meant
+    /// for counters without a server to sync with, tabs syncing with a server would get each
+    /// other's changes twice.
+    #[wasm_bindgen(getter)]
+    pub fn sync_tabs(&self) -> bool {
+        self.sync_tabs
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_sync_tabs(&mut self, sync: bool) {
+        self.sync_tabs = sync;
+    }
+
+    /// Whether to send changes over the WebSocket and to other tabs in a compact binary format
+    /// (postcard) instead of JSON.
+    #[wasm_bindgen(getter)]
+    pub fn binary_sync(&self) -> bool {
+        self.binary_sync
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_binary_sync(&mut self, binary: bool) {
+        self.binary_sync = binary;
+    }
+
+    /// If set, show a login form until the user logs in at this url, then send the token they get
+    /// with every request to load or save the count.
+    #[wasm_bindgen(getter)]
+    pub fn login_url(&self) -> Option<String> {
+        self.login_url.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_login_url(&mut self, url: Option<String>) {
+        self.login_url = url;
+    }
+
+    /// Whether to keep the login token in local storage, so the user stays logged in across visits.
+    #[wasm_bindgen(getter)]
+    pub fn remember_login(&self) -> bool {
+        self.remember_login
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_remember_login(&mut self, remember: bool) {
+        self.remember_login = remember;
+    }
+
+    /// Counts to show a notification at when the counter reaches them, asking for permission to
+    /// show notifications the first time.
+    #[wasm_bindgen(getter)]
+    pub fn milestones(&self) -> Vec<Milestone> {
+        self.milestones.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_milestones(&mut self, milestones: Vec<Milestone>) {
+        self.milestones = milestones;
+    }
+
+    /// Whether to play a tone on every click, with a button to mute them.
+    #[wasm_bindgen(getter)]
+    pub fn sounds(&self) -> bool {
+        self.sounds
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_sounds(&mut self, sounds: bool) {
+        self.sounds = sounds;
+    }
+
+    /// Whether to vibrate on every click on touch devices, with a button to turn it off.
+    #[wasm_bindgen(getter)]
+    pub fn haptics(&self) -> bool {
+        self.haptics
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_haptics(&mut self, haptics: bool) {
+        self.haptics = haptics;
+    }
+
+    /// Whether to offer reading every new count aloud, in browsers that can.
+    #[wasm_bindgen(getter)]
+    pub fn speech(&self) -> bool {
+        self.speech
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_speech(&mut self, speech: bool) {
+        self.speech = speech;
+    }
+
+    /// If set, show the history this many entries a page at a time, instead of in one long
+    /// scrolling list.
+    #[wasm_bindgen(getter)]
+    pub fn history_page_size(&self) -> Option<u32> {
+        self.history_page_size
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_history_page_size(&mut self, size: Option<u32>) {
+        self.history_page_size = size;
+    }
+
+    /// If set, count down from this many, a second at a time, and stop at zero.
+    #[wasm_bindgen(getter)]
+    pub fn countdown(&self) -> Option<u32> {
+        self.countdown
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_countdown(&mut self, start: Option<u32>) {
+        self.countdown = start;
+    }
+
+    /// Keep the tab shown in the url's hash, so it can be linked to and the back button goes back
+    /// to the tab before.
+    #[wasm_bindgen(getter)]
+    pub fn hash_routing(&self) -> bool {
+        self.hash_routing
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_hash_routing(&mut self, hash_routing: bool) {
+        self.hash_routing = hash_routing;
+    }
+
+    /// If set, go idle after this many milliseconds without the mouse moving or a key being
+    /// pressed. Auto-increment pauses and the counter dims until the next one.
+    #[wasm_bindgen(getter)]
+    pub fn idle_timeout(&self) -> Option<u32> {
+        self.idle_timeout
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_idle_timeout(&mut self, timeout: Option<u32>) {
+        self.idle_timeout = timeout;
+    }
+
+    /// Show an overlay with how much memory the app is using, for seeing the effect of changes
+    /// while it runs.
+    #[wasm_bindgen(getter)]
+    pub fn diagnostics(&self) -> bool {
+        self.diagnostics
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_diagnostics(&mut self, diagnostics: bool) {
+        self.diagnostics = diagnostics;
+    }
+}
+
+impl AppConfig {
+    /// Override settings with the `data-*` attributes found on the given element.
+    ///
+    /// ```html
+    /// <main data-initial-count="5" data-step="2"></main>
+    /// ```
+    pub(crate) fn with_data_attributes(mut self, element: &web_sys::Element) -> Self {
+        for name in DATA_ATTRIBUTES {
+            if let Some(value) = element.get_attribute(name) {
+                if let Err(e) = self.set_attribute(name, &value) {
+                    warn!("ignoring {}: {}", name, e);
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Override settings with initial state provided by the server.
+    pub(crate) fn with_initial_state(mut self, state: &InitialState) -> Self {
+        if let Some(count) = state.count {
+            self.initial_count = count;
+        }
+        if let Some(step) = state.step {
+            self.step = step;
+        }
+
+        self
+    }
+
+    /// Apply a single `data-*` attribute.
+    fn set_attribute(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "data-initial-count" => self.initial_count = parse(value)?,
+            "data-step" => self.step = parse(value)?,
+            "data-storage-key" => self.storage_key = Some(value.to_owned()),
+            "data-auto-increment" => self.auto_increment = Some(parse(value)?),
+            "data-count-url" => self.count_url = Some(value.to_owned()),
+            "data-save-url" => self.save_url = Some(value.to_owned()),
+            "data-max-retries" => self.max_retries = parse(value)?,
+            "data-socket-url" => self.socket_url = Some(value.to_owned()),
+            "data-events-url" => self.events_url = Some(value.to_owned()),
+            // a bare `data-sync-tabs` attribute turns it on
+            "data-sync-tabs" => self.sync_tabs = value.trim() != "false",
+            "data-binary-sync" => self.binary_sync = value.trim() != "false",
+            "data-login-url" => self.login_url = Some(value.to_owned()),
+            "data-remember-login" => self.remember_login = value.trim() != "false",
+            "data-milestones" => {
+                self.milestones = value.split(',')
+                    .map(parse)
+                    .collect::<Result<_, _>>()?;
+            }
+            "data-sounds" => self.sounds = value.trim() != "false",
+            "data-haptics" => self.haptics = value.trim() != "false",
+            "data-speech" => self.speech = value.trim() != "false",
+            "data-history-page-size" => self.history_page_size = Some(parse(value)?),
+            "data-countdown" => self.countdown = Some(parse(value)?),
+            "data-hash-routing" => self.hash_routing = value.trim() != "false",
+            "data-idle-timeout" => self.idle_timeout = Some(parse(value)?),
+            "data-diagnostics" => self.diagnostics = value.trim() != "false",
+            _ => return Err(format!("unknown attribute {}", name)),
+        }
+
+        Ok(())
+    }
+}
+
+fn parse<T>(value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.trim()
+        .parse()
+        .map_err(|e| format!("invalid value {:?}: {}", value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_attributes() {
+        let mut config = AppConfig::new();
+        config.set_attribute("data-initial-count", "5").unwrap();
+        config.set_attribute("data-step", " 2 ").unwrap();
+        config.set_attribute("data-auto-increment", "500").unwrap();
+        config.set_attribute("data-sync-tabs", "").unwrap();
+        config.set_attribute("data-milestones", "10, 100,1000").unwrap();
+
+        assert_eq!(config.initial_count(), 5);
+        assert_eq!(config.step(), 2);
+        assert_eq!(config.auto_increment(), Some(500));
+        assert!(config.sync_tabs());
+        assert_eq!(config.milestones(), vec![10, 100, 1000]);
+    }
+
+    #[test]
+    fn invalid_data_attribute() {
+        let mut config = AppConfig::new();
+        assert!(config.set_attribute("data-step", "two").is_err());
+        assert!(config.set_attribute("data-auto-increment", "-1").is_err());
+        assert_eq!(config, AppConfig::default());
+    }
+}
@@ -0,0 +1,108 @@
+//! A PN-counter, a count that can be changed in several places at once and merged.
+//!
+//! Each replica (a tab, say) only ever adds to its own totals of increments and decrements.
+//! Merging takes the largest total seen for every replica, so replicas that have seen the same
+//! changes agree on the count no matter what order they saw them in, or how many times.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PnCounter {
+    /// The total of each replica's increments.
+    #[serde(rename = "p")]
+    increments: BTreeMap<String, u64>,
+    /// The total of each replica's decrements.
+    #[serde(rename = "n")]
+    decrements: BTreeMap<String, u64>,
+}
+
+impl PnCounter {
+    /// Record a change made by the given replica.
+    pub fn add(&mut self, replica: &str, delta: i64) {
+        let totals = match delta {
+            0 => return,
+            d if d > 0 => &mut self.increments,
+            _ => &mut self.decrements,
+        };
+
+        let total = totals.entry(replica.to_owned()).or_insert(0);
+        *total = total.saturating_add(delta.unsigned_abs());
+    }
+
+    /// The sum of every replica's changes.
+    pub fn value(&self) -> i64 {
+        let increments: u64 = self.increments.values().sum();
+        let decrements: u64 = self.decrements.values().sum();
+        (increments as i64).wrapping_sub(decrements as i64)
+    }
+
+    /// Merge in the changes another copy of the counter has seen.
+    pub fn merge(&mut self, other: &PnCounter) {
+        merge_totals(&mut self.increments, &other.increments);
+        merge_totals(&mut self.decrements, &other.decrements);
+    }
+}
+
+fn merge_totals(totals: &mut BTreeMap<String, u64>, other: &BTreeMap<String, u64>) {
+    for (replica, &theirs) in other {
+        let ours = totals.entry(replica.clone()).or_insert(0);
+        *ours = (*ours).max(theirs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter(changes: &[(&str, i64)]) -> PnCounter {
+        let mut counter = PnCounter::default();
+        for &(replica, delta) in changes {
+            counter.add(replica, delta);
+        }
+        counter
+    }
+
+    fn merged(a: &PnCounter, b: &PnCounter) -> PnCounter {
+        let mut merged = a.clone();
+        merged.merge(b);
+        merged
+    }
+
+    #[test]
+    fn value() {
+        assert_eq!(counter(&[("a", 3), ("a", -1), ("b", -5), ("b", 0)]).value(), -3);
+    }
+
+    #[test]
+    fn concurrent_changes_kept() {
+        let a = counter(&[("a", 2)]);
+        let b = counter(&[("b", -1)]);
+        assert_eq!(merged(&a, &b).value(), 1);
+    }
+
+    #[test]
+    fn merge_commutative() {
+        let a = counter(&[("a", 2), ("b", 1)]);
+        let b = counter(&[("b", 4), ("c", -3)]);
+        assert_eq!(merged(&a, &b), merged(&b, &a));
+    }
+
+    #[test]
+    fn merge_associative() {
+        let a = counter(&[("a", 2)]);
+        let b = counter(&[("b", -4), ("a", 1)]);
+        let c = counter(&[("c", 7), ("b", -1)]);
+        assert_eq!(merged(&merged(&a, &b), &c), merged(&a, &merged(&b, &c)));
+    }
+
+    #[test]
+    fn merge_idempotent() {
+        let a = counter(&[("a", 2), ("b", -1)]);
+        assert_eq!(merged(&a, &a), a);
+
+        let b = counter(&[("b", 3)]);
+        let once = merged(&a, &b);
+        assert_eq!(merged(&once, &b), once);
+    }
+}
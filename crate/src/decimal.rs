@@ -0,0 +1,171 @@
+//! Counting in fractions of one without the drift floats would bring.
+//!
+//! The count is fixed-point: it's kept as a whole number of units, each `10^-places` of one, and
+//! every step is a whole number of those units too. A step of 0.1 is 1 unit at one decimal place,
+//! so ten of them land on exactly 1.0. Only what's shown to the user and typed in by them has a
+//! decimal point, storage, the server and the page's scripts all see the units.
+
+use std::cmp::Ordering;
+use crate::Count;
+
+/// The most decimal places the count can have.
+pub const MAX_PLACES: u32 = 3;
+
+/// How many units make one, at `places` decimal places.
+pub fn scale(places: u32) -> Count {
+    Count::pow(10, places)
+}
+
+/// Write out `units` with `places` decimal places, the whole part written by `whole` and the
+/// fraction after `point`.
+pub fn join(units: Count, places: u32, whole: impl Fn(Count) -> String, point: char) -> String {
+    if places == 0 {
+        return whole(units);
+    }
+
+    let scale = scale(places);
+    // the whole part of -0.25 is 0, which has no sign to show
+    let sign = if units < 0 && units / scale == 0 { "-" } else { "" };
+    let fraction = (units % scale).unsigned_abs();
+    let width = places as usize;
+    format!("{}{}{}{:0width$}", sign, whole(units / scale), point, fraction, width = width)
+}
+
+/// `units` with `places` decimal places as typed into a text box, like `-1234.50`.
+pub fn plain(units: Count, places: u32) -> String {
+    join(units, places, |whole| whole.to_string(), '.')
+}
+
+/// `units` at `from` decimal places in units at `to` decimal places, rounding half away from
+/// zero when there are fewer places. `None` if there's no room for that many places.
+pub fn rescale(units: Count, from: u32, to: u32) -> Option<Count> {
+    match to.cmp(&from) {
+        Ordering::Equal => Some(units),
+        Ordering::Greater => units.checked_mul(scale(to - from)),
+        Ordering::Less => {
+            let scale = scale(from - to);
+            let (whole, rest) = (units / scale, units % scale);
+            match rest.unsigned_abs() * 2 >= scale.unsigned_abs() {
+                true => Some(whole + units.signum()),
+                false => Some(whole),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate;
+
+    /// How many random cases each property is checked against.
+    const CASES: usize = 10_000;
+
+    /// Pseudo random numbers from a fixed seed, so a failing case fails every time.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            // xorshift64
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// A count of any size, though mostly large ones.
+        fn count(&mut self) -> Count {
+            self.next() as i64 as Count
+        }
+
+        /// A count small enough to add up without overflowing.
+        fn small(&mut self) -> Count {
+            (self.next() % 20_001) as Count - 10_000
+        }
+
+        fn places(&mut self) -> u32 {
+            (self.next() % u64::from(MAX_PLACES + 1)) as u32
+        }
+    }
+
+    #[test]
+    fn written_out() {
+        assert_eq!(plain(125, 2), "1.25");
+        assert_eq!(plain(-125, 2), "-1.25");
+        assert_eq!(plain(-25, 2), "-0.25");
+        assert_eq!(plain(5, 3), "0.005");
+        assert_eq!(plain(-7, 0), "-7");
+        #[cfg(not(feature = "wide"))]
+        assert_eq!(plain(Count::MIN, 3), "-2147483.648");
+    }
+
+    #[test]
+    fn rescaled() {
+        assert_eq!(rescale(125, 2, 3), Some(1250));
+        assert_eq!(rescale(125, 2, 1), Some(13));
+        assert_eq!(rescale(-125, 2, 1), Some(-13));
+        assert_eq!(rescale(124, 2, 0), Some(1));
+        assert_eq!(rescale(-150, 2, 0), Some(-2));
+        assert_eq!(rescale(Count::MAX, 0, 1), None);
+        assert_eq!(rescale(Count::MAX, 3, 0), Some(Count::MAX / 1000 + 1));
+    }
+
+    #[test]
+    fn parsed_back_exactly() {
+        let mut random = Random(0x5eed);
+        for _ in 0..CASES {
+            let (units, places) = (random.count(), random.places());
+            let text = plain(units, places);
+            assert_eq!(validate::count(&text, false, places), Ok(units), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn steps_add_up_without_drift() {
+        let mut random = Random(0xd41f7);
+        for _ in 0..CASES {
+            let (start, step, places) = (random.small(), random.small(), random.places());
+            let clicks = random.next() % 100;
+            let text = plain(step, places);
+            let step = validate::count(&text, false, places).unwrap();
+
+            let mut count = start;
+            for _ in 0..clicks {
+                count += step;
+            }
+            assert_eq!(count, start + step * clicks as Count, "{} + {} * {}", start, text, clicks);
+        }
+
+        // the example floats get wrong, 0.1 + 0.2 is 0.30000000000000004 as an f64
+        let tenth = validate::count("0.1", false, 1).unwrap();
+        assert_eq!(plain(tenth + 2 * tenth, 1), "0.3");
+    }
+
+    #[test]
+    fn more_places_lose_nothing() {
+        let mut random = Random(0xace);
+        for _ in 0..CASES {
+            let (units, from) = (random.small(), random.places());
+            let to = from + random.places() % (MAX_PLACES - from + 1);
+            let finer = rescale(units, from, to).unwrap();
+            assert_eq!(rescale(finer, to, from), Some(units));
+            // the same text stands for the same count
+            assert_eq!(validate::count(&plain(units, from), false, to), Ok(finer));
+        }
+    }
+
+    #[test]
+    fn fewer_places_round_to_nearest() {
+        let mut random = Random(0xf00d);
+        for _ in 0..CASES {
+            let (units, to) = (random.count() / 2, random.places());
+            let from = to + random.places() % (MAX_PLACES - to + 1);
+            let scale = scale(from - to);
+            let rounded = rescale(units, from, to).unwrap();
+            let error = rounded * scale - units;
+            assert!(error.abs() * 2 <= scale, "{} rounded to {}", units, rounded);
+            // halves round away from zero, so the error is never towards zero by a half
+            assert!(units == 0 || error * 2 != -scale * units.signum());
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Numbers about the running app for the diagnostics overlay, shown with `data-diagnostics`.
+//!
+//! The overlay is for seeing what changes do at runtime, e.g. how much memory the `wee_alloc`
+//! allocator saves, so it's sampled on an interval rather than kept exactly up to date. While
+//! something is animating it also shows the frame rate, worked out from the animation frames'
+//! own timestamps.
+
+use std::collections::VecDeque;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+
+/// How much memory the app is using, in bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Memory {
+    /// The size of the wasm module's linear memory. It only ever grows.
+    pub linear: u64,
+    /// The JavaScript heap in use and its total size, from `performance.memory` in browsers that
+    /// have it.
+    pub heap: Option<(u64, u64)>,
+}
+
+/// How much memory the app is using now.
+pub fn memory() -> Memory {
+    let linear = wasm_bindgen::memory()
+        .dyn_into::<js_sys::WebAssembly::Memory>()
+        .ok()
+        .and_then(|memory| memory.buffer().dyn_into::<js_sys::ArrayBuffer>().ok())
+        .map_or(0, |buffer| u64::from(buffer.byte_length()));
+
+    // `performance.memory` isn't standard, so there's no binding for it
+    let heap = web_sys::window()
+        .and_then(|window| property(&window, "performance"))
+        .and_then(|performance| property(&performance, "memory"))
+        .and_then(|memory| {
+            let bytes = |name| property(&memory, name)?.as_f64().map(|bytes| bytes as u64);
+            Some((bytes("usedJSHeapSize")?, bytes("totalJSHeapSize")?))
+        });
+
+    Memory { linear, heap }
+}
+
+/// The property `name` of `target`, if it's there.
+fn property(target: &JsValue, name: &str) -> Option<JsValue> {
+    js_sys::Reflect::get(target, &JsValue::from_str(name))
+        .ok()
+        .filter(|value| !value.is_undefined() && !value.is_null())
+}
+
+/// Milliseconds of frames the frame rate is worked out over.
+const WINDOW: f64 = 1000.0;
+
+/// The frame rate over the last second of animation frames.
+#[derive(Debug, Default)]
+pub struct Fps {
+    /// The times of the frames in the last second, oldest first.
+    frames: VecDeque<f64>,
+}
+
+impl Fps {
+    /// Note a frame at `time`, from `requestAnimationFrame`.
+    pub fn frame(&mut self, time: f64) {
+        self.frames.push_back(time);
+        while self.frames.front().is_some_and(|&first| time - first > WINDOW) {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Forget the frames so far, the animation stopped and the next one starts afresh.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Frames per second, once there are two frames to go on.
+    pub fn rate(&self) -> Option<f64> {
+        let (first, last) = (self.frames.front()?, self.frames.back()?);
+        if last <= first {
+            return None;
+        }
+        Some((self.frames.len() - 1) as f64 * 1000.0 / (last - first))
+    }
+}
+
+/// `bytes` in megabytes to one decimal place.
+pub fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_megabytes() {
+        assert_eq!(megabytes(0), "0.0 MB");
+        assert_eq!(megabytes(17 * 64 * 1024), "1.1 MB");
+        assert_eq!(megabytes(16 * 1024 * 1024), "16.0 MB");
+    }
+
+    #[test]
+    fn frame_rate() {
+        let mut fps = Fps::default();
+        assert_eq!(fps.rate(), None);
+        fps.frame(1000.0);
+        assert_eq!(fps.rate(), None);
+
+        for frame in 1..=60 {
+            fps.frame(1000.0 + f64::from(frame) * 1000.0 / 60.0);
+        }
+        assert_eq!(fps.rate().map(f64::round), Some(60.0));
+
+        // only the last second counts, a slow patch shows right away
+        for frame in 1..=30 {
+            fps.frame(2000.0 + f64::from(frame) * 1000.0 / 30.0);
+        }
+        assert_eq!(fps.rate().map(f64::round), Some(30.0));
+
+        fps.clear();
+        assert_eq!(fps.rate(), None);
+    }
+}
@@ -0,0 +1,50 @@
+//! Offering files the app makes for download.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use log::error;
+use crate::timer;
+
+/// Download `data` as a file called `name`.
+pub fn save(name: &str, mime: &str, data: &str) {
+    if let Err(e) = try_save(name, mime, data) {
+        error!("error downloading {}: {:?}", name, e);
+    }
+}
+
+/// Download `blob` as a file called `name`.
+pub fn save_blob(name: &str, blob: &web_sys::Blob) {
+    if let Err(e) = try_save_blob(name, blob) {
+        error!("error downloading {}: {:?}", name, e);
+    }
+}
+
+fn try_save(name: &str, mime: &str, data: &str) -> Result<(), JsValue> {
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+    let parts = js_sys::Array::of1(&data.into());
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    try_save_blob(name, &blob)
+}
+
+/// Click a temporary link to the blob, which is how to download something without a server.
+fn try_save_blob(name: &str, blob: &web_sys::Blob) -> Result<(), JsValue> {
+    let url = web_sys::Url::create_object_url_with_blob(blob)?;
+
+    let link: web_sys::HtmlAnchorElement = crate::document()?
+        .create_element("a")?
+        .dyn_into()
+        .map_err(JsValue::from)?;
+    link.set_href(&url);
+    link.set_download(name);
+    link.click();
+
+    // the download may not have started by the time click() returns
+    timer::after(0, move || {
+        if let Err(e) = web_sys::Url::revoke_object_url(&url) {
+            error!("error revoking {}: {:?}", url, e);
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,248 @@
+//! The counter as a `<euca-counter>` custom element.
+//!
+//! ```html
+//! <euca-counter count="10" step="5"></euca-counter>
+//! <euca-counter shadow></euca-counter>
+//! ```
+//!
+//! Each element gets its own app, attached when the element is connected to the document and
+//! detached when it is removed. The `count` and `step` attributes are observed, changing them
+//! updates the counter, and they are kept up to date as the counter changes. With the `shadow`
+//! attribute the app renders into the element's shadow root. The element's `counter` property
+//! holds a `CounterHandle` while it is connected. Elements without an `id` share a stash key, give
+//! them one if each should restore its own count across reloads.
+
+use wasm_bindgen::prelude::*;
+use euca::app::Detach;
+use log::{error, warn};
+use std::cell::RefCell;
+use crate::{App, AppConfig, CounterHandle, Msg, STASH_KEY};
+use crate::handle::Shared;
+
+/// The tag name of the custom element.
+pub const TAG: &str = "euca-counter";
+
+/// The attributes mapped to messages.
+const OBSERVED: &[&str] = &["count", "step"];
+
+#[wasm_bindgen(inline_js = r#"
+export function define_custom_element(name, observed, connected, disconnected, changed) {
+    if (customElements.get(name)) {
+        return;
+    }
+
+    customElements.define(name, class extends HTMLElement {
+        static get observedAttributes() {
+            return observed;
+        }
+
+        connectedCallback() {
+            connected(this);
+        }
+
+        disconnectedCallback() {
+            disconnected(this);
+        }
+
+        attributeChangedCallback(attr, old_value, new_value) {
+            changed(this, attr, new_value);
+        }
+    });
+}
+"#)]
+extern "C" {
+    #[wasm_bindgen(catch)]
+    fn define_custom_element(
+        name: &str,
+        observed: js_sys::Array,
+        connected: &Closure<dyn FnMut(web_sys::HtmlElement)>,
+        disconnected: &Closure<dyn FnMut(web_sys::HtmlElement)>,
+        changed: &Closure<dyn FnMut(web_sys::HtmlElement, String, Option<String>)>,
+    ) -> Result<(), JsValue>;
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_ELEMENT: &str = r#"
+declare global {
+    /** A `<euca-counter>` element, see `define_element()`. */
+    interface EucaCounterElement extends HTMLElement {
+        /** A handle to the element's counter, present while the element is connected. */
+        readonly counter?: CounterHandle;
+    }
+
+    interface HTMLElementTagNameMap {
+        "euca-counter": EucaCounterElement;
+    }
+}
+"#;
+
+/// An app attached to a custom element.
+struct Instance {
+    host: web_sys::Element,
+    container: web_sys::Element,
+    app: App,
+    shared: Shared,
+}
+
+thread_local! {
+    static INSTANCES: RefCell<Vec<Instance>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Define the `<euca-counter>` custom element.
+///
+/// Defining it more than once has no effect.
+#[wasm_bindgen]
+pub fn define_element() -> Result<(), JsValue> {
+    crate::setup()?;
+
+    let connected = Closure::wrap(Box::new(|host: web_sys::HtmlElement| {
+        if let Err(e) = connect(host.into()) {
+            error!("error attaching <{}>: {:?}", TAG, e);
+        }
+    }) as Box<dyn FnMut(web_sys::HtmlElement)>);
+
+    let disconnected = Closure::wrap(Box::new(|host: web_sys::HtmlElement| {
+        disconnect(&host.into());
+    }) as Box<dyn FnMut(web_sys::HtmlElement)>);
+
+    let changed = Closure::wrap(Box::new(
+        |host: web_sys::HtmlElement, name: String, value: Option<String>| {
+            attribute_changed(&host.into(), &name, value.as_deref());
+        }
+    ) as Box<dyn FnMut(web_sys::HtmlElement, String, Option<String>)>);
+
+    let observed = OBSERVED.iter()
+        .map(|name| JsValue::from_str(name))
+        .collect();
+
+    define_custom_element(TAG, observed, &connected, &disconnected, &changed)?;
+
+    // the element definition lives as long as the page
+    connected.forget();
+    disconnected.forget();
+    changed.forget();
+
+    Ok(())
+}
+
+fn connect(host: web_sys::Element) -> Result<(), JsValue> {
+    if crate::boundary::is_poisoned() {
+        return Err("the app crashed".into());
+    }
+
+    let document = host.owner_document()
+        .ok_or("element has no document")?;
+
+    // render into a container so the app has an element to attach to, even in a shadow root
+    let container = document.create_element("div")?;
+    if host.has_attribute("shadow") {
+        let root = match host.shadow_root() {
+            Some(root) => root,
+            None => {
+                let init = web_sys::ShadowRootInit::new(web_sys::ShadowRootMode::Open);
+                host.attach_shadow(&init)?
+            }
+        };
+        root.append_child(&container)?;
+    }
+    else {
+        host.append_child(&container)?;
+    }
+
+    let stash_key = match host.id().as_str() {
+        "" => format!("{}:{}", STASH_KEY, TAG),
+        id => format!("{}:{}#{}", STASH_KEY, TAG, id),
+    };
+
+    // attributes set before the element was connected configure the counter
+    let mut config = AppConfig::default();
+    for name in OBSERVED {
+        match host.get_attribute(name).as_deref().map(|value| attribute_msg(name, value)) {
+            Some(Some(Msg::SetCount(count))) => config.set_initial_count(count),
+            Some(Some(Msg::SetStep(step))) => config.set_step(step),
+            _ => {}
+        }
+    }
+
+    let (app, shared) = crate::mount(&host, container.clone(), stash_key, &config);
+    shared.enable_reflection();
+    js_sys::Reflect::set(&host, &"counter".into(), &CounterHandle::new(shared.clone()).into())?;
+
+    INSTANCES.with(|instances| instances.borrow_mut().push(Instance {
+        host,
+        container,
+        app,
+        shared,
+    }));
+
+    Ok(())
+}
+
+fn disconnect(host: &web_sys::Element) {
+    let instance = INSTANCES.with(|instances| {
+        let mut instances = instances.borrow_mut();
+        let i = instances.iter().position(|instance| instance.host == *host)?;
+        Some(instances.remove(i))
+    });
+
+    let Instance { host, container, app, shared } = match instance {
+        Some(instance) => instance,
+        None => return,
+    };
+
+    shared.detach();
+    app.detach();
+    crate::boundary::unwatch(&container);
+    container.remove();
+
+    if let Err(e) = js_sys::Reflect::delete_property(&host, &"counter".into()) {
+        error!("error removing counter handle from <{}>: {:?}", TAG, e);
+    }
+}
+
+fn attribute_changed(host: &web_sys::Element, name: &str, value: Option<&str>) {
+    // removing an attribute leaves the counter as is
+    let msg = match value.and_then(|value| attribute_msg(name, value)) {
+        Some(msg) => msg,
+        None => return,
+    };
+
+    // this is called for the initial attributes before the element is connected, those are
+    // handled when connecting
+    let shared = INSTANCES.with(|instances| {
+        instances.borrow()
+            .iter()
+            .find(|instance| instance.host == *host)
+            .map(|instance| instance.shared.clone())
+    });
+
+    if let Some(shared) = shared {
+        shared.dispatch(msg);
+    }
+}
+
+/// Translate an observed attribute into a message for the counter.
+fn attribute_msg(name: &str, value: &str) -> Option<Msg> {
+    let msg = match name {
+        "count" => value.trim().parse().map(Msg::SetCount),
+        "step" => value.trim().parse().map(Msg::SetStep),
+        _ => return None,
+    };
+    if msg.is_err() {
+        warn!("ignoring invalid {} attribute on <{}>: {:?}", name, TAG, value);
+    }
+    msg.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_to_messages() {
+        assert_eq!(attribute_msg("count", "10"), Some(Msg::SetCount(10)));
+        assert_eq!(attribute_msg("step", " 5"), Some(Msg::SetStep(5)));
+        assert_eq!(attribute_msg("step", "five"), None);
+        assert_eq!(attribute_msg("id", "3"), None);
+    }
+}
@@ -0,0 +1,75 @@
+//! Receiving counts set elsewhere from a server-sent event stream.
+//!
+//! Each event's data is a count, as a bare number or a JSON object with a `count` field. Unlike a
+//! WebSocket the stream only goes one way, changes made here are saved to the server separately.
+
+use wasm_bindgen::JsCast;
+use log::{error, warn};
+use crate::Msg;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+use crate::sync;
+
+/// An open event stream, closed when dropped.
+pub struct Stream {
+    source: web_sys::EventSource,
+    _listeners: Vec<EventListener>,
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        self.source.close();
+    }
+}
+
+/// Subscribe to the event stream at `url`, sending `Msg::Subscribed`, `Msg::RemoteSet`, and
+/// `Msg::Unsubscribed` to the app as things happen.
+///
+/// The stream replaces any stream the app had before. The browser's own reconnection is disabled,
+/// the app decides when to subscribe again.
+pub fn subscribe(shared: Shared, url: String) {
+    let source = match web_sys::EventSource::new(&url) {
+        Ok(source) => source,
+        Err(e) => {
+            error!("error subscribing to {}: {:?}", url, e);
+            shared.dispatch(Msg::Unsubscribed);
+            return;
+        }
+    };
+
+    let on_open = {
+        let shared = shared.clone();
+        EventListener::new(&source, "open", move |_| shared.dispatch(Msg::Subscribed))
+    };
+
+    let on_message = {
+        let shared = shared.clone();
+        EventListener::new(&source, "message", move |event| {
+            let data = event.dyn_into::<web_sys::MessageEvent>()
+                .ok()
+                .and_then(|event| event.data().as_string());
+
+            match data.as_deref().map(sync::parse_count) {
+                Some(Ok(count)) => shared.dispatch(Msg::RemoteSet(count)),
+                Some(Err(e)) => warn!("ignoring event from {}: {}", url, e),
+                None => warn!("ignoring non-text event from {}", url),
+            }
+        })
+    };
+
+    let on_error = {
+        let (shared, source) = (shared.clone(), source.clone());
+        EventListener::new(&source.clone(), "error", move |_| {
+            source.close();
+            shared.dispatch(Msg::Unsubscribed);
+        })
+    };
+
+    match (on_open, on_message, on_error) {
+        (Ok(on_open), Ok(on_message), Ok(on_error)) => shared.set_stream(Stream {
+            source,
+            _listeners: vec![on_open, on_message, on_error],
+        }),
+        _ => error!("error listening to event stream"),
+    }
+}
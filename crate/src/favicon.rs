@@ -0,0 +1,90 @@
+//! A favicon badge showing the count, so it can be seen from other tabs.
+//!
+//! The count is drawn on an offscreen canvas and the page's icon link is pointed at the resulting
+//! data url. Pages without an icon link get one.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use log::error;
+use crate::Count;
+use crate::decimal;
+
+/// The width and height of the badge in pixels.
+const SIZE: u32 = 32;
+
+/// Show the given count, in units at `places` decimal places, as the page's favicon.
+pub fn show(count: Count, places: u32) {
+    if let Err(e) = draw(count, places).and_then(|url| set_icon(&url)) {
+        error!("error setting favicon: {:?}", e);
+    }
+}
+
+/// Draw the badge and return it as a data url.
+fn draw(count: Count, places: u32) -> Result<String, JsValue> {
+    let document = crate::document()?;
+    let canvas: web_sys::HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+    canvas.set_width(SIZE);
+    canvas.set_height(SIZE);
+
+    let context: web_sys::CanvasRenderingContext2d = canvas.get_context("2d")?
+        .ok_or("canvas has no 2d context")?
+        .dyn_into()?;
+
+    let center = f64::from(SIZE) / 2.0;
+    context.set_fill_style_str("#d33");
+    context.begin_path();
+    context.arc(center, center, center, 0.0, std::f64::consts::TAU)?;
+    context.fill();
+
+    let text = decimal::plain(count, places);
+    context.set_fill_style_str("#fff");
+    context.set_font(&format!("bold {}px sans-serif", font_size(&text)));
+    context.set_text_align("center");
+    context.set_text_baseline("middle");
+    context.fill_text(&text, center, center + 1.0)?;
+
+    canvas.to_data_url()
+}
+
+/// Point the page's icon link at the given url, adding a link if there isn't one.
+fn set_icon(url: &str) -> Result<(), JsValue> {
+    let document = crate::document()?;
+    let link = match document.query_selector("link[rel~='icon']")? {
+        Some(link) => link,
+        None => {
+            let link = document.create_element("link")?;
+            link.set_attribute("rel", "icon")?;
+            document.query_selector("head")?
+                .ok_or("document has no head")?
+                .append_child(&link)?;
+            link
+        }
+    };
+
+    link.set_attribute("href", url)
+}
+
+/// The font size in pixels that fits the given text on the badge.
+fn font_size(text: &str) -> u32 {
+    match text.len() {
+        0..=2 => 20,
+        3 => 15,
+        4 => 11,
+        _ => 8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longer_counts_get_smaller() {
+        assert_eq!(font_size("7"), 20);
+        assert_eq!(font_size("-7"), 20);
+        assert_eq!(font_size("100"), 15);
+        assert_eq!(font_size("-100"), 11);
+        assert_eq!(font_size("-2147483648"), 8);
+        assert_eq!(font_size(&decimal::plain(125, 2)), 11);
+    }
+}
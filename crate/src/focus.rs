@@ -0,0 +1,146 @@
+//! Keeping focus where it was when a render replaces the focused element.
+//!
+//! Patching replaces an element when the one rendered in its place is a different kind of
+//! element, which drops focus (and the caret, for inputs) on the floor. Elements that should keep
+//! focus are given a `data-focus` key, and after rendering, if the focused element was replaced,
+//! focus moves to the element with the same key along with the input's selection.
+//!
+//! The keys also let commands find an element to act on after rendering, like scrolling it into
+//! view.
+
+use wasm_bindgen::JsCast;
+use log::{error, warn};
+use crate::handle::Shared;
+use crate::listener::EventListener;
+
+/// The attribute identifying an element focus should follow across renders.
+pub const KEY: &str = "data-focus";
+
+/// Remember the element focused within the app's host element.
+pub fn track(shared: Shared) {
+    let host = match shared.element() {
+        Some(host) => host,
+        None => {
+            warn!("no host element to track focus in");
+            return;
+        }
+    };
+
+    let listener = {
+        let shared = shared.clone();
+        EventListener::new(&host, "focusin", move |event| {
+            // focus in a shadow root is retargeted to the host, the real target is first in the
+            // composed path
+            let target = event.composed_path()
+                .get(0)
+                .dyn_into::<web_sys::Element>()
+                .ok();
+            shared.set_focused(target);
+        })
+    };
+
+    match listener {
+        Ok(listener) => shared.listen(listener),
+        Err(e) => error!("error tracking focus: {:?}", e),
+    }
+}
+
+/// Focus the replacement for the focused element, if the last render replaced it.
+pub fn restore(shared: &Shared) {
+    let (focused, host) = match (shared.focused(), shared.element()) {
+        (Some(focused), Some(host)) => (focused, host),
+        _ => return,
+    };
+
+    // still there, nothing to do
+    if focused.is_connected() {
+        return;
+    }
+
+    // the user moved on while we weren't looking
+    let document = match crate::document() {
+        Ok(document) => document,
+        Err(_) => return,
+    };
+    let active = document.active_element();
+    if active.is_some() && active != document.body().map(Into::into) {
+        return;
+    }
+
+    let key = match focused.get_attribute(KEY) {
+        Some(key) => key,
+        None => return,
+    };
+
+    let selector = format!("[{}={:?}]", KEY, key);
+    let replacement = match query(&root(host), &selector) {
+        Some(replacement) => replacement,
+        None => return,
+    };
+
+    if let Err(e) = replacement.focus() {
+        error!("error restoring focus: {:?}", e);
+        return;
+    }
+
+    // a detached input still knows where its caret was
+    let inputs = (
+        focused.dyn_ref::<web_sys::HtmlInputElement>(),
+        replacement.dyn_ref::<web_sys::HtmlInputElement>(),
+    );
+    if let (Some(old), Some(new)) = inputs {
+        if let (Ok(Some(start)), Ok(Some(end))) = (old.selection_start(), old.selection_end()) {
+            // not every kind of input has a selection, that's fine
+            let _ = new.set_selection_range(start, end);
+        }
+    }
+}
+
+/// Move focus to the element with the given key.
+pub fn move_to(shared: &Shared, key: &str) {
+    if let Some(element) = find(shared, key) {
+        if let Err(e) = element.focus() {
+            error!("error moving focus to {}: {:?}", key, e);
+        }
+    }
+}
+
+/// Scroll the element with the given key into view, if any of it is out of view. Smooth scrolling
+/// glides there rather than jumping.
+pub fn scroll_into_view(shared: &Shared, key: &str, smooth: bool) {
+    if let Some(element) = find(shared, key) {
+        let options = web_sys::ScrollIntoViewOptions::new();
+        // the nearest edge, which doesn't scroll at all when it's already in view
+        options.set_block(web_sys::ScrollLogicalPosition::Nearest);
+        options.set_inline(web_sys::ScrollLogicalPosition::Nearest);
+        if smooth {
+            options.set_behavior(web_sys::ScrollBehavior::Smooth);
+        }
+        element.scroll_into_view_with_scroll_into_view_options(&options);
+    }
+}
+
+/// The element in the app with the given key.
+fn find(shared: &Shared, key: &str) -> Option<web_sys::HtmlElement> {
+    let selector = format!("[{}={:?}]", KEY, key);
+    query(&root(shared.element()?), &selector)
+}
+
+/// Where the app is rendered in the host element.
+fn root(host: web_sys::Element) -> web_sys::Node {
+    match host.shadow_root() {
+        Some(root) => root.into(),
+        None => host.into(),
+    }
+}
+
+fn query(root: &web_sys::Node, selector: &str) -> Option<web_sys::HtmlElement> {
+    let found = if let Some(element) = root.dyn_ref::<web_sys::Element>() {
+        element.query_selector(selector)
+    }
+    else {
+        root.dyn_ref::<web_sys::ShadowRoot>()?.query_selector(selector)
+    };
+
+    found.ok()??.dyn_into().ok()
+}
@@ -0,0 +1,147 @@
+//! Turning pointer gestures into changes to the count.
+
+use crate::Count;
+
+/// How far a wheel has to scroll, in pixels, to change the count by one step.
+const WHEEL_STEP: f64 = 100.0;
+
+/// The most steps a single wheel event changes the count by.
+const MAX_WHEEL_STEPS: f64 = 10.0;
+
+/// How far, in pixels, a touch has to move to count as a swipe.
+const SWIPE_DISTANCE: i32 = 30;
+
+/// How far, in pixels, dragging over the count has to move to change it by one step.
+const SCRUB_STEP: i32 = 4;
+
+/// How many steps to change the count by for a wheel event.
+///
+/// Scrolling up increments. Any scroll changes the count by at least one step, a small scroll on
+/// a trackpad shouldn't be ignored. `delta_mode` is the event's `deltaMode`, saying whether
+/// `delta_y` is in pixels, lines, or pages.
+pub fn wheel_steps(delta_y: f64, delta_mode: u32) -> i32 {
+    let steps = match delta_mode {
+        // pixels
+        0 => delta_y / WHEEL_STEP,
+        // lines and pages
+        _ => delta_y,
+    };
+
+    if steps == 0.0 || steps.is_nan() {
+        return 0;
+    }
+
+    // away from zero, so any scroll counts
+    let steps = -steps.abs().ceil().min(MAX_WHEEL_STEPS).copysign(steps);
+    steps as i32
+}
+
+/// How many steps to change the count by for a touch that moved from `start` to `end`.
+///
+/// Swiping right or up increments and swiping left or down decrements, whichever way the touch
+/// moved furthest. Touches that barely moved are taps, not swipes.
+pub fn swipe_steps(start: (i32, i32), end: (i32, i32)) -> i32 {
+    let dx = end.0 - start.0;
+    // screen coordinates grow downwards
+    let dy = start.1 - end.1;
+
+    let distance = if dx.abs() >= dy.abs() { dx } else { dy };
+    if distance.abs() < SWIPE_DISTANCE {
+        0
+    }
+    else {
+        distance.signum()
+    }
+}
+
+/// A drag over the count, scrubbing it like a dial.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scrub {
+    pub pointer: i32,
+    /// Where the drag started.
+    pub x: i32,
+    /// The count when the drag started.
+    pub count: Count,
+}
+
+impl Scrub {
+    /// How many steps from the starting count the drag has moved, dragging right increments.
+    pub fn steps(&self, x: i32) -> i32 {
+        (x - self.x) / SCRUB_STEP
+    }
+}
+
+/// Two fingers pinching the count, growing or shrinking the step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pinch {
+    /// How far apart the fingers started.
+    pub distance: f64,
+    /// The step when the pinch started.
+    pub step: i32,
+}
+
+impl Pinch {
+    /// The step for fingers `distance` apart, in proportion to how far they've spread.
+    ///
+    /// The step keeps its sign, and never shrinks to nothing.
+    pub fn step(&self, distance: f64) -> i32 {
+        if self.distance <= 0.0 {
+            return self.step;
+        }
+
+        let scaled = (f64::from(self.step.abs()) * distance / self.distance).round();
+        scaled.clamp(1.0, f64::from(i32::MAX)) as i32 * self.step.signum()
+    }
+}
+
+/// The distance between two points.
+pub fn distance(a: (i32, i32), b: (i32, i32)) -> f64 {
+    f64::from(a.0 - b.0).hypot(f64::from(a.1 - b.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinch() {
+        let pinch = Pinch { distance: 100.0, step: 2 };
+        assert_eq!(pinch.step(100.0), 2);
+        assert_eq!(pinch.step(300.0), 6);
+        assert_eq!(pinch.step(10.0), 1);
+
+        let pinch = Pinch { distance: 50.0, step: -3 };
+        assert_eq!(pinch.step(100.0), -6);
+
+        assert_eq!(distance((0, 0), (3, 4)), 5.0);
+    }
+
+    #[test]
+    fn scrub() {
+        let scrub = Scrub { pointer: 1, x: 100, count: 5 };
+        assert_eq!(scrub.steps(100), 0);
+        assert_eq!(scrub.steps(103), 0);
+        assert_eq!(scrub.steps(140), 10);
+        assert_eq!(scrub.steps(92), -2);
+    }
+
+    #[test]
+    fn swipe() {
+        assert_eq!(swipe_steps((0, 0), (100, 10)), 1);
+        assert_eq!(swipe_steps((100, 0), (0, -10)), -1);
+        assert_eq!(swipe_steps((0, 100), (10, 0)), 1);
+        assert_eq!(swipe_steps((0, 0), (-10, 60)), -1);
+        assert_eq!(swipe_steps((50, 50), (60, 40)), 0);
+    }
+
+    #[test]
+    fn wheel() {
+        assert_eq!(wheel_steps(-100.0, 0), 1);
+        assert_eq!(wheel_steps(4.0, 0), -1);
+        assert_eq!(wheel_steps(-250.0, 0), 3);
+        assert_eq!(wheel_steps(0.0, 0), 0);
+        assert_eq!(wheel_steps(-3.0, 1), 3);
+        assert_eq!(wheel_steps(-5000.0, 0), 10);
+        assert_eq!(wheel_steps(5000.0, 2), -10);
+    }
+}
@@ -0,0 +1,122 @@
+//! Loading and saving the count through a GraphQL endpoint, used instead of plain JSON requests
+//! when the `graphql` feature is enabled.
+//!
+//! The endpoint is expected to implement this schema:
+//!
+//! ```graphql
+//! type Query {
+//!     count: Int!
+//! }
+//!
+//! type Mutation {
+//!     setCount(count: Int!): Int!
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use crate::Count;
+use crate::net::{self, Request};
+
+const LOAD: &str = "query Count { count }";
+const SAVE: &str = "mutation SetCount($count: Int!) { setCount(count: $count) }";
+
+/// The body of a GraphQL request.
+#[derive(Serialize)]
+struct Operation<V> {
+    query: &'static str,
+    variables: V,
+}
+
+#[derive(Serialize)]
+struct NoVariables {}
+
+#[derive(Serialize)]
+struct SaveVariables {
+    count: Count,
+}
+
+/// The body of a GraphQL response.
+#[derive(Deserialize)]
+struct Response<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<ResponseError>,
+}
+
+#[derive(Deserialize)]
+struct ResponseError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct LoadData {
+    count: Count,
+}
+
+fn request<V: Serialize>(url: String, query: &'static str, variables: V) -> Request {
+    let body = serde_json::to_string(&Operation { query, variables })
+        .expect("error serializing graphql request");
+
+    Request::post_json(url, body)
+}
+
+/// Pull the data out of a response, or the errors the server reported instead.
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, String> {
+    let response: Response<T> = serde_json::from_str(body)
+        .map_err(|e| format!("invalid graphql response: {}", e))?;
+
+    match response.data {
+        Some(data) if response.errors.is_empty() => Ok(data),
+        _ => {
+            let errors: Vec<_> = response.errors.into_iter().map(|e| e.message).collect();
+            Err(format!("graphql errors: {}", errors.join(", ")))
+        }
+    }
+}
+
+/// The request loading the count.
+pub fn load_request(url: String) -> Request {
+    request(url, LOAD, NoVariables {})
+}
+
+/// Parse the count out of the response to `load_request()`.
+pub fn parse_loaded(body: &str) -> Result<Count, String> {
+    parse::<LoadData>(body).map(|data| data.count)
+}
+
+/// The request saving the count.
+pub fn save_request(url: String, count: Count) -> Request {
+    request(url, SAVE, SaveVariables { count })
+}
+
+/// Check the response to `save_request()`.
+pub fn parse_saved(body: &str) -> Result<(), net::Error> {
+    parse::<IgnoredAny>(body)
+        .map(|_| ())
+        .map_err(net::Error::Response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_request_body() {
+        let request = save_request("/graphql".to_owned(), 5);
+        let body = request.body.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["query"], SAVE);
+        assert_eq!(body["variables"]["count"], 5);
+    }
+
+    #[test]
+    fn parse_responses() {
+        assert_eq!(parse_loaded(r#"{ "data": { "count": 7 } }"#), Ok(7));
+        assert_eq!(parse_saved(r#"{ "data": { "setCount": 7 } }"#), Ok(()));
+
+        let errors = r#"{ "data": null, "errors": [{ "message": "nope" }] }"#;
+        assert_eq!(parse_loaded(errors), Err("graphql errors: nope".to_owned()));
+        assert!(parse_saved(errors).is_err());
+    }
+}
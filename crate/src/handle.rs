@@ -0,0 +1,386 @@
+//! Handles allowing JavaScript to control a running counter.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use euca::app::Dispatcher;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use crate::{Cmd, Count, Msg};
+use crate::audio::Speaker;
+use crate::crdt::PnCounter;
+use crate::events::Stream;
+use crate::listener::EventListener;
+use crate::socket::Socket;
+use crate::tabs::Channel;
+#[cfg(feature = "voice")]
+use crate::voice::Recognizer;
+
+/// State shared between a running app and the handles given out to JavaScript.
+///
+/// The app publishes its count here after every change, along with the dispatcher that processed
+/// the change. Registered change callbacks are then called and a `countchanged` event is fired on
+/// the element the app is mounted on. Handles dispatch through the most recently published
+/// dispatcher so messages sent from within a callback the app triggered are queued on the
+/// dispatcher already running, rather than on one that won't get to them.
+#[derive(Clone, Default)]
+pub struct Shared(Rc<RefCell<Inner>>);
+
+#[derive(Default)]
+struct Inner {
+    count: Count,
+    dispatcher: Option<Dispatcher<Msg, Cmd>>,
+    callbacks: Vec<js_sys::Function>,
+    element: Option<web_sys::Element>,
+    focused: Option<web_sys::Element>,
+    reflect: bool,
+    listeners: Vec<EventListener>,
+    menu_listeners: Vec<EventListener>,
+    unload_listener: Option<EventListener>,
+    socket: Option<Socket>,
+    stream: Option<Stream>,
+    channel: Option<Channel>,
+    speaker: Option<Speaker>,
+    #[cfg(feature = "voice")]
+    recognizer: Option<Recognizer>,
+    #[cfg(feature = "pwa")]
+    install_prompt: Option<web_sys::Event>,
+}
+
+impl Shared {
+    /// Set the element `countchanged` events are fired on.
+    pub fn set_element(&self, element: web_sys::Element) {
+        self.0.borrow_mut().element = Some(element);
+    }
+
+    /// The element the app is mounted on, if it's attached.
+    pub fn element(&self) -> Option<web_sys::Element> {
+        self.0.borrow().element.clone()
+    }
+
+    /// Remember the element last focused within the app.
+    pub fn set_focused(&self, element: Option<web_sys::Element>) {
+        self.0.borrow_mut().focused = element;
+    }
+
+    /// The element last focused within the app.
+    pub fn focused(&self) -> Option<web_sys::Element> {
+        self.0.borrow().focused.clone()
+    }
+
+    /// Record the latest count and the dispatcher that produced it.
+    pub fn publish(&self, count: Count, dispatcher: &Dispatcher<Msg, Cmd>) {
+        let (callbacks, element) = {
+            let mut inner = self.0.borrow_mut();
+            inner.count = count;
+            inner.dispatcher = Some(dispatcher.clone());
+            (inner.callbacks.clone(), inner.element.clone())
+        };
+
+        // our borrow is released, callbacks and listeners are free to use their handle
+        for callback in callbacks {
+            if let Err(e) = callback.call1(&JsValue::NULL, &count.into()) {
+                log::error!("error in change callback: {:?}", e);
+            }
+        }
+
+        if let Some(element) = element {
+            if let Err(e) = fire_count_changed(&element, count) {
+                log::error!("error firing countchanged: {:?}", e);
+            }
+        }
+    }
+
+    /// The latest count published.
+    pub fn count(&self) -> Count {
+        self.0.borrow().count
+    }
+
+    /// Reflect settings back to attributes on the element set with `set_element()`.
+    pub fn enable_reflection(&self) {
+        self.0.borrow_mut().reflect = true;
+    }
+
+    /// Set an attribute on the host element, if reflection is enabled.
+    pub fn reflect(&self, name: &str, value: &str) {
+        // release our borrow before setting the attribute, that may trigger a callback that sends
+        // us a message
+        let element = {
+            let inner = self.0.borrow();
+            match (&inner.element, inner.reflect) {
+                (Some(element), true) => element.clone(),
+                _ => return,
+            }
+        };
+
+        // skip redundant updates to avoid needless attribute change callbacks
+        if element.get_attribute(name).as_deref() == Some(value) {
+            return;
+        }
+
+        if let Err(e) = element.set_attribute(name, value) {
+            log::error!("error reflecting {} attribute: {:?}", name, e);
+        }
+    }
+
+    /// Keep an event listener registered for as long as the app is attached.
+    pub fn listen(&self, listener: EventListener) {
+        self.0.borrow_mut().listeners.push(listener);
+    }
+
+    /// Keep the listeners closing the context menu registered until `unwatch_menu()` is called.
+    pub fn watch_menu(&self, listeners: Vec<EventListener>) {
+        // the old listeners are dropped after our borrow is released, they hold a reference to us
+        let old = std::mem::replace(&mut self.0.borrow_mut().menu_listeners, listeners);
+        drop(old);
+    }
+
+    /// Remove the listeners closing the context menu.
+    pub fn unwatch_menu(&self) {
+        self.watch_menu(vec![]);
+    }
+
+    /// Keep the listener asking before the page closes registered until `unwatch_unload()` is
+    /// called.
+    pub fn watch_unload(&self, listener: EventListener) {
+        let old = self.0.borrow_mut().unload_listener.replace(listener);
+        drop(old);
+    }
+
+    /// Let the page close without asking.
+    pub fn unwatch_unload(&self) {
+        let old = self.0.borrow_mut().unload_listener.take();
+        drop(old);
+    }
+
+    /// Use the given socket for live updates, closing the previous one.
+    pub fn set_socket(&self, socket: Socket) {
+        // the old socket is dropped after our borrow is released, it holds a reference to us
+        let old = self.0.borrow_mut().socket.replace(socket);
+        drop(old);
+    }
+
+    /// Send the count over the live update socket, if there is one.
+    pub fn send(&self, count: Count) {
+        match self.0.borrow().socket {
+            Some(ref socket) => socket.send(count),
+            None => log::warn!("no socket, dropping count {}", count),
+        }
+    }
+
+    /// Use the given event stream for remote updates, closing the previous one.
+    pub fn set_stream(&self, stream: Stream) {
+        // the old stream is dropped after our borrow is released, it holds a reference to us
+        let old = self.0.borrow_mut().stream.replace(stream);
+        drop(old);
+    }
+
+    /// Use the given channel to sync with other tabs, closing the previous one.
+    pub fn set_channel(&self, channel: Channel) {
+        // the old channel is dropped after our borrow is released, it holds a reference to us
+        let old = self.0.borrow_mut().channel.replace(channel);
+        drop(old);
+    }
+
+    /// Tell other tabs about the changes we've seen, if there's a channel to them.
+    pub fn broadcast(&self, tally: &PnCounter) {
+        match self.0.borrow().channel {
+            Some(ref channel) => channel.broadcast(tally),
+            None => log::warn!("no broadcast channel, dropping tally"),
+        }
+    }
+
+    /// Play a tone, opening an audio context the first time.
+    ///
+    /// The context is kept open until the app is detached, browsers limit how many a page may
+    /// have.
+    pub fn play_tone(&self, freq: f32, ms: u32) {
+        let mut inner = self.0.borrow_mut();
+        if inner.speaker.is_none() {
+            match Speaker::new() {
+                Ok(speaker) => inner.speaker = Some(speaker),
+                Err(e) => {
+                    log::error!("error opening audio context: {:?}", e);
+                    return;
+                }
+            }
+        }
+
+        if let Some(ref speaker) = inner.speaker {
+            if let Err(e) = speaker.play(freq, ms) {
+                log::error!("error playing tone: {:?}", e);
+            }
+        }
+    }
+
+    /// Listen for voice commands with the given recognizer, stopping the previous one.
+    #[cfg(feature = "voice")]
+    pub fn set_recognizer(&self, recognizer: Recognizer) {
+        // the old recognizer is dropped after our borrow is released, it holds a reference to us
+        let old = self.0.borrow_mut().recognizer.replace(recognizer);
+        drop(old);
+    }
+
+    /// Stop listening for voice commands, if we are.
+    #[cfg(feature = "voice")]
+    pub fn stop_listening(&self) {
+        if let Some(ref recognizer) = self.0.borrow().recognizer {
+            recognizer.stop();
+        }
+    }
+
+    /// Keep the browser's `beforeinstallprompt` event until the user asks to install the app.
+    #[cfg(feature = "pwa")]
+    pub fn set_install_prompt(&self, event: web_sys::Event) {
+        self.0.borrow_mut().install_prompt = Some(event);
+    }
+
+    /// The kept `beforeinstallprompt` event, if there is one, forgetting it.
+    #[cfg(feature = "pwa")]
+    pub fn take_install_prompt(&self) -> Option<web_sys::Event> {
+        self.0.borrow_mut().install_prompt.take()
+    }
+
+    /// Forget the app, it has been detached from the dom.
+    ///
+    /// Any messages sent after this are dropped, event listeners are removed, and the live update
+    /// socket, event stream, broadcast channel, and audio context are closed.
+    pub fn detach(&self) {
+        // drop these after releasing our borrow, they may hold a reference to us
+        let (listeners, menu_listeners, unload_listener, socket, stream, channel, speaker) = {
+            let mut inner = self.0.borrow_mut();
+            inner.dispatcher = None;
+            inner.element = None;
+            inner.focused = None;
+            (
+                std::mem::take(&mut inner.listeners),
+                std::mem::take(&mut inner.menu_listeners),
+                inner.unload_listener.take(),
+                inner.socket.take(),
+                inner.stream.take(),
+                inner.channel.take(),
+                inner.speaker.take(),
+            )
+        };
+
+        drop(listeners);
+        drop(menu_listeners);
+        drop(unload_listener);
+        drop(socket);
+        drop(stream);
+        drop(channel);
+        drop(speaker);
+
+        #[cfg(feature = "voice")]
+        {
+            let recognizer = self.0.borrow_mut().recognizer.take();
+            drop(recognizer);
+        }
+
+        #[cfg(feature = "pwa")]
+        {
+            self.0.borrow_mut().install_prompt = None;
+        }
+    }
+
+    /// Send a message to the app, if it is attached.
+    pub fn dispatch(&self, msg: Msg) {
+        // release our borrow before dispatching, the app may publish while processing the message
+        let dispatcher = self.0.borrow().dispatcher.clone();
+        match dispatcher {
+            Some(dispatcher) => dispatcher.dispatch(msg),
+            None => log::warn!("counter not attached, dropping {:?}", msg),
+        }
+    }
+}
+
+/// Fire `new CustomEvent("countchanged", { detail: count })` on the given element.
+fn fire_count_changed(element: &web_sys::Element, count: Count) -> Result<(), JsValue> {
+    let init = web_sys::CustomEventInit::new();
+    init.set_detail(&count.into());
+
+    let event = web_sys::CustomEvent::new_with_event_init_dict("countchanged", &init)?;
+    element.dispatch_event(&event)?;
+
+    Ok(())
+}
+
+// only the identity of the shared state matters when comparing commands
+impl PartialEq for Shared {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for Shared {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Shared")
+            .field(&self.count())
+            .finish()
+    }
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CHANGE_CALLBACK: &str = r#"
+/** Called with the new count every time it changes. */
+export type ChangeCallback = (count: number) => void;
+
+declare global {
+    interface HTMLElementEventMap {
+        /** Fired on a counter's mount element when its count changes. */
+        countchanged: CustomEvent<number>;
+    }
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// A JavaScript function accepting the new count.
+    #[wasm_bindgen(typescript_type = "ChangeCallback")]
+    pub type ChangeCallback;
+}
+
+/// A handle to a running counter, returned by the `run` functions.
+#[wasm_bindgen]
+pub struct CounterHandle {
+    shared: Shared,
+}
+
+impl CounterHandle {
+    pub(crate) fn new(shared: Shared) -> Self {
+        CounterHandle { shared }
+    }
+
+    #[cfg_attr(not(feature = "atomics"), allow(dead_code))]
+    pub(crate) fn shared(&self) -> Shared {
+        self.shared.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl CounterHandle {
+    /// The current count.
+    pub fn count(&self) -> Count {
+        self.shared.count()
+    }
+
+    /// Set the count.
+    pub fn set_count(&self, count: Count) {
+        self.shared.dispatch(Msg::SetCount(count));
+    }
+
+    /// Increment the count by one step.
+    pub fn increment(&self) {
+        self.shared.dispatch(Msg::Increment);
+    }
+
+    /// Decrement the count by one step.
+    pub fn decrement(&self) {
+        self.shared.dispatch(Msg::Decrement);
+    }
+
+    /// Call `callback` with the new count every time it changes.
+    pub fn on_change(&self, callback: ChangeCallback) {
+        self.shared.0.borrow_mut().callbacks.push(callback.unchecked_into());
+    }
+}
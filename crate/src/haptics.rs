@@ -0,0 +1,27 @@
+//! Haptic feedback with `navigator.vibrate`.
+
+use log::warn;
+
+/// Whether this is a touch device that can vibrate.
+pub fn supported() -> bool {
+    let navigator = match web_sys::window() {
+        Some(window) => window.navigator(),
+        None => return false,
+    };
+
+    let vibrate = js_sys::Reflect::has(&navigator, &"vibrate".into()).unwrap_or(false);
+    vibrate && navigator.max_touch_points() > 0
+}
+
+/// Vibrate for `ms` milliseconds.
+pub fn vibrate(ms: u32) {
+    let navigator = match web_sys::window() {
+        Some(window) => window.navigator(),
+        None => return,
+    };
+
+    // browsers refuse until the user has interacted with the page
+    if !navigator.vibrate_with_duration(ms) {
+        warn!("vibration was refused");
+    }
+}
@@ -0,0 +1,330 @@
+//! A log of every change to the count, and when it happened.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use serde::{Deserialize, Serialize};
+use crate::{clock, decimal, validate, Count, Msg};
+use crate::validate::Field;
+
+/// How many changes are kept, older ones are dropped.
+pub const MAX_ENTRIES: usize = 10_000;
+
+/// What changed the count.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Increment,
+    Decrement,
+    Set,
+    Nudge,
+    Swipe,
+    Scrub,
+    Loaded,
+    RolledBack,
+    Remote,
+    Merged,
+    Tick,
+    Reset,
+    /// Reset at midnight.
+    DailyReset,
+    CountedDown,
+}
+
+impl Action {
+    /// What changes the count if `msg` does, `None` for messages that can't.
+    pub fn of(msg: &Msg) -> Option<Self> {
+        let action = match msg {
+            Msg::Increment => Action::Increment,
+            Msg::Decrement => Action::Decrement,
+            Msg::SetCount(_) | Msg::Entered(Field::Count, _) => Action::Set,
+            Msg::Nudge(_) | Msg::HoldRepeat(_) => Action::Nudge,
+            Msg::TouchEnd(..) => Action::Swipe,
+            Msg::ScrubMove(..) => Action::Scrub,
+            Msg::CountLoaded(_) => Action::Loaded,
+            Msg::SaveFailed { .. } => Action::RolledBack,
+            Msg::RemoteSet(_) => Action::Remote,
+            Msg::Merge(_) => Action::Merged,
+            Msg::Tick => Action::Tick,
+            Msg::CountdownTick(_) => Action::CountedDown,
+            Msg::Reset => Action::Reset,
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    /// True if the user changed the count themselves, rather than it coming from a timer, the
+    /// server, or another window.
+    pub fn by_user(self) -> bool {
+        matches!(
+            self,
+            Action::Increment
+                | Action::Decrement
+                | Action::Set
+                | Action::Nudge
+                | Action::Swipe
+                | Action::Scrub
+                | Action::Reset
+        )
+    }
+
+    /// The action's name in exported history, the same as in exported state.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Action::Increment => "increment",
+            Action::Decrement => "decrement",
+            Action::Set => "set",
+            Action::Nudge => "nudge",
+            Action::Swipe => "swipe",
+            Action::Scrub => "scrub",
+            Action::Loaded => "loaded",
+            Action::RolledBack => "rolled_back",
+            Action::Remote => "remote",
+            Action::Merged => "merged",
+            Action::Tick => "tick",
+            Action::Reset => "reset",
+            Action::DailyReset => "daily_reset",
+            Action::CountedDown => "counted_down",
+        }
+    }
+
+    /// The translation key for what the history calls it.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Increment => "history_increment",
+            Action::Decrement => "history_decrement",
+            Action::Set => "history_set",
+            Action::Nudge => "history_nudge",
+            Action::Swipe => "history_swipe",
+            Action::Scrub => "history_scrub",
+            Action::Loaded => "history_loaded",
+            Action::RolledBack => "history_rolled_back",
+            Action::Remote => "history_remote",
+            Action::Merged => "history_merged",
+            Action::Tick => "history_tick",
+            Action::Reset => "history_reset",
+            Action::DailyReset => "history_daily_reset",
+            Action::CountedDown => "history_counted_down",
+        }
+    }
+}
+
+/// Which page of the history to turn to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Turn {
+    First,
+    Previous,
+    Next,
+    Last,
+}
+
+impl Turn {
+    /// The turn for a key pressed while the history has focus.
+    pub fn for_key(key: &str) -> Option<Self> {
+        match key {
+            "Home" => Some(Turn::First),
+            "ArrowLeft" | "PageUp" => Some(Turn::Previous),
+            "ArrowRight" | "PageDown" => Some(Turn::Next),
+            "End" => Some(Turn::Last),
+            _ => None,
+        }
+    }
+
+    /// The page this turns to from `page`, out of `pages`.
+    pub fn from(self, page: usize, pages: usize) -> usize {
+        let last = pages.saturating_sub(1);
+        match self {
+            Turn::First => 0,
+            Turn::Previous => page.min(last).saturating_sub(1),
+            Turn::Next => (page + 1).min(last),
+            Turn::Last => last,
+        }
+    }
+}
+
+/// How many pages of `size` entries `total` entries take, there's always at least one.
+pub fn pages(total: usize, size: usize) -> usize {
+    total.div_ceil(size.max(1)).max(1)
+}
+
+/// The entries on `page`, counting from the newest.
+pub fn page(total: usize, size: usize, page: usize) -> Range<usize> {
+    let start = page.saturating_mul(size).min(total);
+    start..start.saturating_add(size).min(total)
+}
+
+/// Which entries of the history to show.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    /// A count, or part of what the history calls a change.
+    pub query: String,
+    pub increments_only: bool,
+    pub today: bool,
+}
+
+impl Filter {
+    /// Where the entries matching the filter are in `entries`, oldest first, calling each action
+    /// what `name` does.
+    ///
+    /// Entries from before `midnight` aren't today's, and the counts are at `places` decimal
+    /// places.
+    pub fn positions(
+        &self,
+        entries: &VecDeque<Entry>,
+        name: impl Fn(Action) -> &'static str,
+        midnight: f64,
+        places: u32,
+    ) -> Vec<usize> {
+        let query = self.query.trim().to_lowercase();
+        let count = validate::count(&query, false, places).ok();
+
+        entries.iter()
+            .enumerate()
+            .filter(|(_, entry)| !self.increments_only || entry.action == Action::Increment)
+            .filter(|(_, entry)| !self.today || entry.time >= midnight)
+            .filter(|(_, entry)| match count {
+                Some(count) => entry.count == count,
+                None => name(entry.action).to_lowercase().contains(&query),
+            })
+            .map(|(position, _)| position)
+            .collect()
+    }
+}
+
+/// A change to the count.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// When it happened, in milliseconds since the epoch.
+    pub time: f64,
+    pub action: Action,
+    /// The count after the change.
+    pub count: Count,
+}
+
+/// The entries as CSV, with a header row, the counts at `places` decimal places.
+pub fn csv(entries: &VecDeque<Entry>, places: u32) -> String {
+    let mut csv = String::from("time,action,count\n");
+    for entry in entries {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            clock::iso(entry.time),
+            entry.action.as_str(),
+            decimal::plain(entry.count, places),
+        ));
+    }
+    csv
+}
+
+/// Add an entry to the end of `history`, dropping the oldest if there are too many.
+///
+/// Dragging over the count changes it on every move, a drag is kept as one entry.
+pub fn record(history: &mut VecDeque<Entry>, entry: Entry) {
+    match history.back_mut() {
+        Some(last) if last.action == Action::Scrub && entry.action == Action::Scrub => {
+            *last = entry;
+        }
+        _ => history.push_back(entry),
+    }
+
+    if history.len() > MAX_ENTRIES {
+        history.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(time: f64, action: Action, count: Count) -> Entry {
+        Entry { time, action, count }
+    }
+
+    #[test]
+    fn scrubs_recorded_once() {
+        let mut history = VecDeque::new();
+        record(&mut history, entry(0.0, Action::Increment, 1));
+        record(&mut history, entry(1.0, Action::Scrub, 2));
+        record(&mut history, entry(2.0, Action::Scrub, 3));
+        record(&mut history, entry(3.0, Action::Decrement, 2));
+        assert_eq!(history, vec![
+            entry(0.0, Action::Increment, 1),
+            entry(2.0, Action::Scrub, 3),
+            entry(3.0, Action::Decrement, 2),
+        ]);
+    }
+
+    #[test]
+    fn pagination() {
+        assert_eq!(pages(0, 10), 1);
+        assert_eq!(pages(10, 10), 1);
+        assert_eq!(pages(11, 10), 2);
+        assert_eq!(page(25, 10, 0), 0..10);
+        assert_eq!(page(25, 10, 2), 20..25);
+        assert_eq!(page(25, 10, 3), 25..25);
+
+        assert_eq!(Turn::Next.from(0, 3), 1);
+        assert_eq!(Turn::Next.from(2, 3), 2);
+        assert_eq!(Turn::Previous.from(0, 3), 0);
+        // the history shrank since the page was turned
+        assert_eq!(Turn::Previous.from(7, 3), 1);
+        assert_eq!(Turn::Last.from(0, 3), 2);
+        assert_eq!(Turn::for_key("PageDown"), Some(Turn::Next));
+    }
+
+    #[test]
+    fn filtered() {
+        let entries = VecDeque::from(vec![
+            entry(0.0, Action::Increment, 1),
+            entry(1000.0, Action::Increment, 2),
+            entry(2000.0, Action::Decrement, 1),
+            entry(3000.0, Action::Set, 10),
+        ]);
+        let name = |action: Action| match action {
+            Action::Increment => "Incremented",
+            Action::Decrement => "Decremented",
+            _ => "Set",
+        };
+        let apply = |filter: Filter| -> Vec<&Entry> {
+            filter.positions(&entries, name, 1500.0, 0)
+                .into_iter()
+                .map(|position| &entries[position])
+                .collect()
+        };
+
+        assert_eq!(apply(Filter::default()).len(), 4);
+        let query = |query: &str| Filter { query: query.to_owned(), ..Filter::default() };
+        assert_eq!(apply(query(" INCREMENT")), vec![&entries[0], &entries[1]]);
+        assert_eq!(apply(query("1")), vec![&entries[0], &entries[2]]);
+        assert_eq!(apply(query("nothing")), Vec::<&Entry>::new());
+
+        let increments = Filter { increments_only: true, ..Filter::default() };
+        assert_eq!(apply(increments), vec![&entries[0], &entries[1]]);
+        let today = Filter { today: true, ..Filter::default() };
+        assert_eq!(apply(today), vec![&entries[2], &entries[3]]);
+        let both = Filter { query: "1".to_owned(), increments_only: true, today: true };
+        assert_eq!(apply(both), Vec::<&Entry>::new());
+    }
+
+    #[test]
+    fn csv_export() {
+        assert_eq!(csv(&VecDeque::new(), 0), "time,action,count\n");
+        assert_eq!(csv(&VecDeque::from(vec![
+            entry(0.0, Action::Increment, 1),
+            entry(1500.0, Action::RolledBack, -20),
+        ]), 0), "time,action,count\n\
+            1970-01-01T00:00:00.000Z,increment,1\n\
+            1970-01-01T00:00:01.500Z,rolled_back,-20\n");
+        let entries = VecDeque::from(vec![entry(0.0, Action::Set, -5)]);
+        assert_eq!(csv(&entries, 2), "time,action,count\n\
+            1970-01-01T00:00:00.000Z,set,-0.05\n");
+    }
+
+    #[test]
+    fn oldest_dropped() {
+        let mut history = VecDeque::new();
+        for i in 0..=MAX_ENTRIES as Count {
+            record(&mut history, entry(i as f64, Action::Increment, i));
+        }
+        assert_eq!(history.len(), MAX_ENTRIES);
+        assert_eq!(history[0].count, 1);
+    }
+}
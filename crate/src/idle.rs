@@ -0,0 +1,46 @@
+//! Noticing when the user has stopped using the page.
+//!
+//! Mouse moves, key presses, touches and scrolling anywhere in the document count as activity. The
+//! app hears about it at most once every `THROTTLE` milliseconds, mouse moves come far more often
+//! than that, and goes idle once it hasn't heard about any for its idle timeout.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use log::error;
+use crate::Msg;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+
+/// The events that count as the user doing something.
+const EVENTS: &[&str] = &["mousemove", "keydown", "pointerdown", "touchstart", "wheel"];
+
+/// The fewest milliseconds between two `Msg::Active`s.
+const THROTTLE: f64 = 1000.0;
+
+/// Send `Msg::Active` to the app when the user does something anywhere in the document.
+pub fn watch(shared: Shared) {
+    let document = match crate::document() {
+        Ok(document) => document,
+        Err(e) => {
+            error!("error watching for activity: {:?}", e);
+            return;
+        }
+    };
+
+    // shared by all the listeners, a key press right after a mouse move is the same activity
+    let last = Rc::new(Cell::new(f64::NEG_INFINITY));
+    for &kind in EVENTS {
+        let (target, last) = (shared.clone(), last.clone());
+        let listener = EventListener::new(&document, kind, move |_| {
+            let now = js_sys::Date::now();
+            if now - last.get() >= THROTTLE {
+                last.set(now);
+                target.dispatch(Msg::Active);
+            }
+        });
+        match listener {
+            Ok(listener) => shared.listen(listener),
+            Err(e) => error!("error listening for {} events: {:?}", kind, e),
+        }
+    }
+}
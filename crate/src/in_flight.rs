@@ -0,0 +1,102 @@
+//! Commands sent off that haven't answered with how they went yet.
+//!
+//! Sending a command that answers with a message later counts it as in flight, the message
+//! answering it counts it done. The view shows the controls a task affects as busy until then.
+
+use crate::Msg;
+
+/// Something done in the background that answers with a message.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Task {
+    /// Loading the count from the server.
+    Fetch,
+    /// Saving the count to the server.
+    Save,
+    /// Copying the count to the clipboard.
+    Copy,
+}
+
+impl Task {
+    /// The task `msg` says how it went, if it answers one.
+    pub fn answered_by(msg: &Msg) -> Option<Task> {
+        match msg {
+            Msg::CountLoaded(_) => Some(Task::Fetch),
+            Msg::Saved(_) | Msg::SaveFailed { .. } => Some(Task::Save),
+            Msg::Copied | Msg::CopyFailed(_) => Some(Task::Copy),
+            _ => None,
+        }
+    }
+}
+
+/// How many of each task are in flight.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InFlight {
+    fetches: u32,
+    saves: u32,
+    copies: u32,
+}
+
+impl InFlight {
+    fn tally(&mut self, task: Task) -> &mut u32 {
+        match task {
+            Task::Fetch => &mut self.fetches,
+            Task::Save => &mut self.saves,
+            Task::Copy => &mut self.copies,
+        }
+    }
+
+    /// Count a command doing `task` as sent.
+    pub fn start(&mut self, task: Task) {
+        let tally = self.tally(task);
+        *tally = tally.saturating_add(1);
+    }
+
+    /// Count one of the commands doing `task` as answered.
+    ///
+    /// Answers to commands sent before the model was restored weren't counted, so they're ignored
+    /// once nothing is in flight.
+    pub fn finish(&mut self, task: Task) {
+        let tally = self.tally(task);
+        *tally = tally.saturating_sub(1);
+    }
+
+    /// True while a command doing `task` hasn't answered.
+    pub fn busy(&self, task: Task) -> bool {
+        match task {
+            Task::Fetch => self.fetches > 0,
+            Task::Save => self.saves > 0,
+            Task::Copy => self.copies > 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answered() {
+        let mut in_flight = InFlight::default();
+        in_flight.start(Task::Save);
+        in_flight.start(Task::Save);
+        assert!(in_flight.busy(Task::Save));
+        assert!(!in_flight.busy(Task::Fetch));
+
+        in_flight.finish(Task::Save);
+        assert!(in_flight.busy(Task::Save));
+        in_flight.finish(Task::Save);
+        assert!(!in_flight.busy(Task::Save));
+
+        // an answer nothing was waiting for
+        in_flight.finish(Task::Save);
+        assert_eq!(in_flight, InFlight::default());
+    }
+
+    #[test]
+    fn answers() {
+        assert_eq!(Task::answered_by(&Msg::Saved(3)), Some(Task::Save));
+        assert_eq!(Task::answered_by(&Msg::Copied), Some(Task::Copy));
+        assert_eq!(Task::answered_by(&Msg::CountLoaded(Ok(1))), Some(Task::Fetch));
+        assert_eq!(Task::answered_by(&Msg::Increment), None);
+    }
+}
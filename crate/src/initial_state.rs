@@ -0,0 +1,89 @@
+//! Initial state rendered into the page by the server.
+//!
+//! ```html
+//! <script type="application/json" id="counter-state">{ "count": 42, "step": 2 }</script>
+//! ```
+//!
+//! A page with more than one counter gives each its own script element, and names it in the
+//! mount element's `data-state` attribute:
+//!
+//! ```html
+//! <main data-state="first-state"></main>
+//! <script type="application/json" id="first-state">{ "count": 42 }</script>
+//! ```
+
+use serde::Deserialize;
+use log::warn;
+use std::cell::Cell;
+use crate::Count;
+
+/// The id of the script element holding the initial state of a counter without `data-state`.
+pub const SCRIPT_ID: &str = "counter-state";
+
+/// The attribute on the mount element with the id of the script element holding its state.
+pub const STATE_ATTRIBUTE: &str = "data-state";
+
+thread_local! {
+    /// Whether a counter has been given the state in `#counter-state` already.
+    static CLAIMED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Initial state for the counter, anything not given falls back to the app's config.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct InitialState {
+    pub count: Option<Count>,
+    pub step: Option<i32>,
+}
+
+impl InitialState {
+    /// Parse the initial state from JSON.
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Load the initial state for the counter mounted on `host`, if there is any.
+    ///
+    /// A host with a `data-state` attribute gets the state in the script element it names. The
+    /// state in `#counter-state` is only for the first counter mounted without one, the rest
+    /// start from their own settings rather than all from the same count. Invalid JSON is logged
+    /// and ignored.
+    pub fn load(host: &web_sys::Element) -> Option<Self> {
+        let document = host.owner_document()?;
+        let id = match host.get_attribute(STATE_ATTRIBUTE) {
+            Some(id) => id,
+            None if CLAIMED.with(Cell::get) => return None,
+            None => SCRIPT_ID.to_owned(),
+        };
+        let json = document.get_element_by_id(&id)?
+            .text_content()?;
+        if id == SCRIPT_ID {
+            CLAIMED.with(|claimed| claimed.set(true));
+        }
+
+        match InitialState::parse(&json) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                warn!("ignoring invalid #{}: {}", id, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let state = InitialState::parse(r#"{ "count": 42 }"#).unwrap();
+        assert_eq!(state, InitialState { count: Some(42), step: None });
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(InitialState::parse(r#"{ "count": "many" }"#).is_err());
+        assert!(InitialState::parse("42").is_err());
+    }
+}
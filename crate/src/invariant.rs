@@ -0,0 +1,11 @@
+//! Checking a model is in a state it could have got into, after every update in debug builds.
+//!
+//! A message leaving the model broken panics at that message, with what's wrong, rather than
+//! somewhere later that trusted the model. Property tests sending arbitrary messages find those
+//! messages for free.
+
+/// Something with invariants to check.
+pub trait Validate {
+    /// What's wrong with it, if anything.
+    fn validate(&self) -> Result<(), String>;
+}
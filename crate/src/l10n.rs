@@ -0,0 +1,505 @@
+//! Translations of the counter's text.
+//!
+//! Each language has a table of keys to text, the locale picked in the settings panel decides
+//! which table is used. A `{}` in the text is where an argument goes, see `Locale::t1`,
+//! `Locale::t2` and `Locale::t3`. Keys missing from a table fall back to English, though the
+//! tests make sure none are.
+
+/// Text for each key, in one language.
+pub type Table = &'static [(&'static str, &'static str)];
+
+pub const EN: Table = &[
+    ("count", "Count"),
+    ("count_is", "Count: {}"),
+    ("increment", "Increment"),
+    ("decrement", "Decrement"),
+    ("set_count", "Set count"),
+    ("copy", "Copy"),
+    ("copied", "Copied!"),
+    ("copy_failed", "Copy failed"),
+    ("loading", "Loading…"),
+    ("saving", "Saving…"),
+    ("wasm_memory", "Wasm memory"),
+    ("js_heap", "JS heap"),
+    ("frames_per_second", "FPS"),
+    ("reset", "Reset"),
+    ("copy_value", "Copy value"),
+    ("start_auto_increment", "Start auto-increment"),
+    ("stop_auto_increment", "Stop auto-increment"),
+    ("enable_notifications", "Enable notifications"),
+    ("notifications_on", "Notifications on"),
+    ("notifications_blocked", "Notifications blocked"),
+    ("milestone", "The count reached {}!"),
+    ("voice_commands", "Voice commands"),
+    ("stop_voice_commands", "Stop voice commands"),
+    ("listening", "Listening for \"up\", \"down\", or \"reset\""),
+    ("pending_one", "1 change pending sync"),
+    ("pending_many", "{} changes pending sync"),
+    ("step", "Step {}"),
+    ("mute", "Mute"),
+    ("unmute", "Unmute"),
+    ("vibration_off", "Turn vibration off"),
+    ("vibration_on", "Turn vibration on"),
+    ("speak", "Speak changes"),
+    ("stop_speaking", "Stop speaking changes"),
+    ("log_in", "Log in"),
+    ("log_out", "Log out"),
+    ("username", "Username"),
+    ("password", "Password"),
+    ("settings", "Settings"),
+    ("dark_theme", "Dark theme"),
+    ("light_theme", "Light theme"),
+    ("theme", "Theme"),
+    ("high_contrast", "High contrast"),
+    ("normal_contrast", "Normal contrast"),
+    ("direction", "Direction"),
+    ("ltr", "Left to right"),
+    ("rtl", "Right to left"),
+    ("language", "Language"),
+    ("goal", "Goal"),
+    ("set_goal", "Set goal"),
+    ("goal_exceeded", "Goal exceeded by {}"),
+    ("statistics", "Statistics"),
+    ("total_clicks", "Total clicks"),
+    ("increments", "Increments"),
+    ("decrements", "Decrements"),
+    ("streak", "Current streak"),
+    ("clicks_per_minute", "Clicks per minute"),
+    ("history", "History"),
+    ("clear_history", "Clear history"),
+    ("history_increment", "Incremented"),
+    ("history_decrement", "Decremented"),
+    ("history_set", "Set"),
+    ("history_nudge", "Stepped"),
+    ("history_swipe", "Swiped"),
+    ("history_scrub", "Dragged"),
+    ("history_loaded", "Loaded"),
+    ("history_rolled_back", "Rolled back"),
+    ("history_remote", "Set elsewhere"),
+    ("history_merged", "Synced"),
+    ("history_tick", "Auto-incremented"),
+    ("history_reset", "Reset"),
+    ("updated_just_now", "Updated just now"),
+    ("updated_seconds", "Updated {} seconds ago"),
+    ("updated_minute", "Updated a minute ago"),
+    ("updated_minutes", "Updated {} minutes ago"),
+    ("updated_hour", "Updated an hour ago"),
+    ("updated_hours", "Updated {} hours ago"),
+    ("updated_day", "Updated a day ago"),
+    ("updated_days", "Updated {} days ago"),
+    ("previous_page", "Previous page"),
+    ("next_page", "Next page"),
+    ("page_of", "Page {} of {}"),
+    ("search_history", "Search history"),
+    ("increments_only", "Increments only"),
+    ("today", "Today"),
+    ("no_matches", "No matching changes"),
+    ("download_csv", "Download CSV"),
+    ("chart", "Count over time"),
+    ("export_state", "Export"),
+    ("export_image", "Export image"),
+    ("print", "Print"),
+    ("time", "Time"),
+    ("change", "Change"),
+    ("update_available", "Update available"),
+    ("reload", "Reload"),
+    ("install_app", "Install app"),
+    ("import_state", "Import"),
+    ("reset_daily", "Reset daily"),
+    ("history_daily_reset", "Reset for the day"),
+    ("history_counted_down", "Counted down"),
+    ("finished", "Time's up!"),
+    ("restart_countdown", "Restart"),
+    ("stopwatch", "Stopwatch"),
+    ("start", "Start"),
+    ("stop", "Stop"),
+    ("lap", "Lap"),
+    ("reset_stopwatch", "Reset"),
+    ("lap_n", "Lap {}"),
+    ("tab_counter", "Counter"),
+    ("tab_stopwatch", "Stopwatch"),
+    ("tab_history", "History"),
+    ("tab_settings", "Settings"),
+    ("no_history", "Nothing has changed the count yet."),
+    ("step_size", "Step"),
+    ("sounds", "Sounds"),
+    ("auto_increment_every", "Auto increment every (ms)"),
+    ("off", "Off"),
+    ("invalid_number", "Enter a whole number."),
+    ("invalid_too_large", "Enter a number from {} to {}."),
+    ("invalid_negative", "A countdown can't go below zero."),
+    ("invalid_zero", "The step can't be zero."),
+    ("invalid_decimal", "Enter a number."),
+    ("invalid_place", "Use at most one decimal place."),
+    ("invalid_places", "Use at most {} decimal places."),
+    ("decimal_places", "Decimal places"),
+    ("overflow", "Past the limit"),
+    ("saturate", "Stop at the limit"),
+    ("wrap", "Wrap around"),
+    ("limit_max", "The count can't go any higher."),
+    ("limit_min", "The count can't go any lower."),
+    ("limit_zero", "A countdown stops at zero."),
+    ("dismiss", "Dismiss"),
+    ("cancel", "Cancel"),
+    ("saved", "Saved."),
+    ("save_failed", "Couldn't save the count."),
+    ("reconnected", "Reconnected."),
+    ("confirm_reset", "Reset the count to zero?"),
+    ("confirm_clear_history", "Forget every change to the count?"),
+    ("retrying", "{}, retrying ({} of {})"),
+    ("save_reset", "Couldn't save the count, it was reset to {}: {}"),
+    ("wrong_credentials", "Wrong username or password"),
+    ("something_went_wrong", "Something went wrong."),
+    ("reset_app", "reset app"),
+    ("import_failed", "Couldn't import the file: {}"),
+    ("import_too_big", "Couldn't import {}, it's too big"),
+    ("import_unreadable", "Couldn't read {}"),
+];
+
+pub const DE: Table = &[
+    ("count", "Zählerstand"),
+    ("count_is", "Zählerstand: {}"),
+    ("increment", "Erhöhen"),
+    ("decrement", "Verringern"),
+    ("set_count", "Zählerstand setzen"),
+    ("copy", "Kopieren"),
+    ("copied", "Kopiert!"),
+    ("copy_failed", "Kopieren fehlgeschlagen"),
+    ("loading", "Wird geladen…"),
+    ("saving", "Wird gespeichert…"),
+    ("wasm_memory", "Wasm-Speicher"),
+    ("js_heap", "JS-Heap"),
+    ("frames_per_second", "FPS"),
+    ("reset", "Zurücksetzen"),
+    ("copy_value", "Wert kopieren"),
+    ("start_auto_increment", "Automatisch hochzählen"),
+    ("stop_auto_increment", "Automatisches Hochzählen beenden"),
+    ("enable_notifications", "Benachrichtigungen aktivieren"),
+    ("notifications_on", "Benachrichtigungen an"),
+    ("notifications_blocked", "Benachrichtigungen blockiert"),
+    ("milestone", "Der Zählerstand hat {} erreicht!"),
+    ("voice_commands", "Sprachbefehle"),
+    ("stop_voice_commands", "Sprachbefehle beenden"),
+    ("listening", "Hört auf „up“, „down“ oder „reset“"),
+    ("pending_one", "1 Änderung wartet auf Synchronisierung"),
+    ("pending_many", "{} Änderungen warten auf Synchronisierung"),
+    ("step", "Schritt {}"),
+    ("mute", "Stumm schalten"),
+    ("unmute", "Ton einschalten"),
+    ("vibration_off", "Vibration ausschalten"),
+    ("vibration_on", "Vibration einschalten"),
+    ("speak", "Änderungen vorlesen"),
+    ("stop_speaking", "Vorlesen beenden"),
+    ("log_in", "Anmelden"),
+    ("log_out", "Abmelden"),
+    ("username", "Benutzername"),
+    ("password", "Passwort"),
+    ("settings", "Einstellungen"),
+    ("dark_theme", "Dunkles Design"),
+    ("light_theme", "Helles Design"),
+    ("theme", "Design"),
+    ("high_contrast", "Hoher Kontrast"),
+    ("normal_contrast", "Normaler Kontrast"),
+    ("direction", "Richtung"),
+    ("ltr", "Links nach rechts"),
+    ("rtl", "Rechts nach links"),
+    ("language", "Sprache"),
+    ("goal", "Ziel"),
+    ("set_goal", "Ziel setzen"),
+    ("goal_exceeded", "Ziel um {} überschritten"),
+    ("statistics", "Statistik"),
+    ("total_clicks", "Klicks insgesamt"),
+    ("increments", "Erhöhungen"),
+    ("decrements", "Verringerungen"),
+    ("streak", "Aktuelle Serie"),
+    ("clicks_per_minute", "Klicks pro Minute"),
+    ("history", "Verlauf"),
+    ("clear_history", "Verlauf löschen"),
+    ("history_increment", "Erhöht"),
+    ("history_decrement", "Verringert"),
+    ("history_set", "Gesetzt"),
+    ("history_nudge", "Schrittweise geändert"),
+    ("history_swipe", "Gewischt"),
+    ("history_scrub", "Gezogen"),
+    ("history_loaded", "Geladen"),
+    ("history_rolled_back", "Rückgängig gemacht"),
+    ("history_remote", "Anderswo gesetzt"),
+    ("history_merged", "Synchronisiert"),
+    ("history_tick", "Automatisch erhöht"),
+    ("history_reset", "Zurückgesetzt"),
+    ("updated_just_now", "Gerade eben aktualisiert"),
+    ("updated_seconds", "Vor {} Sekunden aktualisiert"),
+    ("updated_minute", "Vor einer Minute aktualisiert"),
+    ("updated_minutes", "Vor {} Minuten aktualisiert"),
+    ("updated_hour", "Vor einer Stunde aktualisiert"),
+    ("updated_hours", "Vor {} Stunden aktualisiert"),
+    ("updated_day", "Vor einem Tag aktualisiert"),
+    ("updated_days", "Vor {} Tagen aktualisiert"),
+    ("previous_page", "Vorherige Seite"),
+    ("next_page", "Nächste Seite"),
+    ("page_of", "Seite {} von {}"),
+    ("search_history", "Verlauf durchsuchen"),
+    ("increments_only", "Nur Erhöhungen"),
+    ("today", "Heute"),
+    ("no_matches", "Keine passenden Änderungen"),
+    ("download_csv", "CSV herunterladen"),
+    ("chart", "Zählerstand im Zeitverlauf"),
+    ("export_state", "Exportieren"),
+    ("export_image", "Als Bild exportieren"),
+    ("print", "Drucken"),
+    ("time", "Zeit"),
+    ("change", "Änderung"),
+    ("update_available", "Update verfügbar"),
+    ("reload", "Neu laden"),
+    ("install_app", "App installieren"),
+    ("import_state", "Importieren"),
+    ("reset_daily", "Täglich zurücksetzen"),
+    ("history_daily_reset", "Für den Tag zurückgesetzt"),
+    ("history_counted_down", "Heruntergezählt"),
+    ("finished", "Die Zeit ist um!"),
+    ("restart_countdown", "Neu starten"),
+    ("stopwatch", "Stoppuhr"),
+    ("start", "Start"),
+    ("stop", "Stopp"),
+    ("lap", "Runde"),
+    ("reset_stopwatch", "Zurücksetzen"),
+    ("lap_n", "Runde {}"),
+    ("tab_counter", "Zähler"),
+    ("tab_stopwatch", "Stoppuhr"),
+    ("tab_history", "Verlauf"),
+    ("tab_settings", "Einstellungen"),
+    ("no_history", "Der Zählerstand hat sich noch nicht geändert."),
+    ("step_size", "Schrittweite"),
+    ("sounds", "Töne"),
+    ("auto_increment_every", "Automatisch erhöhen alle (ms)"),
+    ("off", "Aus"),
+    ("invalid_number", "Gib eine ganze Zahl ein."),
+    ("invalid_too_large", "Gib eine Zahl von {} bis {} ein."),
+    ("invalid_negative", "Ein Countdown kann nicht unter null fallen."),
+    ("invalid_zero", "Die Schrittweite kann nicht null sein."),
+    ("invalid_decimal", "Gib eine Zahl ein."),
+    ("invalid_place", "Verwende höchstens eine Nachkommastelle."),
+    ("invalid_places", "Verwende höchstens {} Nachkommastellen."),
+    ("decimal_places", "Nachkommastellen"),
+    ("overflow", "Über der Grenze"),
+    ("saturate", "An der Grenze anhalten"),
+    ("wrap", "Umlaufen"),
+    ("limit_max", "Höher kann der Zähler nicht gehen."),
+    ("limit_min", "Tiefer kann der Zähler nicht gehen."),
+    ("limit_zero", "Ein Countdown hält bei null an."),
+    ("dismiss", "Schließen"),
+    ("cancel", "Abbrechen"),
+    ("saved", "Gespeichert."),
+    ("save_failed", "Der Zähler konnte nicht gespeichert werden."),
+    ("reconnected", "Wieder verbunden."),
+    ("confirm_reset", "Den Zähler auf null zurücksetzen?"),
+    ("confirm_clear_history", "Alle Änderungen am Zähler vergessen?"),
+    ("retrying", "{}, neuer Versuch ({} von {})"),
+    ("save_reset", "Der Zähler konnte nicht gespeichert werden und ist wieder bei {}: {}"),
+    ("wrong_credentials", "Falscher Benutzername oder falsches Passwort"),
+    ("something_went_wrong", "Etwas ist schiefgelaufen."),
+    ("reset_app", "App zurücksetzen"),
+    ("import_failed", "Die Datei konnte nicht importiert werden: {}"),
+    ("import_too_big", "{} konnte nicht importiert werden, die Datei ist zu groß"),
+    ("import_unreadable", "{} konnte nicht gelesen werden"),
+];
+
+pub const JA: Table = &[
+    ("count", "カウント"),
+    ("count_is", "カウント: {}"),
+    ("increment", "増やす"),
+    ("decrement", "減らす"),
+    ("set_count", "カウントを設定"),
+    ("copy", "コピー"),
+    ("copied", "コピーしました"),
+    ("copy_failed", "コピーできませんでした"),
+    ("loading", "読み込み中…"),
+    ("saving", "保存中…"),
+    ("wasm_memory", "Wasm メモリ"),
+    ("js_heap", "JS ヒープ"),
+    ("frames_per_second", "FPS"),
+    ("reset", "リセット"),
+    ("copy_value", "値をコピー"),
+    ("start_auto_increment", "自動カウントを開始"),
+    ("stop_auto_increment", "自動カウントを停止"),
+    ("enable_notifications", "通知を有効にする"),
+    ("notifications_on", "通知はオンです"),
+    ("notifications_blocked", "通知はブロックされています"),
+    ("milestone", "カウントが{}になりました！"),
+    ("voice_commands", "音声コマンド"),
+    ("stop_voice_commands", "音声コマンドを停止"),
+    ("listening", "「up」「down」「reset」を聞き取り中"),
+    ("pending_one", "1件の変更が同期待ちです"),
+    ("pending_many", "{}件の変更が同期待ちです"),
+    ("step", "ステップ {}"),
+    ("mute", "ミュート"),
+    ("unmute", "ミュート解除"),
+    ("vibration_off", "バイブレーションをオフ"),
+    ("vibration_on", "バイブレーションをオン"),
+    ("speak", "変更を読み上げる"),
+    ("stop_speaking", "読み上げを停止"),
+    ("log_in", "ログイン"),
+    ("log_out", "ログアウト"),
+    ("username", "ユーザー名"),
+    ("password", "パスワード"),
+    ("settings", "設定"),
+    ("dark_theme", "ダークテーマ"),
+    ("light_theme", "ライトテーマ"),
+    ("theme", "テーマ"),
+    ("high_contrast", "ハイコントラスト"),
+    ("normal_contrast", "通常のコントラスト"),
+    ("direction", "方向"),
+    ("ltr", "左から右"),
+    ("rtl", "右から左"),
+    ("language", "言語"),
+    ("goal", "目標"),
+    ("set_goal", "目標を設定"),
+    ("goal_exceeded", "目標を{}超えました"),
+    ("statistics", "統計"),
+    ("total_clicks", "合計クリック数"),
+    ("increments", "増やした回数"),
+    ("decrements", "減らした回数"),
+    ("streak", "現在の連続回数"),
+    ("clicks_per_minute", "1分あたりのクリック数"),
+    ("history", "履歴"),
+    ("clear_history", "履歴を消去"),
+    ("history_increment", "増やした"),
+    ("history_decrement", "減らした"),
+    ("history_set", "設定した"),
+    ("history_nudge", "ステップで変更"),
+    ("history_swipe", "スワイプ"),
+    ("history_scrub", "ドラッグ"),
+    ("history_loaded", "読み込んだ"),
+    ("history_rolled_back", "元に戻した"),
+    ("history_remote", "他の場所で設定"),
+    ("history_merged", "同期した"),
+    ("history_tick", "自動で増やした"),
+    ("history_reset", "リセットした"),
+    ("updated_just_now", "たった今更新"),
+    ("updated_seconds", "{}秒前に更新"),
+    ("updated_minute", "1分前に更新"),
+    ("updated_minutes", "{}分前に更新"),
+    ("updated_hour", "1時間前に更新"),
+    ("updated_hours", "{}時間前に更新"),
+    ("updated_day", "1日前に更新"),
+    ("updated_days", "{}日前に更新"),
+    ("previous_page", "前のページ"),
+    ("next_page", "次のページ"),
+    ("page_of", "{}/{}ページ"),
+    ("search_history", "履歴を検索"),
+    ("increments_only", "増加のみ"),
+    ("today", "今日"),
+    ("no_matches", "一致する変更はありません"),
+    ("download_csv", "CSVをダウンロード"),
+    ("chart", "カウントの推移"),
+    ("export_state", "エクスポート"),
+    ("export_image", "画像としてエクスポート"),
+    ("print", "印刷"),
+    ("time", "時刻"),
+    ("change", "変更"),
+    ("update_available", "アップデートがあります"),
+    ("reload", "再読み込み"),
+    ("install_app", "アプリをインストール"),
+    ("import_state", "インポート"),
+    ("reset_daily", "毎日リセット"),
+    ("history_daily_reset", "日付が変わってリセット"),
+    ("history_counted_down", "カウントダウンした"),
+    ("finished", "時間切れです！"),
+    ("restart_countdown", "やり直す"),
+    ("stopwatch", "ストップウォッチ"),
+    ("start", "スタート"),
+    ("stop", "ストップ"),
+    ("lap", "ラップ"),
+    ("reset_stopwatch", "リセット"),
+    ("lap_n", "ラップ {}"),
+    ("tab_counter", "カウンター"),
+    ("tab_stopwatch", "ストップウォッチ"),
+    ("tab_history", "履歴"),
+    ("tab_settings", "設定"),
+    ("no_history", "カウントはまだ変わっていません。"),
+    ("step_size", "ステップ"),
+    ("sounds", "効果音"),
+    ("auto_increment_every", "自動増加の間隔（ミリ秒）"),
+    ("off", "オフ"),
+    ("invalid_number", "整数を入力してください。"),
+    ("invalid_too_large", "{}から{}までの数を入力してください。"),
+    ("invalid_negative", "カウントダウンはゼロ未満になりません。"),
+    ("invalid_zero", "ステップをゼロにはできません。"),
+    ("invalid_decimal", "数を入力してください。"),
+    ("invalid_place", "小数点以下は1桁までにしてください。"),
+    ("invalid_places", "小数点以下は{}桁までにしてください。"),
+    ("decimal_places", "小数点以下の桁数"),
+    ("overflow", "上限・下限を超えたとき"),
+    ("saturate", "上限・下限で止める"),
+    ("wrap", "反対側に回る"),
+    ("limit_max", "カウントはこれ以上大きくなりません。"),
+    ("limit_min", "カウントはこれ以上小さくなりません。"),
+    ("limit_zero", "カウントダウンはゼロで止まります。"),
+    ("dismiss", "閉じる"),
+    ("cancel", "キャンセル"),
+    ("saved", "保存しました。"),
+    ("save_failed", "カウントを保存できませんでした。"),
+    ("reconnected", "再接続しました。"),
+    ("confirm_reset", "カウントをゼロに戻しますか？"),
+    ("confirm_clear_history", "カウントの変更履歴をすべて消去しますか？"),
+    ("retrying", "{}、再試行しています（{}/{}）"),
+    ("save_reset", "カウントを保存できなかったため、{}に戻しました: {}"),
+    ("wrong_credentials", "ユーザー名またはパスワードが違います"),
+    ("something_went_wrong", "問題が発生しました。"),
+    ("reset_app", "アプリをリセット"),
+    ("import_failed", "ファイルをインポートできませんでした: {}"),
+    ("import_too_big", "{}は大きすぎるためインポートできませんでした"),
+    ("import_unreadable", "{}を読み込めませんでした"),
+];
+
+fn find(table: Table, key: &str) -> Option<&'static str> {
+    table.iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, text)| *text)
+}
+
+/// The text for `key` in `table`, or in English if the table doesn't have it.
+///
+/// Unknown keys are returned as is, which at least shows which one is missing.
+pub fn lookup(table: Table, key: &'static str) -> &'static str {
+    find(table, key)
+        .or_else(|| find(EN, key))
+        .unwrap_or(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(table: Table) -> Vec<&'static str> {
+        let mut keys: Vec<_> = table.iter().map(|(key, _)| *key).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn every_key_translated() {
+        for &table in &[DE, JA] {
+            assert_eq!(keys(table), keys(EN));
+
+            // and every argument has somewhere to go
+            for (key, text) in EN.iter() {
+                assert_eq!(
+                    find(table, key).map(|translated| translated.matches("{}").count()),
+                    Some(text.matches("{}").count()),
+                    "{}", key,
+                );
+            }
+        }
+
+        let mut unique = keys(EN);
+        unique.dedup();
+        assert_eq!(unique, keys(EN));
+    }
+
+    #[test]
+    fn missing_keys() {
+        assert_eq!(lookup(&[], "copy"), "Copy");
+        assert_eq!(lookup(DE, "not a key"), "not a key");
+    }
+}
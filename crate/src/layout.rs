@@ -0,0 +1,110 @@
+//! How the counter is laid out on the page.
+
+use log::error;
+use crate::Msg;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+
+/// The media query for windows narrow enough to stack the counter's controls.
+const NARROW: &str = "(max-width: 480px)";
+
+/// Which way text runs, set as the `dir` attribute on the counter's root node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Ltr,
+    Rtl,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 2] = [Direction::Ltr, Direction::Rtl];
+
+    /// The value of the `dir` attribute, also what's stored.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::Ltr => "ltr",
+            Direction::Rtl => "rtl",
+        }
+    }
+
+    /// The direction for a `dir` attribute value.
+    pub fn parse(dir: &str) -> Option<Self> {
+        match dir {
+            "ltr" => Some(Direction::Ltr),
+            "rtl" => Some(Direction::Rtl),
+            _ => None,
+        }
+    }
+
+    /// The translation key for what the settings panel calls it.
+    pub fn label(self) -> &'static str {
+        match self {
+            Direction::Ltr => "ltr",
+            Direction::Rtl => "rtl",
+        }
+    }
+}
+
+/// How the count and the buttons changing it are arranged, by how wide the window is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// Side by side, on wide windows.
+    Horizontal,
+    /// Stacked, on narrow ones.
+    Vertical,
+}
+
+impl Mode {
+    /// The mode for a window that's narrow or not.
+    pub fn for_width(narrow: bool) -> Self {
+        if narrow { Mode::Vertical } else { Mode::Horizontal }
+    }
+}
+
+/// The breakpoint between the modes, `None` without a window to ask.
+fn narrow() -> Option<web_sys::MediaQueryList> {
+    web_sys::window()?.match_media(NARROW).ok()?
+}
+
+/// The mode for the window as it is now, horizontal if there's no telling.
+pub fn current() -> Mode {
+    Mode::for_width(narrow().is_some_and(|query| query.matches()))
+}
+
+/// Send `Msg::Resized` to the app whenever the window is resized across the breakpoint.
+///
+/// Listening to the media query rather than `resize` means there's nothing to do for all the
+/// resizes that don't change the mode.
+pub fn watch(shared: Shared) {
+    let query = match narrow() {
+        Some(query) => query,
+        None => return,
+    };
+
+    let (target, changed) = (shared.clone(), query.clone());
+    let listener = EventListener::new(&query, "change", move |_| {
+        target.dispatch(Msg::Resized(Mode::for_width(changed.matches())));
+    });
+    match listener {
+        Ok(listener) => shared.listen(listener),
+        Err(e) => error!("error watching the window's width: {:?}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direction_names() {
+        for dir in &Direction::ALL {
+            assert_eq!(Direction::parse(dir.as_str()), Some(*dir));
+        }
+        assert_eq!(Direction::parse("up"), None);
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(Mode::for_width(true), Mode::Vertical);
+        assert_eq!(Mode::for_width(false), Mode::Horizontal);
+    }
+}
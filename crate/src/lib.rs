@@ -1,7 +1,181 @@
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 use cfg_if::cfg_if;
 use euca::app::*;
 use euca::dom::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// A text node formatted with `format!`, like the typed-html variant's `text!`.
+macro_rules! text {
+    ($($arg:tt)*) => {
+        ::euca::dom::Dom::text(format!($($arg)*))
+    };
+}
+
+/// A `keydown` handler sending a message only for the keys it's given, so views don't each cast
+/// the event to a `KeyboardEvent` to look at its key. Keys are matched like the arms of a `match`,
+/// or a function maps them to a message. Keys with a message don't also do what they'd do
+/// otherwise, like scrolling the page.
+///
+/// ```ignore
+/// .on("keydown", on_key! {
+///     "Enter" => Msg::Commit,
+///     "Escape" | "Esc" => Msg::Cancel,
+/// })
+/// .on("keydown", on_key!(|key| Turn::for_key(key).map(Msg::TurnHistoryPage)))
+/// ```
+macro_rules! on_key {
+    ($($($key:literal)|+ => $msg:expr),+ $(,)?) => {
+        on_key!(|key: &str| match key {
+            $($($key)|+ => Some($msg),)+
+            _ => None,
+        })
+    };
+    ($key_msg:expr) => {
+        ::euca::dom::Handler::Event(|event| $crate::key_pressed(event, $key_msg))
+    };
+}
+
+/// Listen for pointers being pressed, moved, and let go over an element, whether they're a mouse,
+/// a finger, or a pen. The handler is given the `pointer::Phase` and the `pointer::Pointer`, see
+/// `crate/src/pointer.rs`.
+///
+/// ```ignore
+/// on_pointer!(Dom::elem("div"), |phase, pointer| match phase {
+///     Phase::Down => Some(Msg::DragStart(pointer.id, pointer.x)),
+///     Phase::Move => Some(Msg::DragMove(pointer.id, pointer.x)),
+///     Phase::Up => Some(Msg::DragEnd(pointer.id)),
+/// })
+/// ```
+macro_rules! on_pointer {
+    ($dom:expr, $handler:expr) => {{
+        use ::euca::dom::Handler;
+        use $crate::pointer::{handle, Phase};
+        $dom
+            .on("pointerdown", Handler::Event(|event| handle(event, Phase::Down, $handler)))
+            .on("pointermove", Handler::Event(|event| handle(event, Phase::Move, $handler)))
+            .on("pointerup", Handler::Event(|event| handle(event, Phase::Up, $handler)))
+            .on("pointercancel", Handler::Event(|event| handle(event, Phase::Up, $handler)))
+    }};
+}
+
+mod storage;
+#[cfg(debug_assertions)]
+mod a11y;
+#[cfg(feature = "atomics")]
+mod atomic;
+mod audio;
+mod auth;
+mod boundary;
+mod clipboard;
+mod clock;
+mod codec;
+mod chart;
+mod children;
+mod conditional;
+mod config;
+mod confetti;
+mod crdt;
+mod decimal;
+mod diagnostics;
+mod download;
+mod element;
+mod events;
+mod favicon;
+mod focus;
+mod gesture;
+#[cfg(feature = "graphql")]
+mod graphql;
+mod handle;
+mod haptics;
+mod history;
+mod idle;
+mod in_flight;
+mod initial_state;
+mod invariant;
+mod l10n;
+mod layout;
+mod listener;
+mod locale;
+mod memo;
+mod menu;
+mod migrate;
+mod modal;
+mod net;
+mod notify;
+mod overflow;
+mod picture;
+mod pointer;
+mod preferences;
+mod print;
+#[cfg(feature = "pwa")]
+mod pwa;
+mod route;
+mod socket;
+mod speech;
+mod stats;
+mod stopwatch;
+#[cfg(all(feature = "strict", debug_assertions))]
+mod strict;
+mod snapshot;
+mod spawn;
+mod sync;
+mod tabs;
+mod theme;
+mod timer;
+mod timing;
+mod toast;
+mod tween;
+mod unload;
+mod upload;
+mod validate;
+mod virtual_list;
+#[cfg(feature = "voice")]
+mod voice;
+#[cfg(feature = "worker")]
+mod worker;
+
+pub use config::AppConfig;
+pub use element::define_element;
+pub use handle::CounterHandle;
+use auth::LoginError;
+use children::Children;
+use codec::Format;
+use conditional::{Conditional, Empty};
+use confetti::Confetti;
+use crdt::PnCounter;
+use diagnostics::{Fps, Memory};
+use gesture::{Pinch, Scrub};
+use handle::Shared;
+use history::{Action, Entry, Filter, Turn};
+use in_flight::{InFlight, Task};
+use initial_state::InitialState;
+use invariant::Validate;
+use layout::{Direction, Mode};
+use locale::Locale;
+use memo::Memo;
+use modal::Question;
+use notify::Permission;
+use overflow::{Limit, Overflow};
+use picture::Picture;
+use pointer::{Phase, Pointer};
+use preferences::Preferences;
+use route::Tab;
+use snapshot::{ImportError, Snapshot};
+use spawn::Job;
+use stats::Stats;
+use stopwatch::Stopwatch;
+use sync::Pending;
+use theme::{Scheme, Theme};
+use timing::{Timer, Timers};
+use toast::{Level, Toasts};
+use tween::Tween;
+use validate::{Field, Invalid};
+use virtual_list::Viewport;
 
 cfg_if! {
     // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
@@ -10,122 +184,5680 @@ cfg_if! {
         #[global_allocator]
         static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
     }
-}
+}
+
+cfg_if! {
+    // When the `wide` feature is enabled, count with 128 bits so the count can't overflow in
+    // practice.
+    if #[cfg(feature = "wide")] {
+        pub type Count = i128;
+    }
+    else {
+        pub type Count = i32;
+    }
+}
+
+// shared memory holds the count in an `Int32Array`
+#[cfg(all(feature = "wide", feature = "atomics"))]
+compile_error!("the `wide` and `atomics` features can't be enabled together");
+
+/// Prefix for the keys used to stash models in session storage so they survive a reload.
+const STASH_KEY: &str = "euca-counter";
+
+/// Milliseconds to wait for the count to stop changing before saving it.
+const SAVE_DELAY: u32 = 500;
+
+/// Milliseconds to wait for the history search to stop being typed before filtering by it.
+const SEARCH_DELAY: u32 = 250;
+
+/// The fewest milliseconds between reading out two counts, a count that changes faster than that
+/// is read out when there's time.
+const SPEECH_INTERVAL: u32 = 1000;
+
+/// Milliseconds between increments when auto increment is turned on from the context menu.
+const AUTO_INCREMENT: u32 = 1000;
+
+/// Hides an element from view while leaving it to screen readers.
+const VISUALLY_HIDDEN: &str = "position: absolute; width: 1px; height: 1px; overflow: hidden; \
+    clip: rect(0 0 0 0); white-space: nowrap";
+
+/// How many times a failed load or save is retried by default.
+const MAX_RETRIES: u32 = 3;
+
+/// Milliseconds a toast is shown for.
+const TOAST: u32 = 3000;
+
+/// Milliseconds to show why a click didn't change the count as far as it should have.
+const WARNING: u32 = 4000;
+
+/// Milliseconds between ticks of a countdown.
+const COUNTDOWN_TICK: u32 = 1000;
+
+/// Milliseconds between refreshes of when the count was last updated.
+const CLOCK_REFRESH: u32 = 5000;
+
+/// Milliseconds between samples of how much memory is in use, for the diagnostics overlay.
+const MEMORY_SAMPLE: u32 = 2000;
+
+/// Milliseconds a step button is held down for before it starts stepping over and over.
+const HOLD_DELAY: u32 = 500;
+
+/// Milliseconds between steps while a step button is held down.
+const HOLD_REPEAT: u32 = 100;
+
+/// Milliseconds the count keeps its `changed` class after changing, long enough for a transition.
+const CHANGED: u32 = 300;
+
+/// The frequencies in hertz of the tones played on increment and decrement.
+const INCREMENT_TONE: f32 = 880.0;
+const DECREMENT_TONE: f32 = 440.0;
+
+/// The frequency and length of the alarm played when a countdown finishes.
+const FINISHED_TONE: f32 = 1320.0;
+const FINISHED_TONE_LENGTH: u32 = 600;
+
+/// Milliseconds each tone plays for.
+const TONE_LENGTH: u32 = 60;
+
+/// Milliseconds to vibrate for on every click.
+const VIBRATION: u32 = 15;
+
+struct Model {
+    count: Count,
+    /// What the user picked in the settings, remembered in local storage.
+    preferences: Preferences,
+    /// The settings from the config, only what the user changed from these is remembered.
+    defaults: Preferences,
+    /// Whether a tick is on its way, so changing the interval doesn't start a second timer.
+    ticking: bool,
+    /// True while automatic increments are paused from the context menu.
+    paused: bool,
+    /// How many milliseconds without activity until the counter goes idle, if it ever does.
+    idle_timeout: Option<u32>,
+    /// True while the user is away, automatic increments pause and the counter dims.
+    idle: bool,
+    /// Incremented on every activity so only the latest one's timer makes the counter idle.
+    activity: u32,
+    /// Whether the diagnostics overlay is shown.
+    diagnostics: bool,
+    /// How much memory was in use when it was last sampled, if it's been sampled yet.
+    memory: Option<Memory>,
+    /// The frame rate while something is animating, for the diagnostics overlay.
+    fps: Fps,
+    /// Where the context menu is open, in client coordinates.
+    menu: Option<(i32, i32)>,
+    /// What the user is being asked to confirm, if anything.
+    confirming: Option<Question>,
+    /// Where to load the initial count from, if anywhere.
+    count_url: Option<String>,
+    /// True while waiting for the count to load.
+    loading: bool,
+    /// Where to save the count to, if anywhere.
+    save_url: Option<String>,
+    /// The last count known to be on the server, changes are rolled back to this if saving them
+    /// fails.
+    confirmed: Count,
+    /// Why the last attempt to load or save the count failed.
+    sync_error: Option<String>,
+    /// How many times a failed load or save is retried before giving up.
+    max_retries: u32,
+    /// How many times the current load or save has been retried.
+    retries: u32,
+    /// True while the browser is offline, changes are queued instead of saved.
+    offline: bool,
+    /// Changes that couldn't be saved yet, saved once the browser is back online.
+    pending: Option<Pending>,
+    /// Where to connect for live updates from other windows, if anywhere.
+    socket_url: Option<String>,
+    /// How many times we've tried to reconnect since the live update socket closed.
+    reconnects: u32,
+    /// Where to subscribe to counts set elsewhere, if anywhere.
+    events_url: Option<String>,
+    /// How many times we've tried to subscribe again since the event stream closed.
+    resubscribes: u32,
+    /// Whether to keep the count in sync with the same counter in other tabs.
+    sync_tabs: bool,
+    /// Every change made in this tab and the tabs it syncs with.
+    tally: PnCounter,
+    /// Identifies this tab's changes in the tally.
+    replica: String,
+    /// The format changes are sent to other windows and tabs in.
+    format: Format,
+    /// Where to log in, if the server needs us to.
+    login_url: Option<String>,
+    /// Whether to keep the token in local storage.
+    remember_login: bool,
+    username: String,
+    password: String,
+    /// True while waiting to hear back about logging in.
+    logging_in: bool,
+    /// Why logging in failed.
+    login_error: Option<LoginError>,
+    /// Sent with requests to load and save the count once we're logged in.
+    token: Option<String>,
+    /// Loads, saves and copies that haven't said how they went yet.
+    in_flight: InFlight,
+    /// How copying, saving and syncing went, shown briefly.
+    toasts: Toasts,
+    /// The limit the last click stopped the count at, shown briefly after the click.
+    warning: Option<Limit>,
+    /// Incremented on every warning so only the latest one is cleared.
+    warnings: u32,
+    /// True for a moment after the count changes.
+    changed: bool,
+    /// Incremented on every change so only the latest one clears `changed`.
+    changes: u32,
+    /// True when the browser wants less motion, the count jumps to new counts then.
+    reduced_motion: bool,
+    /// Counting up or down to the count, if it changed a moment ago.
+    tween: Option<Tween>,
+    /// Thrown when the count reaches a milestone.
+    confetti: Option<Confetti>,
+    /// Counts to show a notification at.
+    milestones: Vec<Count>,
+    /// The count the user is working towards, if they set one.
+    goal: Option<Count>,
+    /// When the count was last reset for the day, if it's reset every day.
+    daily_reset: Option<f64>,
+    /// The count to count down from, in countdown mode.
+    countdown: Option<Count>,
+    /// Whether the countdown reached zero.
+    finished: bool,
+    /// Bumped each time the countdown starts ticking, so ticks from before are dropped.
+    countdown_generation: u32,
+    stats: Stats,
+    stopwatch: Stopwatch,
+    /// The tab shown.
+    tab: Tab,
+    /// Whether the tab shown is kept in the url's hash.
+    hash_routing: bool,
+    /// Whether the statistics panel is expanded.
+    stats_open: bool,
+    /// Whether the print summary is shown in place of everything else, to print it.
+    printing: bool,
+    /// Every change to the count, oldest first.
+    history: VecDeque<Entry>,
+    /// Which part of the history is scrolled into view.
+    history_viewport: Viewport,
+    /// How many history entries to show a page at a time, if it's paginated rather than scrolled.
+    history_page_size: Option<usize>,
+    /// The page of history shown, the first has the newest entries.
+    history_page: usize,
+    history_filter: Filter,
+    /// What's typed in the history's search box, the filter catches up once typing stops.
+    search: String,
+    /// Where the entries the history is filtered for are in it, kept until the next update.
+    filtered: Memo<Vec<usize>>,
+    /// The history's chart, kept until the next update.
+    chart: Memo<String>,
+    /// What's wrong with what's typed in to set the count, if anything.
+    count_invalid: Option<Invalid>,
+    /// What's wrong with what's typed in to set the step, if anything.
+    step_invalid: Option<Invalid>,
+    /// Why the latest import failed.
+    import_error: Option<ImportError>,
+    /// When the count last changed.
+    changed_at: Option<f64>,
+    /// How much the count last changed by.
+    last_delta: Count,
+    /// True while the pointer is over the count, which shows a tooltip about the last change.
+    hovering: bool,
+    /// The time as of the latest refresh, to show how long ago the count changed.
+    now: f64,
+    /// Where the time comes from, in milliseconds since the epoch.
+    clock: fn() -> f64,
+    /// Whether we may show notifications.
+    notifications: Permission,
+    /// A milestone reached while we were asking to show notifications.
+    milestone: Option<Count>,
+    /// Whether to play a tone on every click.
+    sounds: bool,
+    /// Whether to vibrate on every click, if we can.
+    haptics: bool,
+    /// True on touch devices that can vibrate.
+    can_vibrate: bool,
+    /// Whether the user wants vibration.
+    vibrate: bool,
+    /// Whether to offer reading the count aloud, if we can.
+    speech: bool,
+    /// True in browsers that can read text aloud.
+    can_speak: bool,
+    /// Whether the user wants every new count read aloud.
+    speak: bool,
+    /// The color scheme the browser prefers.
+    preferred_scheme: Scheme,
+    /// The color scheme the user picked, if they picked one.
+    scheme: Option<Scheme>,
+    /// True when the browser wants high contrast.
+    prefers_contrast: bool,
+    /// Whether the user turned high contrast on or off, if they did.
+    high_contrast: Option<bool>,
+    direction: Direction,
+    /// Whether the controls are side by side or stacked, by how wide the window is.
+    layout: Mode,
+    /// The locale the browser prefers.
+    preferred_locale: &'static Locale,
+    #[cfg(feature = "voice")]
+    voice: voice::Voice,
+    #[cfg(feature = "pwa")]
+    service_worker: pwa::Status,
+    #[cfg(feature = "pwa")]
+    install: pwa::Install,
+    /// Where the current touch started, to tell whether it was a swipe.
+    touch_start: Option<(i32, i32)>,
+    /// The drag scrubbing the count, if there is one.
+    scrub: Option<Scrub>,
+    /// The pointer holding down a step button, and the steps each repeat changes the count by.
+    holding: Option<(i32, i32)>,
+    /// Whether a step button was just let go of. It stepped the count when it was pressed, so the
+    /// click that follows is dropped.
+    released: bool,
+    /// The pinch changing the step, if there is one.
+    pinch: Option<Pinch>,
+    /// Debouncing saves and searches, and throttling speech.
+    timers: Timers,
+    /// The session storage key this instance stashes its model under.
+    stash_key: String,
+    /// Where the count is published for JavaScript handles.
+    shared: Shared,
+}
+
+impl Model {
+    fn new() -> Self {
+        Model {
+            count: 0,
+            preferences: Preferences::default(),
+            defaults: Preferences::default(),
+            ticking: false,
+            paused: false,
+            idle_timeout: None,
+            idle: false,
+            activity: 0,
+            diagnostics: false,
+            memory: None,
+            fps: Fps::default(),
+            menu: None,
+            confirming: None,
+            count_url: None,
+            loading: false,
+            save_url: None,
+            confirmed: 0,
+            sync_error: None,
+            max_retries: MAX_RETRIES,
+            retries: 0,
+            offline: false,
+            pending: None,
+            socket_url: None,
+            reconnects: 0,
+            events_url: None,
+            resubscribes: 0,
+            sync_tabs: false,
+            tally: PnCounter::default(),
+            replica: "local".to_owned(),
+            format: Format::Json,
+            login_url: None,
+            remember_login: false,
+            username: String::new(),
+            password: String::new(),
+            logging_in: false,
+            login_error: None,
+            token: None,
+            in_flight: InFlight::default(),
+            toasts: Toasts::default(),
+            warning: None,
+            warnings: 0,
+            changed: false,
+            changes: 0,
+            reduced_motion: false,
+            tween: None,
+            confetti: None,
+            milestones: vec![],
+            goal: None,
+            daily_reset: None,
+            countdown: None,
+            finished: false,
+            countdown_generation: 0,
+            stats: Stats::default(),
+            stopwatch: Stopwatch::default(),
+            tab: Tab::Counter,
+            hash_routing: false,
+            stats_open: false,
+            printing: false,
+            history: VecDeque::new(),
+            history_viewport: Viewport::default(),
+            history_page_size: None,
+            history_page: 0,
+            history_filter: Filter::default(),
+            search: String::new(),
+            filtered: Memo::default(),
+            chart: Memo::default(),
+            count_invalid: None,
+            step_invalid: None,
+            import_error: None,
+            changed_at: None,
+            last_delta: 0,
+            hovering: false,
+            now: 0.0,
+            clock: clock::now,
+            notifications: Permission::Unsupported,
+            milestone: None,
+            sounds: false,
+            haptics: false,
+            can_vibrate: false,
+            vibrate: true,
+            speech: false,
+            can_speak: false,
+            speak: false,
+            preferred_scheme: Scheme::Light,
+            scheme: None,
+            prefers_contrast: false,
+            high_contrast: None,
+            direction: Direction::Ltr,
+            layout: Mode::Horizontal,
+            preferred_locale: &locale::LOCALES[0],
+            #[cfg(feature = "voice")]
+            voice: voice::Voice::default(),
+            #[cfg(feature = "pwa")]
+            service_worker: pwa::Status::Unregistered,
+            #[cfg(feature = "pwa")]
+            install: pwa::Install::Unavailable,
+            touch_start: None,
+            scrub: None,
+            holding: None,
+            released: false,
+            pinch: None,
+            timers: Timers::default(),
+            stash_key: STASH_KEY.to_owned(),
+            shared: Shared::default(),
+        }
+    }
+
+    /// Create a model from the host page's configuration.
+    ///
+    /// The `stash_key` is used unless the config specifies its own.
+    fn configure(config: &AppConfig, stash_key: String) -> Self {
+        let mut model = Model::new();
+        model.count = config.initial_count();
+        // a step of zero wouldn't change the count, and one past the largest couldn't be picked in
+        // the settings, the default is kept instead
+        if preferences::is_valid_step(config.step()) {
+            model.preferences.step = config.step();
+        }
+        model.preferences.auto_increment = config.auto_increment();
+        model.defaults = model.preferences.clone();
+        model.count_url = config.count_url();
+        model.save_url = config.save_url();
+        model.max_retries = config.max_retries();
+        model.socket_url = config.socket_url();
+        model.events_url = config.events_url();
+        model.sync_tabs = config.sync_tabs();
+        if config.binary_sync() {
+            model.format = Format::Binary;
+        }
+        model.login_url = config.login_url();
+        model.remember_login = config.remember_login();
+        #[allow(clippy::useless_conversion)] // milestones are a different type for wide counts
+        let milestones = config.milestones().into_iter().map(Count::from).collect();
+        model.milestones = milestones;
+        model.sounds = config.sounds();
+        model.haptics = config.haptics();
+        model.speech = config.speech();
+        model.history_page_size = config.history_page_size()
+            .filter(|&size| size > 0)
+            .map(|size| size as usize);
+        model.countdown = config.countdown()
+            .filter(|&start| start > 0)
+            .map(|start| start as Count);
+        if let Some(start) = model.countdown {
+            model.count = start;
+        }
+        model.hash_routing = config.hash_routing();
+        model.idle_timeout = config.idle_timeout().filter(|&ms| ms > 0);
+        model.diagnostics = config.diagnostics();
+        model.stash_key = config.storage_key().unwrap_or(stash_key);
+        model
+    }
+
+    /// Restore the count stashed by a previous instance of the app (e.g. before a rebuild reloaded
+    /// the page), if there is one, along with any changes still waiting to be saved.
+    fn restore(mut self) -> Self {
+        let count = storage::load_session(&self.stash_key)
+            .and_then(|count| migrate::open(&count, migrate::STASH).ok());
+        if let Some(count) = count {
+            self.count = count;
+        }
+
+        let tally = storage::load_session(&self.tally_key())
+            .and_then(|tally| migrate::open(&tally, migrate::TALLY).ok());
+        if let Some(tally) = tally {
+            self.tally = tally;
+        }
+
+        let scheme = storage::load_local(&self.scheme_key())
+            .and_then(|scheme| Scheme::parse(&scheme));
+        if let Some(scheme) = scheme {
+            self.scheme = Some(scheme);
+        }
+
+        if let Some(contrast) = storage::load_local(&self.contrast_key()) {
+            self.high_contrast = Some(contrast == "high");
+        }
+
+        let direction = storage::load_local(&self.direction_key())
+            .and_then(|direction| Direction::parse(&direction));
+        if let Some(direction) = direction {
+            self.direction = direction;
+        }
+
+        match storage::load_local(&self.preferences_key()) {
+            // the config's settings are the defaults for anything not kept
+            Some(json) => {
+                // anything that can't be read is left at its default
+                let value = migrate::open(&json, migrate::PREFERENCES).unwrap_or_default();
+                self.preferences = Preferences::load(&value, self.defaults.clone());
+            }
+            // the theme and locale were kept on their own before
+            None => {
+                let theme = storage::load_local(&self.theme_key()).and_then(|t| Theme::find(&t));
+                if let Some(theme) = theme {
+                    self.preferences.theme = theme;
+                }
+                let locale = storage::load_local(&self.locale_key())
+                    .and_then(|locale| Locale::find(&locale));
+                if let Some(locale) = locale {
+                    self.preferences.locale = Some(locale);
+                }
+                // kept with the rest from now on
+                if theme.is_some() || locale.is_some() {
+                    storage::store_local(&self.preferences_key(), &self.kept_preferences());
+                    storage::remove_local(&self.theme_key());
+                    storage::remove_local(&self.locale_key());
+                }
+            }
+        }
+
+        // goals are positive, the same as for imported ones
+        let goal = storage::load_local(&self.goal_key())
+            .and_then(|goal| goal.parse().ok())
+            .filter(|&goal| goal > 0);
+        if let Some(goal) = goal {
+            self.goal = Some(goal);
+        }
+
+        let daily_reset = storage::load_local(&self.daily_reset_key())
+            .and_then(|time| time.parse().ok());
+        if let Some(time) = daily_reset {
+            self.daily_reset = Some(time);
+        }
+
+        if let Some(token) = storage::load_local(&self.token_key()) {
+            self.token = Some(token);
+        }
+
+        let pending = storage::load_local(&self.pending_key())
+            .and_then(|pending| migrate::open(&pending, migrate::PENDING).ok());
+        if let Some(pending) = pending {
+            self.pending = Some(pending);
+        }
+
+        self
+    }
+
+    /// The session storage key the tally is stashed under.
+    fn tally_key(&self) -> String {
+        format!("{}:tally", self.stash_key)
+    }
+
+    /// The session storage key this tab's id in the tally is handed on to the next page under.
+    fn replica_key(&self) -> String {
+        format!("{}:replica", self.stash_key)
+    }
+
+    /// The local storage key the login token is remembered under.
+    fn token_key(&self) -> String {
+        format!("{}:token", self.stash_key)
+    }
+
+    /// True if the host page wants us logged in and we aren't.
+    fn needs_login(&self) -> bool {
+        self.login_url.is_some() && self.token.is_none()
+    }
+
+    /// True if changes can be saved right away, rather than queued.
+    fn can_sync(&self) -> bool {
+        !self.offline && !self.needs_login()
+    }
+
+    /// Load the count from the server, if there's somewhere to load it from.
+    fn fetch(&mut self, cmds: &mut Commands<Cmd>) {
+        if let Some(ref url) = self.count_url {
+            self.in_flight.start(Task::Fetch);
+            cmds.push(Cmd::FetchCount {
+                shared: self.shared.clone(),
+                url: url.clone(),
+                token: self.token.clone(),
+            });
+        }
+    }
+
+    /// Save a count to the server, if there's somewhere to save it.
+    fn save(&mut self, count: Count, cmds: &mut Commands<Cmd>) {
+        if let Some(ref url) = self.save_url {
+            self.in_flight.start(Task::Save);
+            cmds.push(Cmd::SaveCount {
+                shared: self.shared.clone(),
+                url: url.clone(),
+                token: self.token.clone(),
+                count,
+                previous: self.confirmed,
+                generation: self.timers.generation(Timer::Save),
+            });
+        }
+    }
+
+    /// The local storage key the user's color scheme is kept under.
+    fn scheme_key(&self) -> String {
+        format!("{}:scheme", self.stash_key)
+    }
+
+    /// The local storage key the user's preferences are kept under.
+    fn preferences_key(&self) -> String {
+        format!("{}:preferences", self.stash_key)
+    }
+
+    /// The local storage key the user's theme was kept under, before it was kept with the rest of
+    /// their preferences.
+    fn theme_key(&self) -> String {
+        format!("{}:theme", self.stash_key)
+    }
+
+    /// The local storage key the user's contrast setting is kept under.
+    fn contrast_key(&self) -> String {
+        format!("{}:contrast", self.stash_key)
+    }
+
+    /// The local storage key the user's text direction is kept under.
+    fn direction_key(&self) -> String {
+        format!("{}:direction", self.stash_key)
+    }
+
+    /// The local storage key the user's locale was kept under, like the theme.
+    fn locale_key(&self) -> String {
+        format!("{}:locale", self.stash_key)
+    }
+
+    /// The local storage key the user's goal is kept under.
+    fn goal_key(&self) -> String {
+        format!("{}:goal", self.stash_key)
+    }
+
+    /// The local storage key the time of the latest daily reset is kept under.
+    fn daily_reset_key(&self) -> String {
+        format!("{}:daily-reset", self.stash_key)
+    }
+
+    /// The locale the count is formatted for.
+    fn locale(&self) -> &'static Locale {
+        self.preferences.locale.unwrap_or(self.preferred_locale)
+    }
+
+    /// Whether high contrast is in use.
+    fn high_contrast(&self) -> bool {
+        self.high_contrast.unwrap_or(self.prefers_contrast)
+    }
+
+    /// The count displayed, which catches up with the true count after it changes.
+    fn displayed(&self) -> Count {
+        self.tween.map_or(self.count, |tween| tween.displayed)
+    }
+
+    /// True while there's something to draw animation frames for.
+    fn animating(&self) -> bool {
+        self.tween.is_some() || self.confetti.is_some() || self.stopwatch.running()
+    }
+
+    /// The history entries the user filtered for, oldest first.
+    fn filtered_history(&self) -> Vec<&Entry> {
+        let positions = self.filtered.get(|| {
+            let locale = self.locale();
+            let midnight = clock::start_of_day((self.clock)());
+            let name = |action: Action| locale.t(action.label());
+            self.history_filter.positions(&self.history, name, midnight, self.preferences.places)
+        });
+        positions.iter().map(|&position| &self.history[position]).collect()
+    }
+
+    /// The color scheme in use.
+    fn scheme(&self) -> Scheme {
+        self.scheme.unwrap_or(self.preferred_scheme)
+    }
+
+    /// The local storage key changes waiting to be saved are kept under.
+    fn pending_key(&self) -> String {
+        format!("{}:pending", self.stash_key)
+    }
+
+    /// True if there are changes the server doesn't have yet, queued or waiting to be saved.
+    fn unsynced(&self) -> bool {
+        self.save_url.is_some() && (self.pending.is_some() || self.count != self.confirmed)
+    }
+
+    /// Queue the current count to be saved once the browser is back online.
+    fn enqueue(&mut self, cmds: &mut Commands<Cmd>) {
+        if self.pending.map(|pending| pending.count) != Some(self.count) {
+            self.pending = Some(Pending::queue(self.pending, self.count));
+            cmds.push(Cmd::StorePending(self.pending_key(), self.pending));
+        }
+    }
+
+    /// Connect the live update socket, if there's somewhere to connect to.
+    fn connect(&self, cmds: &mut Commands<Cmd>) {
+        if let Some(ref url) = self.socket_url {
+            cmds.push(Cmd::Connect {
+                shared: self.shared.clone(),
+                url: url.clone(),
+                format: self.format,
+            });
+        }
+    }
+
+    /// Subscribe to the event stream, if there's one to subscribe to.
+    fn subscribe(&self, cmds: &mut Commands<Cmd>) {
+        if let Some(ref url) = self.events_url {
+            cmds.push(Cmd::Subscribe {
+                shared: self.shared.clone(),
+                url: url.clone(),
+            });
+        }
+    }
+
+    /// Save the queued changes.
+    fn flush(&mut self, cmds: &mut Commands<Cmd>) {
+        if !self.can_sync() {
+            return;
+        }
+
+        if let Some(pending) = self.pending {
+            self.save(pending.count, cmds);
+        }
+    }
+
+    /// Read out the count, unless a count was read out a moment ago. The latest count is read out
+    /// once there's time then.
+    fn speak_count(&mut self, cmds: &mut Commands<Cmd>) {
+        let now = (self.clock)();
+        let (shared, interval) = (&self.shared, SPEECH_INTERVAL);
+        if self.timers.throttle(cmds, shared, Timer::Speech, interval, now, Msg::SpeakDue) {
+            cmds.push(Cmd::Speak(decimal::plain(self.count, self.preferences.places)));
+        }
+    }
+
+    /// Try a failed load or save again after a backoff, sending `msg` when it's time.
+    fn retry(&mut self, error: String, msg: Msg, cmds: &mut Commands<Cmd>) {
+        self.retries += 1;
+        let (retries, max) = (self.retries.to_string(), self.max_retries.to_string());
+        self.sync_error = Some(self.locale().t3("retrying", &error, &retries, &max));
+        cmds.push(Cmd::Retry(self.shared.clone(), self.retries, msg));
+    }
+
+    /// Show a toast for a little while.
+    fn push_toast(&mut self, level: Level, text: impl Into<String>, cmds: &mut Commands<Cmd>) {
+        let id = self.toasts.push(level, text.into());
+        cmds.push(Cmd::Delay(self.shared.clone(), TOAST, Msg::DismissToast(id)));
+    }
+
+    /// Say why the last click stopped short at `limit` for a little while.
+    fn warn(&mut self, limit: Limit, cmds: &mut Commands<Cmd>) {
+        self.warning = Some(limit);
+        self.warnings = self.warnings.wrapping_add(1);
+        cmds.push(Cmd::Delay(self.shared.clone(), WARNING, Msg::WarningExpired(self.warnings)));
+    }
+
+    /// Ask the user to confirm `question` before doing it.
+    fn ask(&mut self, question: Question, cmds: &mut Commands<Cmd>) {
+        self.confirming = Some(question);
+        let cancel = modal::CANCEL.to_owned();
+        cmds.post_render.push(Cmd::MoveFocus(self.shared.clone(), cancel));
+    }
+
+    /// Close the confirmation dialog, if it's open, focusing what it was opened from.
+    fn close_modal(&mut self, cmds: &mut Commands<Cmd>) {
+        if let Some(question) = self.confirming.take() {
+            let key = question.return_focus().to_owned();
+            cmds.post_render.push(Cmd::MoveFocus(self.shared.clone(), key));
+        }
+    }
+
+    /// Close the context menu, if it's open.
+    fn close_menu(&mut self, cmds: &mut Commands<Cmd>) {
+        if self.menu.take().is_some() {
+            cmds.push(Cmd::UnwatchMenu(self.shared.clone()));
+        }
+    }
+
+    /// The state to export.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            version: snapshot::VERSION,
+            count: self.count,
+            step: self.preferences.step,
+            goal: self.goal,
+            history: self.history.clone(),
+            settings: snapshot::Settings {
+                scheme: self.scheme.map(|scheme| scheme.as_str().to_owned()),
+                theme: Some(self.preferences.theme.name.to_owned()),
+                high_contrast: self.high_contrast,
+                direction: Some(self.direction.as_str().to_owned()),
+                locale: self.preferences.locale.map(|locale| locale.tag.to_owned()),
+                places: Some(self.preferences.places),
+            },
+        }
+    }
+
+    /// Replace our state with an imported snapshot, remembering its settings like the user had
+    /// picked them.
+    fn import(&mut self, snapshot: Snapshot, cmds: &mut Commands<Cmd>) {
+        self.import_error = None;
+        // the counts are in the file's units, which are whole numbers if it doesn't say
+        self.preferences.places = snapshot.settings.places.unwrap_or(0);
+        self.count = snapshot.count;
+        self.set_step(snapshot.step, cmds);
+        self.goal = snapshot.goal;
+        cmds.push(Cmd::StoreGoal(self.goal_key(), self.goal));
+        self.history = snapshot.history;
+        self.history_page = 0;
+
+        let settings = snapshot.settings;
+        if let Some(scheme) = settings.scheme.as_deref().and_then(Scheme::parse) {
+            self.scheme = Some(scheme);
+            cmds.push(Cmd::ApplyScheme(self.shared.clone(), scheme));
+            cmds.push(Cmd::StoreScheme(self.scheme_key(), scheme));
+        }
+        if let Some(theme) = settings.theme.as_deref().and_then(Theme::find) {
+            self.preferences.theme = theme;
+        }
+        if let Some(high) = settings.high_contrast {
+            self.high_contrast = Some(high);
+            cmds.push(Cmd::StoreContrast(self.contrast_key(), high));
+        }
+        if let Some(direction) = settings.direction.as_deref().and_then(Direction::parse) {
+            self.direction = direction;
+            cmds.push(Cmd::StoreDirection(self.direction_key(), direction));
+        }
+        if let Some(locale) = settings.locale.as_deref().and_then(Locale::find) {
+            self.preferences.locale = Some(locale);
+            cmds.push(Cmd::SetTitle(title(self.count, self.preferences.places, locale)));
+        }
+        self.store_preferences(cmds);
+    }
+
+    /// Reset the count if it's reset every day and midnight has passed since it last was.
+    fn reset_daily(&mut self, cmds: &mut Commands<Cmd>) {
+        let now = (self.clock)();
+        match self.daily_reset {
+            Some(last) if clock::new_day(last, now, clock::start_of_day) => {
+                self.daily_reset = Some(now);
+                cmds.push(Cmd::StoreDailyReset(self.daily_reset_key(), self.daily_reset));
+                if self.count != 0 {
+                    self.count = 0;
+                    history::record(&mut self.history, Entry {
+                        time: now,
+                        action: Action::DailyReset,
+                        count: 0,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn select_tab(&mut self, tab: Tab, cmds: &mut Commands<Cmd>) {
+        if tab != self.tab {
+            self.tab = tab;
+            if self.hash_routing {
+                cmds.push(Cmd::SetHash(tab));
+            }
+        }
+    }
+
+    /// Start the countdown ticking down from the count, dropping its ticks from before.
+    fn start_countdown(&mut self, cmds: &mut Commands<Cmd>) {
+        self.countdown_generation = self.countdown_generation.wrapping_add(1);
+        let tick = Msg::CountdownTick(self.countdown_generation);
+        cmds.push(Cmd::Delay(self.shared.clone(), COUNTDOWN_TICK, tick));
+    }
+
+    /// Check what's typed into the text box for `field`.
+    fn check(&self, field: Field, text: &str) -> Result<Count, Invalid> {
+        validate::field(field, text, self.countdown.is_some(), self.preferences.places)
+    }
+
+    /// What's wrong with what's typed into the text box for `field`, if it says.
+    fn invalid(&mut self, field: Field) -> Option<&mut Option<Invalid>> {
+        match field {
+            Field::Count => Some(&mut self.count_invalid),
+            Field::Step => Some(&mut self.step_invalid),
+            // a goal that isn't one is dropped when it's entered
+            Field::Goal => None,
+        }
+    }
+
+    /// How much each click changes the count by.
+    fn step(&self) -> Count {
+        Count::from(self.preferences.step)
+    }
+
+    /// `count` changed by `steps` steps, going only as far as the overflow policy allows.
+    fn stepped(&self, count: Count, steps: i32) -> Count {
+        self.preferences.overflow.add(count, steps, self.step())
+    }
+
+    /// Change the count by `steps` steps for a click, warning if it stops at the largest or
+    /// smallest count instead.
+    fn click(&mut self, steps: i32, cmds: &mut Commands<Cmd>) {
+        if self.preferences.overflow == Overflow::Saturate {
+            if let Some(limit) = overflow::past(self.count, steps, self.step()) {
+                self.warn(limit, cmds);
+            }
+        }
+        self.count = self.stepped(self.count, steps);
+    }
+
+    /// Step the count up once if `up`, or down, for a press of a step button or its key.
+    fn press(&mut self, up: bool, cmds: &mut Commands<Cmd>) {
+        self.click(if up { 1 } else { -1 }, cmds);
+        self.stats.click(up, (self.clock)());
+        self.play(if up { INCREMENT_TONE } else { DECREMENT_TONE }, cmds);
+        self.buzz(cmds);
+    }
+
+    /// Copy the count to the clipboard, one copy at a time. The copy button is disabled until it's
+    /// done.
+    fn copy(&mut self, cmds: &mut Commands<Cmd>) {
+        if self.in_flight.busy(Task::Copy) {
+            return;
+        }
+
+        self.in_flight.start(Task::Copy);
+        let text = decimal::plain(self.count, self.preferences.places);
+        cmds.push(Cmd::CopyToClipboard(self.shared.clone(), text));
+    }
+
+    /// Change the step, reflecting it back to the host element.
+    fn set_step(&mut self, step: i32, cmds: &mut Commands<Cmd>) {
+        // the text box shows the new step now
+        self.step_invalid = None;
+        if step != self.preferences.step {
+            self.preferences.step = step;
+            cmds.push(Cmd::Reflect(self.shared.clone(), "step", step.to_string()));
+            self.store_preferences(cmds);
+        }
+    }
+
+    fn set_goal(&mut self, goal: Option<Count>, cmds: &mut Commands<Cmd>) {
+        self.goal = goal;
+        cmds.push(Cmd::StoreGoal(self.goal_key(), goal));
+    }
+
+    /// Count in units at `places` decimal places, converting everything counted in the old ones.
+    ///
+    /// Nothing changes if the count has no room for that many places.
+    fn set_places(&mut self, places: u32, cmds: &mut Commands<Cmd>) {
+        let from = self.preferences.places;
+        let to = places.min(decimal::MAX_PLACES);
+        let rescale = |units| decimal::rescale(units, from, to);
+        let count = match rescale(self.count) {
+            Some(count) => count,
+            None => return,
+        };
+        // only the count has to fit, anything else too large for the places is as large as can be
+        let rescale = |units: Count| {
+            rescale(units).unwrap_or(if units < 0 { Count::MIN } else { Count::MAX })
+        };
+
+        self.preferences.places = to;
+        self.count = count;
+        // a count on its way is in the old units, jump to the count instead
+        self.tween = None;
+        self.confirmed = rescale(self.confirmed);
+        if let Some(ref mut pending) = self.pending {
+            pending.count = rescale(pending.count);
+        }
+        self.milestones.iter_mut().for_each(|milestone| *milestone = rescale(*milestone));
+        self.countdown = self.countdown.map(rescale);
+        self.last_delta = rescale(self.last_delta);
+        for entry in &mut self.history {
+            entry.count = rescale(entry.count);
+        }
+        if let Some(goal) = self.goal {
+            self.set_goal(Some(rescale(goal).max(1)), cmds);
+        }
+
+        // a step too fine for the places is the finest step there is
+        let max = Count::from(preferences::MAX_STEP);
+        #[allow(clippy::unnecessary_cast)] // a wide step comes back down to an i32
+        let step = match rescale(self.step()).clamp(-max, max) as i32 {
+            0 => self.preferences.step.signum(),
+            step => step,
+        };
+        self.preferences.step = step;
+        cmds.push(Cmd::Reflect(self.shared.clone(), "step", step.to_string()));
+        self.store_preferences(cmds);
+    }
+
+    /// Change how often the count goes up by itself, starting the ticks if they'd stopped.
+    fn set_auto_increment(&mut self, ms: Option<u32>, cmds: &mut Commands<Cmd>) {
+        self.preferences.auto_increment = ms;
+        if let (Some(ms), false) = (ms, self.ticking) {
+            self.ticking = true;
+            cmds.push(Cmd::Delay(self.shared.clone(), ms, Msg::Tick));
+        }
+        self.store_preferences(cmds);
+    }
+
+    fn store_preferences(&self, cmds: &mut Commands<Cmd>) {
+        cmds.push(Cmd::StorePreferences(self.preferences_key(), self.kept_preferences()));
+    }
+
+    /// The user's preferences as they're kept in local storage.
+    fn kept_preferences(&self) -> String {
+        migrate::envelope(&self.preferences.to_value(&self.defaults), migrate::PREFERENCES)
+    }
+
+    /// Play a tone, unless we're muted.
+    fn play(&self, freq: f32, cmds: &mut Commands<Cmd>) {
+        if self.sounds && !self.preferences.muted {
+            cmds.push(Cmd::PlayTone { shared: self.shared.clone(), freq, ms: TONE_LENGTH });
+        }
+    }
+
+    /// Vibrate, if we can and the user wants us to.
+    fn buzz(&self, cmds: &mut Commands<Cmd>) {
+        if self.haptics && self.can_vibrate && self.vibrate {
+            cmds.push(Cmd::Vibrate(VIBRATION));
+        }
+    }
+
+    /// Tell the user they reached a milestone, asking to show notifications first if we haven't.
+    fn reach_milestone(&mut self, milestone: Count, cmds: &mut Commands<Cmd>) {
+        match self.notifications {
+            Permission::Granted => {
+                let locale = self.locale();
+                let milestone = locale.decimal(milestone, self.preferences.places);
+                cmds.push(Cmd::Notify(locale.t1("milestone", &milestone)));
+            }
+            Permission::Default => {
+                self.milestone = Some(milestone);
+                cmds.push(Cmd::Spawn(self.shared.clone(), notify::request_permission()));
+            }
+            Permission::Denied | Permission::Unsupported => {}
+        }
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Msg {
+    /// Sent once when the app is attached.
+    Init,
+    Increment,
+    Decrement,
+    SetCount(Count),
+    SetStep(i32),
+    /// Change how many decimal places the count has.
+    SetPlaces(u32),
+    /// Change what a step past the largest or smallest count does.
+    SetOverflow(Overflow),
+    /// Something was typed into a text box, it's checked but not used yet.
+    Typed(Field, String),
+    /// What's typed into a text box was entered, it's used if it checks out.
+    Entered(Field, String),
+    /// Change the milliseconds between automatic increments, or stop them.
+    SetAutoIncrement(Option<u32>),
+    /// Change the count by the given number of steps, up or down.
+    Nudge(i32),
+    /// A touch started at the given client coordinates.
+    TouchStart(i32, i32),
+    /// A touch ended at the given client coordinates, change the count if it was a swipe.
+    TouchEnd(i32, i32),
+    /// The given pointer started dragging over the count at the given client x coordinate.
+    ScrubStart(i32, i32),
+    /// The given pointer moved to the given client x coordinate.
+    ScrubMove(i32, i32),
+    /// The given pointer stopped dragging.
+    ScrubEnd(i32),
+    /// The given pointer pressed a button stepping the count by the given steps.
+    HoldStart(i32, i32),
+    /// Time to step again, if the button is still held since the given generation.
+    HoldRepeat(u32),
+    /// The given pointer let go of a step button.
+    HoldEnd(i32),
+    /// The increment button was clicked if true, or the decrement button.
+    StepClicked(bool),
+    /// The pointer moved onto the count, or off it.
+    HoverCount(bool),
+    /// Open the context menu at the given client coordinates.
+    OpenMenu(i32, i32),
+    CloseMenu,
+    /// Set the count back to zero.
+    Reset,
+    /// Ask the user to confirm something before doing it.
+    Ask(Question),
+    Modal(modal::Msg),
+    /// Close the context menu and do what the chosen item does.
+    MenuItem(menu::Item),
+    /// Two fingers started pinching this far apart.
+    PinchStart(f64),
+    /// The pinching fingers moved this far apart.
+    Pinch(f64),
+    /// Time to load the count from the server again.
+    FetchDue,
+    /// The result of loading the count from the server.
+    CountLoaded(Result<Count, String>),
+    /// Time to save the count, if nothing changed since the given generation.
+    SaveDue(u32),
+    /// Time to filter the history by what's typed, if nothing was typed since the given
+    /// generation.
+    SearchDue(u32),
+    /// Time to read out the count, if nothing was read out since the given generation.
+    SpeakDue(u32),
+    /// The server saved the given count.
+    Saved(Count),
+    /// The server failed to save a count, queue, retry, or roll back to the count it had before.
+    SaveFailed {
+        /// The generation of the changes that weren't saved.
+        generation: u32,
+        previous: Count,
+        error: net::Error,
+    },
+    /// Save the changes queued while offline.
+    Flush,
+    /// The browser came back online.
+    Online,
+    /// The browser went offline.
+    Offline,
+    /// Another window set the count.
+    RemoteSet(Count),
+    /// Merge in the changes made in other tabs.
+    Merge(PnCounter),
+    SetUsername(String),
+    SetPassword(String),
+    /// Log in with the username and password entered.
+    LogIn,
+    /// The server accepted our credentials and gave us this token.
+    LoggedIn(String),
+    /// The server didn't accept our credentials, or couldn't be asked.
+    LoginFailed(LoginError),
+    /// Forget the token and show the login form again.
+    LoggedOut,
+    /// Copy the count to the clipboard.
+    Copy,
+    /// The count was copied.
+    Copied,
+    /// The count couldn't be copied.
+    CopyFailed(String),
+    /// Stop showing the given toast, its time is up or the user closed it.
+    DismissToast(u32),
+    /// Time to stop showing the given warning.
+    WarningExpired(u32),
+    /// The user closed the warning.
+    DismissWarning,
+    /// Time to stop showing the given change to the count.
+    ChangeShown(u32),
+    /// Time to draw an animation frame, at the given time in milliseconds.
+    Frame(f64),
+    /// Ask to show notifications when the count reaches a milestone.
+    EnableNotifications,
+    /// The user decided whether we may show notifications.
+    NotificationPermission(Permission),
+    /// Mute or unmute the tones played on every click.
+    ToggleMute,
+    /// Turn vibrating on every click on or off.
+    ToggleVibration,
+    /// Turn reading every new count aloud on or off.
+    ToggleSpeech,
+    /// Switch between the light and dark color schemes.
+    ToggleTheme,
+    /// Switch to the theme with the given name.
+    PickTheme(String),
+    /// Turn high contrast on or off.
+    ToggleContrast,
+    /// Lay the counter out in the given direction.
+    SetDirection(Direction),
+    /// Format the count for the locale with the given tag.
+    PickLocale(String),
+    /// Work towards the given count, or towards nothing.
+    SetGoal(Option<Count>),
+    /// Expand or collapse the statistics panel.
+    ToggleStats,
+    Stopwatch(stopwatch::Msg),
+    SelectTab(Tab),
+    /// The window was resized across the layout breakpoint, it wants the given layout now.
+    Resized(Mode),
+    /// Select the tab the given number of tabs along, focusing it.
+    CycleTab(i32),
+    /// Forget every change logged.
+    ClearHistory,
+    /// Download the history as CSV.
+    ExportHistory,
+    /// The history was scrolled.
+    HistoryScrolled(Viewport),
+    TurnHistoryPage(Turn),
+    /// Show only history entries for the given count, or with names containing the given text,
+    /// once it's done being typed.
+    SearchHistory(String),
+    ToggleIncrementsOnly,
+    /// Show only today's history entries, or all of them.
+    ToggleToday,
+    /// Time to refresh how long ago the count changed, and to check whether it's a new day.
+    RefreshClock,
+    /// Start or stop resetting the count every day at midnight.
+    ToggleDailyReset,
+    /// Download the count, step, goal, history, and settings as JSON.
+    ExportState,
+    /// Download a picture of the count and how it's changed.
+    ExportImage,
+    /// Print a summary of the count, statistics, and history.
+    Print,
+    /// The print dialog closed, go back to the normal view.
+    Printed,
+    /// Read state exported to the given file.
+    ImportState(web_sys::File),
+    StateImported(Result<Snapshot, ImportError>),
+    /// Start or stop listening for voice commands.
+    #[cfg(feature = "voice")]
+    ToggleVoice,
+    #[cfg(feature = "voice")]
+    VoiceStarted,
+    /// Listening for voice commands failed.
+    #[cfg(feature = "voice")]
+    VoiceFailed(String),
+    #[cfg(feature = "voice")]
+    VoiceStopped,
+    /// The service worker was registered, or a new version of it is installing or installed.
+    #[cfg(feature = "pwa")]
+    ServiceWorkerChanged(pwa::Status),
+    /// Switch to the new version of the service worker and reload the page with it.
+    #[cfg(feature = "pwa")]
+    ReloadForUpdate,
+    /// Show the browser's prompt to install the app.
+    #[cfg(feature = "pwa")]
+    Install,
+    /// The browser offered to install the app, or the user chose whether to.
+    #[cfg(feature = "pwa")]
+    InstallChanged(pwa::Install),
+    /// The live update socket connected.
+    Connected,
+    /// The live update socket closed, or couldn't be opened.
+    Disconnected,
+    /// Time to reconnect the live update socket.
+    Reconnect,
+    /// The event stream opened.
+    Subscribed,
+    /// The event stream closed, or couldn't be opened.
+    Unsubscribed,
+    /// Time to subscribe to the event stream again.
+    Resubscribe,
+    /// Time for an automatic increment.
+    Tick,
+    /// The user did something, they aren't idle.
+    Active,
+    /// Time to go idle, if nothing happened since the activity with the given generation.
+    IdleDue(u32),
+    /// Time to sample how much memory is in use.
+    SampleMemory,
+    /// How much memory is in use.
+    MemorySampled(Memory),
+    /// Time for the countdown started with the given generation to tick down.
+    CountdownTick(u32),
+    /// The countdown reached zero.
+    Finished,
+    /// Count down again from the start.
+    RestartCountdown,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Cmd {
+    /// Stash the count in session storage under the given key.
+    Stash(String, Count),
+    /// Send a message to the app after the given number of milliseconds.
+    ///
+    /// The message is dropped if the app has been detached by then.
+    Delay(Shared, u32, Msg),
+    /// Send a message to the app after backing off for the given retry attempt.
+    Retry(Shared, u32, Msg),
+    /// Send `Msg::Frame` to the app before the browser next repaints.
+    NextFrame(Shared),
+    /// Publish the count for JavaScript handles.
+    Publish(Shared, Count),
+    /// Reflect a setting back to an attribute on the host element, if the host wants that.
+    Reflect(Shared, &'static str, String),
+    /// Set the document title.
+    SetTitle(String),
+    /// Show the count, at the given decimal places, in the page's favicon.
+    SetFavicon(Count, u32),
+    /// Keep the changes waiting to be saved in local storage under the given key.
+    StorePending(String, Option<Pending>),
+    /// Send `Msg::Online` and `Msg::Offline` to the app as the browser's connectivity changes.
+    WatchOnline(Shared),
+    /// Send `Msg::Active` when the user does something on the page.
+    WatchActivity(Shared),
+    /// Send `Msg::MemorySampled` with how much memory is in use.
+    SampleMemory(Shared),
+    /// Connect a live update socket to the given url.
+    Connect {
+        shared: Shared,
+        url: String,
+        format: Format,
+    },
+    /// Send the count over the live update socket.
+    Send(Shared, Count),
+    /// Join the broadcast channel with the given name to sync with other tabs.
+    OpenChannel(Shared, String, Format),
+    /// Stash the tally in session storage under the given key.
+    StashTally(String, PnCounter),
+    /// Tell other tabs about the changes we've seen.
+    Broadcast(Shared, PnCounter),
+    /// Subscribe to the event stream at the given url.
+    Subscribe {
+        shared: Shared,
+        url: String,
+    },
+    /// Exchange credentials for a token at the given url, sending `Msg::LoggedIn` or
+    /// `Msg::LoginFailed` with the result.
+    LogIn {
+        shared: Shared,
+        url: String,
+        username: String,
+        password: String,
+    },
+    /// Remember the login token in local storage under the given key, or forget it.
+    StoreToken(String, Option<String>),
+    /// Copy the text to the clipboard, sending `Msg::Copied` or `Msg::CopyFailed` with the
+    /// result.
+    CopyToClipboard(Shared, String),
+    /// Run the job, sending the app the message it finishes with.
+    Spawn(Shared, Job),
+    /// Show a notification with the given text.
+    Notify(String),
+    /// Vibrate for the given number of milliseconds.
+    Vibrate(u32),
+    /// Read the text aloud.
+    Speak(String),
+    /// Show the app in the given color scheme.
+    ApplyScheme(Shared, Scheme),
+    /// Remember the user's color scheme in local storage under the given key.
+    StoreScheme(String, Scheme),
+    /// Remember whether the user wants high contrast in local storage under the given key.
+    StoreContrast(String, bool),
+    /// Remember the user's text direction in local storage under the given key.
+    StoreDirection(String, Direction),
+    /// Remember the user's preferences, as JSON, in local storage under the given key.
+    StorePreferences(String, String),
+    /// Offer a file for download.
+    DownloadFile {
+        name: String,
+        mime: &'static str,
+        data: String,
+    },
+    /// Open the print dialog.
+    Print,
+    /// Send `Msg::Printed` when the print dialog closes.
+    WatchPrint(Shared),
+    /// Offer a PNG image of the picture for download.
+    DownloadImage {
+        name: String,
+        picture: Picture,
+    },
+    /// Remember the user's goal in local storage under the given key, or forget it.
+    StoreGoal(String, Option<Count>),
+    /// Read exported state from a file the user picked.
+    ReadFile(Shared, web_sys::File),
+    /// Remember when the count was last reset for the day under the given key, or forget it so
+    /// it isn't reset anymore.
+    StoreDailyReset(String, Option<f64>),
+    /// Remember which element has focus, so it can be restored if rendering replaces it.
+    TrackFocus(Shared),
+    /// Focus the replacement for the focused element, if rendering replaced it.
+    RestoreFocus(Shared),
+    /// Focus the element with the given focus key.
+    MoveFocus(Shared, String),
+    /// Scroll the element with the given focus key into view if it's out of view, smoothly if
+    /// the flag is set.
+    ScrollIntoView(Shared, String, bool),
+    /// Name the tab in the url's hash.
+    SetHash(Tab),
+    /// Send `Msg::SelectTab` when the url's hash changes.
+    WatchHash(Shared),
+    /// Send `Msg::Resized` when the window is resized across the layout breakpoint.
+    WatchLayout(Shared),
+    /// Send `Msg::CloseMenu` on clicks outside the context menu.
+    WatchMenu(Shared),
+    /// Stop watching for clicks outside the context menu.
+    UnwatchMenu(Shared),
+    /// Ask before the page closes, there are changes that aren't saved.
+    WatchUnload(Shared),
+    /// Let the page close without asking, everything is saved.
+    UnwatchUnload(Shared),
+    /// Start listening for voice commands.
+    #[cfg(feature = "voice")]
+    Listen(Shared),
+    /// Stop listening for voice commands.
+    #[cfg(feature = "voice")]
+    StopListening(Shared),
+    /// Register the service worker, reporting it with `Msg::ServiceWorkerChanged`.
+    #[cfg(feature = "pwa")]
+    RegisterServiceWorker(Shared),
+    /// Have the waiting service worker take over and reload the page.
+    #[cfg(feature = "pwa")]
+    ActivateUpdate(Shared),
+    /// Keep the browser's offers to install the app, reporting them with `Msg::InstallChanged`.
+    #[cfg(feature = "pwa")]
+    WatchInstall(Shared),
+    /// Show the browser's install prompt, reporting the choice with `Msg::InstallChanged`.
+    #[cfg(feature = "pwa")]
+    PromptInstall(Shared),
+    /// Play a tone at `freq` hertz for `ms` milliseconds.
+    PlayTone {
+        shared: Shared,
+        freq: f32,
+        ms: u32,
+    },
+    /// Load the count from the given url, sending `Msg::CountLoaded` with the result.
+    FetchCount {
+        shared: Shared,
+        url: String,
+        token: Option<String>,
+    },
+    /// Save the count to the given url, sending `Msg::Saved` or `Msg::SaveFailed` with the
+    /// result.
+    SaveCount {
+        shared: Shared,
+        url: String,
+        token: Option<String>,
+        count: Count,
+        /// The count confirmed by the server before this save.
+        previous: Count,
+        /// The generation of the changes being saved.
+        generation: u32,
+    },
+}
+
+impl SideEffect<Msg> for Cmd {
+    fn process(self, dispatcher: &Dispatcher<Msg, Self>) {
+        match self {
+            Cmd::Stash(key, count) => {
+                storage::store_session(&key, &migrate::envelope(&count, migrate::STASH))
+            }
+            Cmd::Delay(shared, ms, msg) => timer::after(ms, move || shared.dispatch(msg)),
+            Cmd::Retry(shared, attempt, msg) => {
+                let ms = sync::backoff(attempt, js_sys::Math::random());
+                timer::after(ms, move || shared.dispatch(msg))
+            }
+            Cmd::NextFrame(shared) => {
+                timer::next_frame(move |time| shared.dispatch(Msg::Frame(time)))
+            }
+            Cmd::Publish(shared, count) => shared.publish(count, dispatcher),
+            Cmd::Reflect(shared, name, value) => shared.reflect(name, &value),
+            Cmd::SetTitle(title) => match document() {
+                Ok(document) => document.set_title(&title),
+                Err(e) => log::error!("error setting title: {:?}", e),
+            },
+            Cmd::SetFavicon(count, places) => favicon::show(count, places),
+            Cmd::StorePending(key, None) => storage::remove_local(&key),
+            Cmd::StorePending(key, Some(pending)) => {
+                storage::store_local(&key, &migrate::envelope(&pending, migrate::PENDING))
+            }
+            Cmd::WatchOnline(shared) => sync::watch_online(shared),
+            Cmd::WatchActivity(shared) => idle::watch(shared),
+            Cmd::SampleMemory(shared) => shared.dispatch(Msg::MemorySampled(diagnostics::memory())),
+            Cmd::Connect { shared, url, format } => socket::connect(shared, url, format),
+            Cmd::Send(shared, count) => shared.send(count),
+            Cmd::OpenChannel(shared, name, format) => tabs::open(shared, name, format),
+            Cmd::StashTally(key, tally) => {
+                storage::store_session(&key, &migrate::envelope(&tally, migrate::TALLY))
+            }
+            Cmd::Broadcast(shared, tally) => shared.broadcast(&tally),
+            Cmd::Subscribe { shared, url } => events::subscribe(shared, url),
+            Cmd::LogIn { shared, url, username, password } => {
+                auth::login(shared, url, username, password)
+            }
+            Cmd::StoreToken(key, Some(token)) => storage::store_local(&key, &token),
+            Cmd::StoreToken(key, None) => storage::remove_local(&key),
+            Cmd::CopyToClipboard(shared, text) => clipboard::copy(shared, text),
+            Cmd::Spawn(shared, job) => spawn::spawn(shared, job),
+            Cmd::Notify(text) => notify::show(&text),
+            Cmd::PlayTone { shared, freq, ms } => shared.play_tone(freq, ms),
+            Cmd::Vibrate(ms) => haptics::vibrate(ms),
+            Cmd::Speak(text) => speech::speak(&text),
+            Cmd::ApplyScheme(shared, scheme) => theme::apply(&shared, scheme),
+            Cmd::StoreScheme(key, scheme) => storage::store_local(&key, scheme.as_str()),
+            Cmd::StoreContrast(key, high) => {
+                storage::store_local(&key, if high { "high" } else { "normal" })
+            }
+            Cmd::StoreDirection(key, direction) => storage::store_local(&key, direction.as_str()),
+            Cmd::StorePreferences(key, json) => storage::store_local(&key, &json),
+            Cmd::DownloadFile { name, mime, data } => download::save(&name, mime, &data),
+            Cmd::DownloadImage { name, picture } => picture::save(&name, &picture),
+            Cmd::Print => print::print(),
+            Cmd::WatchPrint(shared) => print::watch(shared),
+            Cmd::StoreGoal(key, Some(goal)) => storage::store_local(&key, &goal.to_string()),
+            Cmd::StoreGoal(key, None) => storage::remove_local(&key),
+            Cmd::ReadFile(shared, file) => upload::read_state(shared, file),
+            Cmd::StoreDailyReset(key, Some(time)) => storage::store_local(&key, &time.to_string()),
+            Cmd::StoreDailyReset(key, None) => storage::remove_local(&key),
+            Cmd::TrackFocus(shared) => focus::track(shared),
+            Cmd::RestoreFocus(shared) => focus::restore(&shared),
+            Cmd::MoveFocus(shared, key) => focus::move_to(&shared, &key),
+            Cmd::ScrollIntoView(shared, key, smooth) => {
+                focus::scroll_into_view(&shared, &key, smooth)
+            }
+            Cmd::SetHash(tab) => route::set_hash(tab),
+            Cmd::WatchHash(shared) => route::watch(shared),
+            Cmd::WatchLayout(shared) => layout::watch(shared),
+            Cmd::WatchMenu(shared) => menu::watch(shared),
+            Cmd::UnwatchMenu(shared) => shared.unwatch_menu(),
+            Cmd::WatchUnload(shared) => unload::watch(shared),
+            Cmd::UnwatchUnload(shared) => shared.unwatch_unload(),
+            #[cfg(feature = "voice")]
+            Cmd::Listen(shared) => voice::listen(shared),
+            #[cfg(feature = "voice")]
+            Cmd::StopListening(shared) => shared.stop_listening(),
+            #[cfg(feature = "pwa")]
+            Cmd::RegisterServiceWorker(shared) => pwa::register(shared),
+            #[cfg(feature = "pwa")]
+            Cmd::ActivateUpdate(shared) => pwa::activate_update(shared),
+            #[cfg(feature = "pwa")]
+            Cmd::WatchInstall(shared) => pwa::watch_install(shared),
+            #[cfg(feature = "pwa")]
+            Cmd::PromptInstall(shared) => pwa::prompt_install(shared),
+            Cmd::FetchCount { shared, url, token } => sync::fetch_count(shared, url, token),
+            Cmd::SaveCount { shared, url, token, count, previous, generation } => {
+                sync::save_count(shared, url, token, count, previous, generation)
+            }
+        }
+    }
+}
+
+impl Update<Msg, Cmd> for Model {
+    fn update(&mut self, msg: Msg, cmds: &mut Commands<Cmd>) {
+        let count = self.count;
+        let animating = self.animating();
+        // nothing asks before the page closes until the app starts
+        let unsynced = !matches!(msg, Msg::Init) && self.unsynced();
+        // changes that came from elsewhere don't need to be saved or sent back
+        let remote = matches!(
+            msg,
+            Msg::CountLoaded(_) | Msg::SaveFailed { .. } | Msg::RemoteSet(_) | Msg::Merge(_)
+        );
+        let action = Action::of(&msg);
+        // changing the decimal places changes the units the count is in, not what it counts
+        let rescaled = matches!(msg, Msg::SetPlaces(_));
+        if let Some(task) = Task::answered_by(&msg) {
+            self.in_flight.finish(task);
+        }
+
+        match msg {
+            Msg::Init => {
+                self.confirmed = self.count;
+                cmds.push(Cmd::Publish(self.shared.clone(), self.count));
+                cmds.push(Cmd::Reflect(self.shared.clone(), "count", self.count.to_string()));
+                let step = self.preferences.step.to_string();
+                cmds.push(Cmd::Reflect(self.shared.clone(), "step", step));
+                cmds.push(Cmd::SetTitle(title(self.count, self.preferences.places, self.locale())));
+                cmds.push(Cmd::SetFavicon(self.count, self.preferences.places));
+                cmds.push(Cmd::TrackFocus(self.shared.clone()));
+                cmds.push(Cmd::WatchLayout(self.shared.clone()));
+                cmds.push(Cmd::ApplyScheme(self.shared.clone(), self.scheme()));
+                cmds.push(Cmd::Delay(self.shared.clone(), CLOCK_REFRESH, Msg::RefreshClock));
+                self.reset_daily(cmds);
+
+                if self.hash_routing {
+                    cmds.push(Cmd::WatchHash(self.shared.clone()));
+                }
+
+                if self.countdown.is_some() {
+                    if self.count > 0 {
+                        self.start_countdown(cmds);
+                    }
+                    else {
+                        self.finished = true;
+                    }
+                }
+
+                // wait until we're logged in to load the count
+                if self.count_url.is_some() {
+                    self.loading = true;
+                    if !self.needs_login() {
+                        self.fetch(cmds);
+                    }
+                }
+
+                if let Some(ms) = self.preferences.auto_increment {
+                    self.ticking = true;
+                    cmds.push(Cmd::Delay(self.shared.clone(), ms, Msg::Tick));
+                }
+
+                if let Some(ms) = self.idle_timeout {
+                    cmds.push(Cmd::WatchActivity(self.shared.clone()));
+                    cmds.push(Cmd::Delay(self.shared.clone(), ms, Msg::IdleDue(self.activity)));
+                }
+
+                if self.diagnostics {
+                    cmds.push(Cmd::SampleMemory(self.shared.clone()));
+                }
+
+                cmds.push(Cmd::WatchPrint(self.shared.clone()));
+
+                #[cfg(feature = "pwa")]
+                {
+                    cmds.push(Cmd::RegisterServiceWorker(self.shared.clone()));
+                    cmds.push(Cmd::WatchInstall(self.shared.clone()));
+                }
+
+                if self.save_url.is_some() {
+                    cmds.push(Cmd::WatchOnline(self.shared.clone()));
+                    self.flush(cmds);
+                }
+
+                self.connect(cmds);
+                self.subscribe(cmds);
+
+                // let the other tabs know what we've seen, they reply with anything we missed
+                if self.sync_tabs {
+                    let name = self.stash_key.clone();
+                    cmds.push(Cmd::OpenChannel(self.shared.clone(), name, self.format));
+                    cmds.push(Cmd::Broadcast(self.shared.clone(), self.tally.clone()));
+                }
+            }
+            Msg::Increment => self.press(true, cmds),
+            Msg::Decrement => self.press(false, cmds),
+            Msg::StepClicked(up) => {
+                // clicked with a pointer, which stepped already, unless it's from the keyboard
+                if !std::mem::take(&mut self.released) {
+                    self.press(up, cmds);
+                }
+            }
+            Msg::SetCount(count) => self.count = count,
+            Msg::Nudge(steps) => self.click(steps, cmds),
+            Msg::TouchStart(x, y) => self.touch_start = Some((x, y)),
+            Msg::TouchEnd(x, y) => {
+                self.pinch = None;
+                if let Some(start) = self.touch_start.take() {
+                    self.count = self.stepped(self.count, gesture::swipe_steps(start, (x, y)));
+                }
+            }
+            Msg::PinchStart(distance) => {
+                // the first finger down wasn't a swipe or a drag after all
+                self.touch_start = None;
+                self.scrub = None;
+                self.pinch = Some(Pinch { distance, step: self.preferences.step });
+            }
+            Msg::Pinch(distance) => {
+                if let Some(pinch) = self.pinch {
+                    self.set_step(pinch.step(distance), cmds);
+                }
+            }
+            // a second finger pinches rather than taking over the drag
+            Msg::ScrubStart(pointer, x) if self.scrub.is_none() => {
+                self.scrub = Some(Scrub { pointer, x, count: self.count });
+            }
+            Msg::ScrubStart(..) => {}
+            Msg::ScrubMove(pointer, x) => match self.scrub {
+                Some(scrub) if scrub.pointer == pointer => {
+                    let steps = scrub.steps(x);
+                    if steps != 0 {
+                        // a finger dragging across scrubs, it isn't a swipe
+                        self.touch_start = None;
+                    }
+                    self.count = self.stepped(scrub.count, steps);
+                }
+                _ => {}
+            },
+            Msg::ScrubEnd(pointer) => {
+                if self.scrub.is_some_and(|scrub| scrub.pointer == pointer) {
+                    self.scrub = None;
+                }
+            }
+            Msg::HoldStart(pointer, steps) => {
+                self.holding = Some((pointer, steps));
+                self.released = false;
+                self.press(steps > 0, cmds);
+                self.timers.debounce(cmds, &self.shared, Timer::Hold, HOLD_DELAY, Msg::HoldRepeat);
+            }
+            Msg::HoldRepeat(generation) if self.timers.is_current(Timer::Hold, generation) => {
+                if let Some((_, steps)) = self.holding {
+                    self.click(steps, cmds);
+                    let repeat = Msg::HoldRepeat;
+                    self.timers.debounce(cmds, &self.shared, Timer::Hold, HOLD_REPEAT, repeat);
+                }
+            }
+            // let go since
+            Msg::HoldRepeat(_) => {}
+            Msg::HoldEnd(pointer) => {
+                if self.holding.is_some_and(|(held, _)| held == pointer) {
+                    self.holding = None;
+                    self.released = true;
+                    self.timers.cancel(Timer::Hold);
+                }
+            }
+            Msg::SetStep(step) if preferences::is_valid_step(step) => self.set_step(step, cmds),
+            // e.g. from the host's `step` attribute, kept out the same as from the config
+            Msg::SetStep(_) => {}
+            // the goal is only looked at once it's entered
+            Msg::Typed(Field::Goal, _) => {}
+            Msg::Entered(Field::Goal, text) => match text.trim() {
+                "" => self.set_goal(None, cmds),
+                text => {
+                    let goal = self.check(Field::Goal, text);
+                    if let Some(goal) = goal.ok().filter(|&goal| goal > 0) {
+                        self.set_goal(Some(goal), cmds);
+                    }
+                }
+            },
+            Msg::Typed(field, text) => {
+                let invalid = self.check(field, &text).err();
+                if let Some(slot) = self.invalid(field) {
+                    *slot = invalid;
+                }
+            }
+            Msg::Entered(field, text) => match self.check(field, &text) {
+                Ok(value) => match field {
+                    Field::Count => self.count = value,
+                    Field::Step => {
+                        #[allow(clippy::useless_conversion)] // the count is wider than a step
+                        let step = i32::try_from(value).expect("steps are checked to fit");
+                        self.set_step(step, cmds);
+                    }
+                    Field::Goal => {}
+                },
+                Err(invalid) => {
+                    if let Some(slot) = self.invalid(field) {
+                        *slot = Some(invalid);
+                    }
+                }
+            },
+            Msg::SetPlaces(places) => self.set_places(places, cmds),
+            Msg::SetOverflow(overflow) => {
+                self.preferences.overflow = overflow;
+                self.store_preferences(cmds);
+            }
+            Msg::FetchDue => self.fetch(cmds),
+            Msg::CountLoaded(Ok(count)) => {
+                self.loading = false;
+                self.count = count;
+                self.confirmed = count;
+                self.retries = 0;
+                self.sync_error = None;
+            }
+            Msg::CountLoaded(Err(e)) if self.retries < self.max_retries => {
+                self.retry(e, Msg::FetchDue, cmds);
+            }
+            Msg::CountLoaded(Err(e)) => {
+                self.loading = false;
+                self.retries = 0;
+                self.sync_error = Some(e);
+            }
+            // save later if we can't now
+            Msg::SaveDue(generation)
+                if self.timers.is_current(Timer::Save, generation) && !self.can_sync() =>
+            {
+                self.enqueue(cmds);
+            }
+            Msg::SaveDue(generation) if self.timers.is_current(Timer::Save, generation) => {
+                self.save(self.count, cmds);
+            }
+            // the count changed again since this save was scheduled
+            Msg::SaveDue(_) => {}
+            Msg::Saved(count) => {
+                self.confirmed = count;
+                self.retries = 0;
+                // back in sync after failing
+                if self.sync_error.take().is_some() {
+                    self.push_toast(Level::Success, self.locale().t("saved"), cmds);
+                }
+
+                // the server is up to date, nothing queued needs saving anymore
+                if count == self.count && self.pending.take().is_some() {
+                    cmds.push(Cmd::StorePending(self.pending_key(), None));
+                }
+            }
+            // the count changed again since this save was sent, the newer save will be retried or
+            // rolled back if it fails too
+            Msg::SaveFailed { generation, .. }
+                if !self.timers.is_current(Timer::Save, generation) => {}
+            // keep the change, it's saved when the connection comes back
+            Msg::SaveFailed { error: net::Error::Network(e), .. } => {
+                self.enqueue(cmds);
+                if self.retries < self.max_retries {
+                    self.retry(e, Msg::Flush, cmds);
+                }
+                else {
+                    self.retries = 0;
+                    self.sync_error = Some(e);
+                }
+            }
+            Msg::SaveFailed { error, .. } if self.retries < self.max_retries => {
+                let msg = Msg::SaveDue(self.timers.generation(Timer::Save));
+                self.retry(error.to_string(), msg, cmds);
+            }
+            Msg::SaveFailed { previous, error, .. } => {
+                self.push_toast(Level::Error, self.locale().t("save_failed"), cmds);
+                self.count = previous;
+                self.confirmed = previous;
+                let shown = self.locale().decimal(previous, self.preferences.places);
+                self.sync_error = Some(self.locale().t2("save_reset", &shown, &error.to_string()));
+
+                // drop any save scheduled for the changes we just threw away
+                self.timers.cancel(Timer::Save);
+                self.retries = 0;
+                if self.pending.take().is_some() {
+                    cmds.push(Cmd::StorePending(self.pending_key(), None));
+                }
+            }
+            Msg::Flush => self.flush(cmds),
+            Msg::Online => {
+                self.offline = false;
+                self.flush(cmds);
+            }
+            Msg::Offline => self.offline = true,
+            Msg::RemoteSet(count) => self.count = count,
+            Msg::Merge(theirs) => {
+                let ours = self.tally.clone();
+                self.tally.merge(&theirs);
+                let delta = self.tally.value().wrapping_sub(ours.value());
+                self.count = self.preferences.overflow.add_delta(self.count, delta);
+
+                if self.tally != ours {
+                    cmds.push(Cmd::StashTally(self.tally_key(), self.tally.clone()));
+                }
+
+                // they're missing changes we've seen
+                if self.tally != theirs {
+                    cmds.push(Cmd::Broadcast(self.shared.clone(), self.tally.clone()));
+                }
+            }
+            Msg::Connected => {
+                if self.reconnects > 0 {
+                    self.push_toast(Level::Success, self.locale().t("reconnected"), cmds);
+                }
+                self.reconnects = 0;
+            }
+            Msg::Disconnected => {
+                if self.socket_url.is_some() {
+                    self.reconnects = self.reconnects.saturating_add(1);
+                    cmds.push(Cmd::Retry(self.shared.clone(), self.reconnects, Msg::Reconnect));
+                }
+            }
+            Msg::Reconnect => self.connect(cmds),
+            Msg::Subscribed => self.resubscribes = 0,
+            Msg::Unsubscribed => {
+                if self.events_url.is_some() {
+                    self.resubscribes = self.resubscribes.saturating_add(1);
+                    let attempt = self.resubscribes;
+                    cmds.push(Cmd::Retry(self.shared.clone(), attempt, Msg::Resubscribe));
+                }
+            }
+            Msg::Resubscribe => self.subscribe(cmds),
+            Msg::SetUsername(username) => self.username = username,
+            Msg::SetPassword(password) => self.password = password,
+            Msg::LogIn => {
+                if let (Some(ref url), false) = (&self.login_url, self.logging_in) {
+                    self.logging_in = true;
+                    self.login_error = None;
+                    cmds.push(Cmd::LogIn {
+                        shared: self.shared.clone(),
+                        url: url.clone(),
+                        username: self.username.clone(),
+                        password: self.password.clone(),
+                    });
+                }
+            }
+            Msg::LoggedIn(token) => {
+                self.logging_in = false;
+                self.password.clear();
+                self.token = Some(token);
+                if self.remember_login {
+                    cmds.push(Cmd::StoreToken(self.token_key(), self.token.clone()));
+                }
+
+                // catch up on what we skipped while logged out
+                if self.loading {
+                    self.fetch(cmds);
+                }
+                self.flush(cmds);
+            }
+            Msg::LoginFailed(e) => {
+                self.logging_in = false;
+                self.login_error = Some(e);
+            }
+            Msg::LoggedOut => {
+                self.token = None;
+                cmds.push(Cmd::StoreToken(self.token_key(), None));
+            }
+            Msg::Copy => self.copy(cmds),
+            Msg::Copied => self.push_toast(Level::Success, self.locale().t("copied"), cmds),
+            Msg::CopyFailed(e) => {
+                log::error!("error copying count: {}", e);
+                self.push_toast(Level::Error, self.locale().t("copy_failed"), cmds);
+            }
+            Msg::DismissToast(id) => self.toasts.dismiss(id),
+            Msg::WarningExpired(warnings) => {
+                if warnings == self.warnings {
+                    self.warning = None;
+                }
+            }
+            Msg::DismissWarning => self.warning = None,
+            Msg::ChangeShown(changes) => {
+                if changes == self.changes {
+                    self.changed = false;
+                }
+            }
+            Msg::Frame(time) => {
+                if !self.tween.as_mut().is_some_and(|tween| tween.frame(time)) {
+                    self.tween = None;
+                }
+                if !self.confetti.as_mut().is_some_and(|confetti| confetti.frame(time)) {
+                    self.confetti = None;
+                }
+                // frame times don't come from our clock
+                self.stopwatch.frame((self.clock)());
+
+                if self.animating() {
+                    if self.diagnostics {
+                        self.fps.frame(time);
+                    }
+                    cmds.push(Cmd::NextFrame(self.shared.clone()));
+                }
+                else {
+                    self.fps.clear();
+                }
+            }
+            Msg::EnableNotifications => {
+                cmds.push(Cmd::Spawn(self.shared.clone(), notify::request_permission()));
+            }
+            Msg::ToggleMute => {
+                self.preferences.muted = !self.preferences.muted;
+                self.store_preferences(cmds);
+            }
+            Msg::SetAutoIncrement(ms) => self.set_auto_increment(ms, cmds),
+            Msg::ToggleVibration => self.vibrate = !self.vibrate,
+            Msg::ToggleSpeech => self.speak = !self.speak,
+            Msg::SpeakDue(generation) => {
+                if self.speak && self.timers.is_current(Timer::Speech, generation) {
+                    self.speak_count(cmds);
+                }
+            }
+            Msg::ToggleTheme => {
+                let scheme = self.scheme().toggled();
+                self.scheme = Some(scheme);
+                cmds.push(Cmd::ApplyScheme(self.shared.clone(), scheme));
+                cmds.push(Cmd::StoreScheme(self.scheme_key(), scheme));
+            }
+            Msg::PickTheme(name) => {
+                if let Some(theme) = Theme::find(&name) {
+                    self.preferences.theme = theme;
+                    self.store_preferences(cmds);
+                }
+            }
+            Msg::ToggleContrast => {
+                let high = !self.high_contrast();
+                self.high_contrast = Some(high);
+                cmds.push(Cmd::StoreContrast(self.contrast_key(), high));
+            }
+            Msg::SetDirection(direction) => {
+                self.direction = direction;
+                cmds.push(Cmd::StoreDirection(self.direction_key(), direction));
+            }
+            Msg::PickLocale(tag) => {
+                if let Some(locale) = Locale::find(&tag) {
+                    self.preferences.locale = Some(locale);
+                    self.store_preferences(cmds);
+                    cmds.push(Cmd::SetTitle(title(self.count, self.preferences.places, locale)));
+                }
+            }
+            Msg::ToggleStats => self.stats_open = !self.stats_open,
+            Msg::Stopwatch(msg) => self.stopwatch.update(msg, (self.clock)()),
+            Msg::SelectTab(tab) => self.select_tab(tab, cmds),
+            Msg::Resized(layout) => self.layout = layout,
+            Msg::CycleTab(offset) => {
+                let tab = self.tab.cycle(offset);
+                self.select_tab(tab, cmds);
+                cmds.post_render.push(Cmd::MoveFocus(self.shared.clone(), tab_key(tab)));
+            }
+            Msg::ClearHistory => {
+                self.close_modal(cmds);
+                self.history.clear();
+            }
+            Msg::ExportHistory => {
+                cmds.push(Cmd::DownloadFile {
+                    name: "history.csv".to_owned(),
+                    mime: "text/csv",
+                    data: history::csv(&self.history, self.preferences.places),
+                });
+            }
+            Msg::HistoryScrolled(viewport) => self.history_viewport = viewport,
+            Msg::SearchHistory(query) => {
+                self.search = query;
+                let (shared, delay) = (&self.shared, SEARCH_DELAY);
+                self.timers.debounce(cmds, shared, Timer::Search, delay, Msg::SearchDue);
+            }
+            Msg::SearchDue(generation) if self.timers.is_current(Timer::Search, generation) => {
+                self.history_filter.query = self.search.clone();
+                self.history_page = 0;
+            }
+            // more was typed since
+            Msg::SearchDue(_) => {}
+            Msg::ToggleIncrementsOnly => {
+                self.history_filter.increments_only = !self.history_filter.increments_only;
+                self.history_page = 0;
+            }
+            Msg::ToggleToday => {
+                self.history_filter.today = !self.history_filter.today;
+                self.history_page = 0;
+            }
+            Msg::TurnHistoryPage(turn) => {
+                if let Some(size) = self.history_page_size {
+                    let pages = history::pages(self.filtered_history().len(), size);
+                    self.history_page = turn.from(self.history_page, pages);
+                }
+            }
+            Msg::RefreshClock => {
+                self.now = (self.clock)();
+                cmds.push(Cmd::Delay(self.shared.clone(), CLOCK_REFRESH, Msg::RefreshClock));
+                self.reset_daily(cmds);
+            }
+            Msg::ToggleDailyReset => {
+                // the count is next reset at midnight, not now
+                self.daily_reset = match self.daily_reset {
+                    Some(_) => None,
+                    None => Some((self.clock)()),
+                };
+                cmds.push(Cmd::StoreDailyReset(self.daily_reset_key(), self.daily_reset));
+            }
+            Msg::SetGoal(goal) => self.set_goal(goal, cmds),
+            Msg::ExportState => {
+                cmds.push(Cmd::DownloadFile {
+                    name: "counter.json".to_owned(),
+                    mime: "application/json",
+                    data: self.snapshot().to_json(),
+                });
+            }
+            Msg::ExportImage => {
+                let count = self.locale().decimal(self.count, self.preferences.places);
+                let counts: Vec<Count> = self.history.iter().map(|entry| entry.count).collect();
+                cmds.push(Cmd::DownloadImage {
+                    name: "counter.png".to_owned(),
+                    picture: Picture::new(count, &counts),
+                });
+            }
+            Msg::Print => {
+                // print once the summary is on the page
+                self.printing = true;
+                cmds.post_render.push(Cmd::Print);
+            }
+            Msg::Printed => self.printing = false,
+            Msg::ImportState(file) => cmds.push(Cmd::ReadFile(self.shared.clone(), file)),
+            Msg::StateImported(Ok(snapshot)) => self.import(snapshot, cmds),
+            Msg::StateImported(Err(e)) => self.import_error = Some(e),
+            #[cfg(feature = "voice")]
+            Msg::ToggleVoice => {
+                if self.voice.listening {
+                    cmds.push(Cmd::StopListening(self.shared.clone()));
+                }
+                else {
+                    self.voice.error = None;
+                    cmds.push(Cmd::Listen(self.shared.clone()));
+                }
+            }
+            #[cfg(feature = "voice")]
+            Msg::VoiceStarted => self.voice.listening = true,
+            #[cfg(feature = "voice")]
+            Msg::VoiceFailed(e) => self.voice.error = Some(e),
+            #[cfg(feature = "voice")]
+            Msg::VoiceStopped => self.voice.listening = false,
+            #[cfg(feature = "pwa")]
+            Msg::ServiceWorkerChanged(status) => self.service_worker = status,
+            #[cfg(feature = "pwa")]
+            Msg::ReloadForUpdate => cmds.push(Cmd::ActivateUpdate(self.shared.clone())),
+            #[cfg(feature = "pwa")]
+            Msg::Install => {
+                self.install = pwa::Install::Prompting;
+                cmds.push(Cmd::PromptInstall(self.shared.clone()));
+            }
+            #[cfg(feature = "pwa")]
+            Msg::InstallChanged(install) => self.install = install,
+            Msg::NotificationPermission(permission) => {
+                self.notifications = permission;
+                if let Some(milestone) = self.milestone.take() {
+                    self.reach_milestone(milestone, cmds);
+                }
+            }
+            Msg::Tick => {
+                self.ticking = false;
+                if let Some(ms) = self.preferences.auto_increment {
+                    // keep ticking while paused, so unpausing doesn't start a second timer
+                    if !self.paused && !self.idle {
+                        self.count = self.stepped(self.count, 1);
+                    }
+                    self.ticking = true;
+                    cmds.push(Cmd::Delay(self.shared.clone(), ms, Msg::Tick));
+                }
+            }
+            Msg::Active => {
+                self.idle = false;
+                if let Some(ms) = self.idle_timeout {
+                    self.activity = self.activity.wrapping_add(1);
+                    cmds.push(Cmd::Delay(self.shared.clone(), ms, Msg::IdleDue(self.activity)));
+                }
+            }
+            Msg::IdleDue(activity) => {
+                if activity == self.activity {
+                    self.idle = true;
+                }
+            }
+            Msg::SampleMemory => cmds.push(Cmd::SampleMemory(self.shared.clone())),
+            Msg::MemorySampled(memory) => {
+                self.memory = Some(memory);
+                cmds.push(Cmd::Delay(self.shared.clone(), MEMORY_SAMPLE, Msg::SampleMemory));
+            }
+            Msg::CountdownTick(generation)
+                if generation == self.countdown_generation && !self.finished =>
+            {
+                self.count = self.count.saturating_sub(1);
+                if self.count > 0 {
+                    let tick = Msg::CountdownTick(generation);
+                    cmds.push(Cmd::Delay(self.shared.clone(), COUNTDOWN_TICK, tick));
+                }
+            }
+            // the countdown finished or started over since this tick was scheduled
+            Msg::CountdownTick(_) => {}
+            Msg::Finished if self.count == 0 => {
+                self.finished = true;
+                if !self.preferences.muted {
+                    cmds.push(Cmd::PlayTone {
+                        shared: self.shared.clone(),
+                        freq: FINISHED_TONE,
+                        ms: FINISHED_TONE_LENGTH,
+                    });
+                }
+                self.buzz(cmds);
+            }
+            // the count left zero again before this was delivered
+            Msg::Finished => {}
+            Msg::RestartCountdown => {
+                // the countdown starts ticking again once the count changes
+                if let Some(start) = self.countdown {
+                    self.count = start;
+                }
+            }
+            Msg::HoverCount(hovering) => {
+                self.hovering = hovering;
+                // the tooltip says how long ago as of now, not the last refresh
+                if hovering {
+                    self.now = (self.clock)();
+                }
+            }
+            Msg::OpenMenu(x, y) => {
+                if self.menu.is_none() {
+                    cmds.push(Cmd::WatchMenu(self.shared.clone()));
+                }
+                self.menu = Some((x, y));
+            }
+            Msg::CloseMenu => self.close_menu(cmds),
+            Msg::Reset => {
+                self.close_modal(cmds);
+                self.count = 0;
+            }
+            Msg::Ask(question) => self.ask(question, cmds),
+            Msg::Modal(modal::Msg::Cancel) => self.close_modal(cmds),
+            Msg::Modal(modal::Msg::Focus(key)) => {
+                cmds.post_render.push(Cmd::MoveFocus(self.shared.clone(), key.to_owned()));
+            }
+            Msg::MenuItem(item) => {
+                self.close_menu(cmds);
+                match item {
+                    menu::Item::Reset => self.ask(Question::Reset, cmds),
+                    menu::Item::Copy => self.copy(cmds),
+                    menu::Item::ToggleAutoIncrement
+                        if self.preferences.auto_increment.is_some() =>
+                    {
+                        self.paused = !self.paused;
+                    }
+                    menu::Item::ToggleAutoIncrement => {
+                        self.set_auto_increment(Some(AUTO_INCREMENT), cmds);
+                    }
+                }
+            }
+        }
+
+        // any render could replace the focused element
+        cmds.post_render.push(Cmd::RestoreFocus(self.shared.clone()));
+
+        // a countdown doesn't go below zero, however the count was changed
+        if self.countdown.is_some() && self.count < 0 {
+            self.count = 0;
+            if let Some(Action::Decrement) | Some(Action::Nudge) = action {
+                self.warn(Limit::Zero, cmds);
+            }
+        }
+
+        if self.countdown.is_some() && self.count != count {
+            if self.count == 0 && !self.finished {
+                cmds.push(Cmd::Delay(self.shared.clone(), 0, Msg::Finished));
+            }
+            else if self.count > 0 && self.finished {
+                self.finished = false;
+                self.start_countdown(cmds);
+            }
+        }
+
+        if self.count != count {
+            // the text box shows the new count now
+            self.count_invalid = None;
+            cmds.push(Cmd::Stash(self.stash_key.clone(), self.count));
+            cmds.push(Cmd::Publish(self.shared.clone(), self.count));
+            cmds.push(Cmd::Reflect(self.shared.clone(), "count", self.count.to_string()));
+            cmds.push(Cmd::SetTitle(title(self.count, self.preferences.places, self.locale())));
+            cmds.push(Cmd::SetFavicon(self.count, self.preferences.places));
+
+            let now = (self.clock)();
+            self.now = now;
+            self.changed_at = Some(now);
+            // rescaling isn't a change, the last one is rescaled along with the count
+            if !rescaled {
+                self.last_delta = match self.preferences.overflow {
+                    // gone round past the largest or smallest count, it still changed by the step
+                    Overflow::Wrap => self.count.wrapping_sub(count),
+                    Overflow::Saturate => self.count.saturating_sub(count),
+                };
+            }
+            if let Some(action) = action {
+                history::record(&mut self.history, Entry { time: now, action, count: self.count });
+            }
+
+            // a keyboard shortcut or voice command can change the count scrolled out of view,
+            // show the user what they did
+            if self.tab == Tab::Counter && action.is_some_and(Action::by_user) {
+                let smooth = !self.reduced_motion;
+                let scroll = Cmd::ScrollIntoView(self.shared.clone(), "count".to_owned(), smooth);
+                cmds.post_render.push(scroll);
+            }
+
+            // flag the change for page styles to transition on
+            self.changed = true;
+            self.changes = self.changes.wrapping_add(1);
+            cmds.push(Cmd::Delay(self.shared.clone(), CHANGED, Msg::ChangeShown(self.changes)));
+
+            // count through the numbers in between, picking up from wherever a count still in
+            // progress has got to
+            if !self.reduced_motion && !rescaled {
+                let displayed = self.tween.map_or(count, |tween| tween.displayed);
+                self.tween = Some(Tween::new(displayed, self.count));
+            }
+
+            if self.speak {
+                self.speak_count(cmds);
+            }
+
+            // other windows notify about their own changes
+            if !remote && !rescaled {
+                if let Some(milestone) = notify::reached(&self.milestones, count, self.count) {
+                    if !self.reduced_motion {
+                        self.confetti = Some(Confetti::new(self.changes ^ milestone as u32));
+                    }
+                    self.reach_milestone(milestone, cmds);
+                }
+            }
+
+            if self.socket_url.is_some() && !remote {
+                cmds.push(Cmd::Send(self.shared.clone(), self.count));
+            }
+
+            if self.sync_tabs && !remote {
+                // a wide count can jump further than the tally keeps track of
+                #[allow(clippy::useless_conversion)]
+                let delta = i128::from(self.count) - i128::from(count);
+                let delta = i64::try_from(delta)
+                    .unwrap_or(if delta < 0 { i64::MIN } else { i64::MAX });
+                self.tally.add(&self.replica, delta);
+                cmds.push(Cmd::StashTally(self.tally_key(), self.tally.clone()));
+                cmds.push(Cmd::Broadcast(self.shared.clone(), self.tally.clone()));
+            }
+
+            // save once the count stops changing
+            if self.save_url.is_some() && !remote {
+                self.retries = 0;
+                self.timers.debounce(cmds, &self.shared, Timer::Save, SAVE_DELAY, Msg::SaveDue);
+            }
+        }
+
+        if !animating && self.animating() {
+            cmds.push(Cmd::NextFrame(self.shared.clone()));
+        }
+
+        // ask before the page closes only while closing it would lose changes
+        match (unsynced, self.unsynced()) {
+            (false, true) => cmds.push(Cmd::WatchUnload(self.shared.clone())),
+            (true, false) => cmds.push(Cmd::UnwatchUnload(self.shared.clone())),
+            _ => {}
+        }
+
+        // anything derived from the model is worked out again for the next render
+        self.filtered.clear();
+        self.chart.clear();
+
+        // catch a broken model at the message that broke it
+        debug_assert_eq!(self.validate(), Ok(()));
+    }
+}
+
+/// The document title showing the given count.
+fn title(count: Count, places: u32, locale: &Locale) -> String {
+    locale.t1("count_is", &locale.decimal(count, places))
+}
+
+fn button(text: &str, msg: Msg) -> Dom<Msg, Cmd> {
+    Dom::elem("button")
+        .attr(focus::KEY, text)
+        .event("click", msg)
+        .push(Dom::text(text))
+}
+
+/// A button with a label for screen readers, for buttons whose text doesn't say what they do.
+fn labelled_button(text: &str, label: &str, msg: Msg) -> Dom<Msg, Cmd> {
+    button(text, msg)
+        .attr("aria-label", label)
+}
+
+/// The button incrementing the count if `up`, or decrementing it, which keeps going while it's held
+/// down.
+///
+/// The buttons stay in reading order for rtl, their class lets the page's styles put them back
+/// where they always are on screen.
+fn step_button(up: bool, locale: &Locale) -> Dom<Msg, Cmd> {
+    if up {
+        let button = labelled_button("+", locale.t("increment"), Msg::StepClicked(true))
+            .attr("class", "increment");
+        on_pointer!(button, |phase, pointer| hold(phase, pointer, 1))
+    }
+    else {
+        let button = labelled_button("-", locale.t("decrement"), Msg::StepClicked(false))
+            .attr("class", "decrement");
+        on_pointer!(button, |phase, pointer| hold(phase, pointer, -1))
+    }
+}
+
+/// The node everything else is rendered in, styled with the theme.
+fn root(theme: &Theme, high_contrast: bool, idle: bool, children: Vec<Dom<Msg, Cmd>>)
+-> Dom<Msg, Cmd>
+{
+    let mut class = String::from("counter");
+    if high_contrast {
+        class.push_str(" high-contrast");
+    }
+    if idle {
+        class.push_str(" idle");
+    }
+    Dom::elem("div")
+        .attr("class", class)
+        .attr("style", theme.style())
+        .extend(children)
+}
+
+/// A labelled list of `(value, text)` options with `current` selected, sending the picked value
+/// to `handler`.
+fn picker(
+    label: &'static str,
+    options: Vec<(&str, &str)>,
+    current: &str,
+    handler: fn(String) -> Option<Msg>,
+) -> Dom<Msg, Cmd> {
+    Dom::elem("label")
+        .push(text!("{} ", label))
+        .push(Dom::elem("select")
+            .attr(focus::KEY, label)
+            .on("change", Handler::InputValue(handler))
+            .children_from(options, |(value, text)| {
+                let option = Dom::elem("option").attr("value", value);
+                let option = if value == current { option.attr("selected", "") } else { option };
+                option.push(Dom::text(text))
+            })
+        )
+}
+
+/// A text box to type a count into.
+fn count_input(count: Count, places: u32, invalid: Option<Invalid>, locale: &Locale)
+-> Dom<Msg, Cmd>
+{
+    let input = Dom::elem("input")
+        // a number input has no selection to restore after rendering
+        .attr("inputmode", input_mode(places))
+        .attr("aria-label", locale.t("set_count"))
+        .attr(focus::KEY, "count-input")
+        .attr("value", decimal::plain(count, places))
+        .on("input", Handler::InputValue(|value| Some(Msg::Typed(Field::Count, value))))
+        .on("change", Handler::InputValue(|value| Some(Msg::Entered(Field::Count, value))));
+    checked(input, invalid)
+}
+
+/// Which keyboard to type numbers with `places` decimal places on.
+fn input_mode(places: u32) -> &'static str {
+    if places == 0 { "numeric" } else { "decimal" }
+}
+
+/// A text box to type the step into.
+fn step_input(step: i32, places: u32, invalid: Option<Invalid>, locale: &Locale)
+-> Dom<Msg, Cmd>
+{
+    let input = Dom::elem("input")
+        .attr("inputmode", input_mode(places))
+        .attr(focus::KEY, "step-input")
+        .attr("value", decimal::plain(Count::from(step), places))
+        .on("input", Handler::InputValue(|value| Some(Msg::Typed(Field::Step, value))))
+        .on("change", Handler::InputValue(|value| Some(Msg::Entered(Field::Step, value))));
+
+    Dom::elem("label")
+        .push(text!("{} ", locale.t("step_size")))
+        .push(checked(input, invalid))
+}
+
+/// Flag a text box whose text didn't check out for screen readers.
+fn checked(input: Dom<Msg, Cmd>, invalid: Option<Invalid>) -> Dom<Msg, Cmd> {
+    match invalid {
+        Some(_) => input.attr("aria-invalid", "true"),
+        None => input,
+    }
+}
+
+/// What's wrong with the text typed into the text box before it.
+fn invalid_message(invalid: Invalid, places: u32, locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("span")
+        .attr("class", "invalid")
+        .attr("role", "alert")
+        .push(Dom::text(invalid.message(places, locale)))
+}
+
+/// A text box to type the milliseconds between automatic increments into, empty for none.
+fn auto_increment_input(ms: Option<u32>, locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("label")
+        .push(text!("{} ", locale.t("auto_increment_every")))
+        .push(Dom::elem("input")
+            .attr("inputmode", "numeric")
+            .attr("placeholder", locale.t("off"))
+            .attr(focus::KEY, "auto-increment-input")
+            .attr("value", ms.map(|ms| ms.to_string()).unwrap_or_default())
+            .on("change", Handler::InputValue(|value| match value.trim() {
+                "" => Some(Msg::SetAutoIncrement(None)),
+                ms => ms.parse().ok()
+                    .filter(|&ms| ms >= preferences::MIN_INTERVAL)
+                    .map(|ms| Msg::SetAutoIncrement(Some(ms))),
+            })))
+}
+
+/// A text box to type a goal into, positive or left empty for none.
+fn goal_input(goal: Option<Count>, places: u32, locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("input")
+        .attr("inputmode", input_mode(places))
+        .attr("aria-label", locale.t("set_goal"))
+        .attr("placeholder", locale.t("goal"))
+        .attr(focus::KEY, "goal-input")
+        .attr("value", goal.map(|goal| decimal::plain(goal, places)).unwrap_or_default())
+        .on("change", Handler::InputValue(|value| Some(Msg::Entered(Field::Goal, value))))
+}
+
+/// How far the count is towards the goal, and how far past it once it's exceeded.
+fn goal_progress(count: Count, goal: Count, places: u32, locale: &Locale) -> Dom<Msg, Cmd> {
+    let progress = Dom::elem("progress")
+        .attr("aria-label", locale.t("goal"))
+        .attr("max", goal.to_string())
+        .attr("value", count.clamp(0, goal).to_string());
+
+    if count <= goal {
+        return Dom::elem("div")
+            .attr("class", "goal")
+            .push(progress);
+    }
+
+    let over = locale.decimal(count.saturating_sub(goal), places);
+    Dom::elem("div")
+        .attr("class", "goal exceeded")
+        .push(progress)
+        .push(Dom::elem("span").push(Dom::text(locale.t1("goal_exceeded", &over))))
+}
+
+/// The count, focusable and changed with the arrow keys like a native spin button.
+///
+/// The `displayed` count is shown, screen readers get the true `count`. Both are in units at
+/// `places` decimal places.
+fn counter(count: Count, displayed: Count, changed: bool, places: u32, locale: &Locale)
+-> Dom<Msg, Cmd>
+{
+    let count = Dom::elem("div")
+        .attr("class", if changed { "count changed" } else { "count" })
+        .attr("role", "spinbutton")
+        .attr("tabindex", "0")
+        .attr("aria-label", locale.t("count"))
+        .attr(focus::KEY, "count")
+        .attr("aria-valuenow", decimal::plain(count, places))
+        .on("keydown", on_key!(key_msg))
+        // swipes, drags and pinches over the count are ours, not the browser's to scroll and zoom
+        // with
+        .attr("style", "touch-action: none")
+        .on("wheel", Handler::Event(wheel))
+        .on("touchstart", Handler::Event(touch_start))
+        .on("touchmove", Handler::Event(|e| pinch_distance(&e).map(Msg::Pinch)))
+        .on("touchend", Handler::Event(|e| touch(e).map(|(x, y)| Msg::TouchEnd(x, y))))
+        .on("contextmenu", Handler::Event(|e| {
+            let e: web_sys::MouseEvent = e.dyn_into().ok()?;
+            e.prevent_default();
+            Some(Msg::OpenMenu(e.client_x(), e.client_y()))
+        }));
+
+    on_pointer!(count, scrub)
+        .event("mouseenter", Msg::HoverCount(true))
+        .event("mouseleave", Msg::HoverCount(false))
+        .push(Dom::text(locale.decimal(displayed, places)))
+}
+
+/// The tooltip over the count, saying how much the count last changed by, when, and how long ago.
+///
+/// It's hidden from screen readers, the line saying when the count was updated tells them as much.
+fn last_change(delta: Count, changed_at: f64, now: f64, places: u32, locale: &Locale)
+-> Dom<Msg, Cmd>
+{
+    let sign = if delta > 0 { "+" } else { "" };
+    Dom::elem("div")
+        .attr("class", "tooltip")
+        .attr("role", "tooltip")
+        .attr("aria-hidden", "true")
+        .push(Dom::elem("strong").push(text!("{}{}", sign, locale.decimal(delta, places))))
+        .push(Dom::elem("time").push(Dom::text(locale.time(changed_at))))
+        .push(Dom::elem("span").push(Dom::text(locale.updated_ago(now - changed_at))))
+}
+
+/// A visually hidden region screen readers announce the count from whenever it changes.
+///
+/// The region has to stay in the dom with only its text changing, screen readers announce changes
+/// to live regions but not live regions being added.
+fn announcement(count: Count, places: u32, locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("div")
+        .attr("role", "status")
+        .attr("aria-live", "polite")
+        .attr("style", VISUALLY_HIDDEN)
+        .push(Dom::text(title(count, places, locale)))
+}
+
+/// The message for a key pressed while the count has focus.
+fn key_msg(key: &str) -> Option<Msg> {
+    match key {
+        "ArrowUp" | "ArrowRight" => Some(Msg::Increment),
+        "ArrowDown" | "ArrowLeft" => Some(Msg::Decrement),
+        "PageUp" => Some(Msg::Nudge(10)),
+        "PageDown" => Some(Msg::Nudge(-10)),
+        _ => None,
+    }
+}
+
+/// The message `key_msg` has for the key pressed in a `keydown` event, if it has one, see
+/// `on_key!`.
+fn key_pressed(event: web_sys::Event, key_msg: impl FnOnce(&str) -> Option<Msg>) -> Option<Msg> {
+    let event: web_sys::KeyboardEvent = event.dyn_into().ok()?;
+    let msg = key_msg(&event.key())?;
+    event.prevent_default();
+    Some(msg)
+}
+
+/// Scrub the count by dragging across it, with a mouse, a pen, or a finger.
+fn scrub(phase: Phase, pointer: Pointer) -> Option<Msg> {
+    let msg = match phase {
+        Phase::Down => Msg::ScrubStart(pointer.id, pointer.x),
+        Phase::Move => Msg::ScrubMove(pointer.id, pointer.x),
+        Phase::Up => Msg::ScrubEnd(pointer.id),
+    };
+    Some(msg)
+}
+
+/// Keep stepping the count by `steps` while a step button is held down.
+fn hold(phase: Phase, pointer: Pointer, steps: i32) -> Option<Msg> {
+    match phase {
+        Phase::Down => Some(Msg::HoldStart(pointer.id, steps)),
+        Phase::Move => None,
+        Phase::Up => Some(Msg::HoldEnd(pointer.id)),
+    }
+}
+
+/// Start a swipe, or a pinch once a second finger touches.
+fn touch_start(event: web_sys::Event) -> Option<Msg> {
+    match pinch_distance(&event) {
+        Some(distance) => Some(Msg::PinchStart(distance)),
+        None => touch(event).map(|(x, y)| Msg::TouchStart(x, y)),
+    }
+}
+
+/// How far apart the fingers are, if exactly two are touching.
+fn pinch_distance(event: &web_sys::Event) -> Option<f64> {
+    let touches = event.dyn_ref::<web_sys::TouchEvent>()?.touches();
+    if touches.length() != 2 {
+        return None;
+    }
+
+    let (a, b) = (touches.get(0)?, touches.get(1)?);
+    Some(gesture::distance((a.client_x(), a.client_y()), (b.client_x(), b.client_y())))
+}
+
+/// The client coordinates where a touch event happened.
+fn touch(event: web_sys::Event) -> Option<(i32, i32)> {
+    let event: web_sys::TouchEvent = event.dyn_into().ok()?;
+    let touch = event.changed_touches().get(0)?;
+    Some((touch.client_x(), touch.client_y()))
+}
+
+/// Scroll over the count to change it.
+///
+/// Listeners on elements aren't passive unless asked to be, so we can keep the page from
+/// scrolling while the count changes.
+fn wheel(event: web_sys::Event) -> Option<Msg> {
+    let event: web_sys::WheelEvent = event.dyn_into().ok()?;
+    event.prevent_default();
+
+    match gesture::wheel_steps(event.delta_y(), event.delta_mode()) {
+        0 => None,
+        steps => Some(Msg::Nudge(steps)),
+    }
+}
+
+/// Says the countdown is over, with a button to count down again.
+fn finished(locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("div")
+        .attr("class", "finished banner")
+        .attr("role", "alert")
+        .push(Dom::elem("span").push(Dom::text(locale.t("finished"))))
+        .push(button(locale.t("restart_countdown"), Msg::RestartCountdown))
+}
+
+/// Says the count isn't loaded yet, with a spinner while it's being fetched rather than waiting
+/// for the user to log in.
+fn loading(fetching: bool, locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("div")
+        .attr("class", "loading")
+        .if_then(fetching, spinner)
+        .push(Dom::text(locale.t("loading")))
+}
+
+/// Spins while something's in flight, the text beside it says what.
+fn spinner() -> Dom<Msg, Cmd> {
+    Dom::elem("span")
+        .attr("class", "spinner")
+        .attr("aria-hidden", "true")
+}
+
+/// Says the count is being saved.
+fn saving(locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("span")
+        .attr("class", "saving")
+        .attr("role", "status")
+        .push(spinner())
+        .push(Dom::text(locale.t("saving")))
+}
+
+/// The button copying the count, disabled until the last copy is done.
+fn copy_button(copying: bool, locale: &Locale) -> Dom<Msg, Cmd> {
+    let mut copy = button(locale.t("copy"), Msg::Copy);
+    if copying {
+        copy = copy.attr("disabled", "").attr("aria-busy", "true");
+    }
+    copy
+}
+
+fn context_menu((x, y): (i32, i32), ticking: bool, locale: &Locale) -> Dom<Msg, Cmd> {
+    let item = |text: &str, item| {
+        Dom::elem("button")
+            .attr("role", "menuitem")
+            .event("click", Msg::MenuItem(item))
+            .push(Dom::text(text))
+    };
+
+    let auto_increment = if ticking { "stop_auto_increment" } else { "start_auto_increment" };
+    Dom::elem("div")
+        .attr("class", menu::CLASS)
+        .attr("role", "menu")
+        .attr("style", format!("position: fixed; left: {}px; top: {}px", x, y))
+        .push(item(locale.t("reset"), menu::Item::Reset))
+        .push(item(locale.t("copy_value"), menu::Item::Copy))
+        .push(item(locale.t(auto_increment), menu::Item::ToggleAutoIncrement))
+}
+
+/// A layer over the page for confetti to fall through, hidden from screen readers.
+fn confetti(confetti: &Confetti) -> Dom<Msg, Cmd> {
+    Dom::elem("div")
+        .attr("class", "confetti")
+        .attr("aria-hidden", "true")
+        .attr("style", "position: fixed; inset: 0; overflow: hidden; pointer-events: none")
+        .extend(confetti.pieces().into_iter().map(|style| Dom::elem("div").attr("style", style)))
+}
+
+/// A button expanding the statistics panel, followed by the panel if it's expanded.
+fn statistics(stats: &Stats, open: bool, locale: &Locale) -> Vec<Dom<Msg, Cmd>> {
+    let toggle = button(locale.t("statistics"), Msg::ToggleStats)
+        .attr("aria-expanded", if open { "true" } else { "false" });
+
+    vec![toggle].if_then(open, || stats_list(stats, locale))
+}
+
+/// The statistics, each labelled.
+fn stats_list(stats: &Stats, locale: &Locale) -> Dom<Msg, Cmd> {
+    let rows = vec![
+        ("total_clicks", stats.clicks().to_string()),
+        ("increments", stats.increments.to_string()),
+        ("decrements", stats.decrements.to_string()),
+        ("streak", stats.streak.to_string()),
+        ("clicks_per_minute", stats.per_minute().to_string()),
+    ];
+    let rows = rows.into_iter().flat_map(|(label, value)| vec![
+        Dom::elem("dt").push(Dom::text(locale.t(label))),
+        Dom::elem("dd").push(Dom::text(value)),
+    ]);
+    Dom::elem("dl").attr("class", "stats").extend(rows)
+}
+
+/// The whole history as a table, oldest first, for the print summary.
+fn history_table(history: &VecDeque<Entry>, places: u32, locale: &Locale) -> Dom<Msg, Cmd> {
+    let header = Dom::elem("tr")
+        .push(Dom::elem("th").attr("scope", "col").push(Dom::text(locale.t("time"))))
+        .push(Dom::elem("th").attr("scope", "col").push(Dom::text(locale.t("change"))))
+        .push(Dom::elem("th").attr("scope", "col").push(Dom::text(locale.t("count"))));
+    let rows = history.iter().map(|entry| {
+        Dom::elem("tr")
+            .push(Dom::elem("td").push(Dom::text(locale.time(entry.time))))
+            .push(Dom::elem("td").push(Dom::text(locale.t(entry.action.label()))))
+            .push(Dom::elem("td").push(Dom::text(locale.decimal(entry.count, places))))
+    });
+    Dom::elem("table")
+        .push(Dom::elem("thead").push(header))
+        .push(Dom::elem("tbody").extend(rows))
+}
+
+/// The diagnostics overlay, with how much memory was in use when it was last sampled and the frame
+/// rate while something is animating.
+fn diagnostics_overlay(memory: Option<Memory>, fps: Option<f64>, locale: &Locale) -> Dom<Msg, Cmd> {
+    let memory = memory.unwrap_or_default();
+    let mut rows = vec![("wasm_memory", diagnostics::megabytes(memory.linear))];
+    if let Some((used, total)) = memory.heap {
+        let heap = format!("{} / {}", diagnostics::megabytes(used), diagnostics::megabytes(total));
+        rows.push(("js_heap", heap));
+    }
+    if let Some(fps) = fps {
+        rows.push(("frames_per_second", format!("{:.0}", fps)));
+    }
+
+    let rows = rows.into_iter().flat_map(|(label, value)| vec![
+        Dom::elem("dt").push(Dom::text(locale.t(label))),
+        Dom::elem("dd").push(Dom::text(value)),
+    ]);
+    Dom::elem("dl")
+        .attr("class", "diagnostics")
+        .attr("aria-hidden", "true")
+        .extend(rows)
+}
+
+/// The focus key of a tab in the tab list.
+fn tab_key(tab: Tab) -> String {
+    format!("tab-{}", tab.as_str())
+}
+
+/// The tabs to pick from, the arrow keys move between them like in a native tab list.
+fn tab_list(current: Tab, locale: &Locale) -> Dom<Msg, Cmd> {
+    let tabs = Tab::ALL.iter().map(|&tab| {
+        let selected = tab == current;
+        Dom::elem("button")
+            .attr("role", "tab")
+            .attr(focus::KEY, tab_key(tab))
+            .attr("aria-selected", if selected { "true" } else { "false" })
+            // only the selected tab is in the tab order
+            .attr("tabindex", if selected { "0" } else { "-1" })
+            .event("click", Msg::SelectTab(tab))
+            .push(Dom::text(locale.t(tab.label())))
+    });
+
+    Dom::elem("div")
+        .attr("class", "tabs")
+        .attr("role", "tablist")
+        .on("keydown", on_key! {
+            "ArrowRight" => Msg::CycleTab(1),
+            "ArrowLeft" => Msg::CycleTab(-1),
+        })
+        .extend(tabs)
+}
+
+/// The stopwatch's time and controls, and its laps once there are any.
+fn stopwatch(stopwatch: &Stopwatch, locale: &Locale) -> Dom<Msg, Cmd> {
+    let control = |text, msg| button(locale.t(text), Msg::Stopwatch(msg));
+    let toggle = if stopwatch.running() {
+        control("stop", stopwatch::Msg::Stop)
+    }
+    else {
+        control("start", stopwatch::Msg::Start)
+    };
+
+    let section = Dom::elem("section")
+        .attr("class", "stopwatch")
+        .attr("aria-label", locale.t("stopwatch"))
+        .push(Dom::elem("time")
+            .attr("class", "elapsed")
+            .push(Dom::text(stopwatch::format(stopwatch.elapsed()))))
+        .push(toggle)
+        .push(control("lap", stopwatch::Msg::Lap))
+        .push(control("reset_stopwatch", stopwatch::Msg::Reset));
+
+    section.if_then(!stopwatch.laps.is_empty(), || {
+        let laps = stopwatch.laps.iter().enumerate();
+        // laps are only ever added, keyed by their number
+        Dom::elem("ol")
+            .attr("class", "laps")
+            .keyed_children_from(laps, |&(i, _)| i as u64, |(i, &lap)| {
+                Dom::elem("li")
+                    .push(text!("{} ", locale.t1("lap_n", &(i + 1).to_string())))
+                    .push(Dom::elem("time").push(Dom::text(stopwatch::format(lap))))
+            })
+    })
+}
+
+/// The changes to the count, newest first, scrolling once there are more than fit.
+///
+/// Only the entries scrolled into view are rendered, there can be thousands.
+fn history(entries: &[&Entry], viewport: Viewport, places: u32, locale: &Locale)
+-> Vec<Dom<Msg, Cmd>>
+{
+    let total = entries.len();
+    let visible = viewport.rows(total);
+    let (above, below) = viewport.padding(&visible, total);
+
+    vec![
+        Dom::elem("ol")
+            .attr("class", "history")
+            .attr("aria-label", locale.t("history"))
+            // so the list can be scrolled with the keyboard
+            .attr("tabindex", "0")
+            .attr("style", format!("padding-top: {}px; padding-bottom: {}px", above, below))
+            .on("scroll", Handler::Event(history_scrolled))
+            .keyed_children_from(history_rows(entries, visible), |&(key, _)| key, |(key, entry)| {
+                history_row(entry, key, total, places, locale)
+            }),
+    ]
+}
+
+/// One page of `size` changes to the count, with buttons to turn the page.
+fn paged_history(entries: &[&Entry], size: usize, page: usize, places: u32, locale: &Locale)
+-> Vec<Dom<Msg, Cmd>>
+{
+    let pages = history::pages(entries.len(), size);
+    let page = page.min(pages - 1);
+    let rows = history::page(entries.len(), size, page);
+
+    let mut previous = button(locale.t("previous_page"), Msg::TurnHistoryPage(Turn::Previous));
+    if page == 0 {
+        previous = previous.attr("disabled", "");
+    }
+    let mut next = button(locale.t("next_page"), Msg::TurnHistoryPage(Turn::Next));
+    if page + 1 == pages {
+        next = next.attr("disabled", "");
+    }
+
+    vec![
+        Dom::elem("ol")
+            .attr("class", "history paged")
+            .attr("aria-label", locale.t("history"))
+            // so the page can be turned with the keyboard
+            .attr("tabindex", "0")
+            .on("keydown", on_key!(|key| Turn::for_key(key).map(Msg::TurnHistoryPage)))
+            .keyed_children_from(history_rows(entries, rows), |&(key, _)| key, |(key, entry)| {
+                history_row(entry, key, entries.len(), places, locale)
+            }),
+        Dom::elem("div")
+            .attr("class", "pages")
+            .push(previous)
+            .push(Dom::elem("span")
+                .push(Dom::text(locale.t2("page_of", &(page + 1).to_string(), &pages.to_string()))))
+            .push(next),
+    ]
+}
+
+/// The filters for the history, with `search` typed in the search box.
+fn history_filter(filter: &Filter, search: &str, locale: &Locale) -> Dom<Msg, Cmd> {
+    let chip = |text: &str, pressed: bool, msg| {
+        button(text, msg)
+            .attr("class", "chip")
+            .attr("aria-pressed", if pressed { "true" } else { "false" })
+    };
+
+    Dom::elem("div")
+        .attr("class", "history-filter")
+        .push(Dom::elem("input")
+            .attr("type", "search")
+            .attr("aria-label", locale.t("search_history"))
+            .attr("placeholder", locale.t("search_history"))
+            .attr(focus::KEY, "history-search")
+            .attr("value", search)
+            .on("input", Handler::InputValue(|value| Some(Msg::SearchHistory(value))))
+        )
+        .push(chip(locale.t("increments_only"), filter.increments_only, Msg::ToggleIncrementsOnly))
+        .push(chip(locale.t("today"), filter.today, Msg::ToggleToday))
+}
+
+/// The history entries in `rows`, counting from the newest, each with its position counting from
+/// the oldest. New entries go on the end, so that stays the same as they're added.
+fn history_rows<'a>(entries: &'a [&'a Entry], rows: Range<usize>)
+-> impl Iterator<Item = (u64, &'a Entry)> + 'a
+{
+    entries.iter().enumerate().rev().skip(rows.start).take(rows.len())
+        .map(|(position, &entry)| (position as u64, entry))
+}
+
+/// The history entry at `position` of `total`, counting from the oldest.
+fn history_row(entry: &Entry, position: u64, total: usize, places: u32, locale: &Locale)
+-> Dom<Msg, Cmd>
+{
+    Dom::elem("li")
+        // screen readers only see the rendered rows, tell them where these are in the rest
+        .attr("aria-posinset", (total - position as usize).to_string())
+        .attr("aria-setsize", total.to_string())
+        .push(Dom::elem("time").push(Dom::text(locale.time(entry.time))))
+        .push(text!(" {} ", locale.t(entry.action.label())))
+        .push(Dom::elem("span")
+            .attr("class", "count")
+            .push(Dom::text(locale.decimal(entry.count, places))))
+}
+
+/// Where the history is scrolled to, measuring its rows while we're at it.
+fn history_scrolled(event: web_sys::Event) -> Option<Msg> {
+    let list: web_sys::Element = event.target()?.dyn_into().ok()?;
+    let row_height = list.query_selector("li").ok().flatten()
+        .and_then(|row| row.dyn_into::<web_sys::HtmlElement>().ok())
+        .map(|row| f64::from(row.offset_height()))
+        .filter(|&height| height > 0.0)
+        .unwrap_or(Viewport::default().row_height);
+
+    Some(Msg::HistoryScrolled(Viewport {
+        scroll_top: f64::from(list.scroll_top()),
+        height: f64::from(list.client_height()),
+        row_height,
+    }))
+}
+
+/// The toasts shown, newest last.
+///
+/// The list stays in the dom when there are none, so screen readers announce the toasts added.
+fn toasts(toasts: &Toasts, locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("div")
+        .attr("class", "toasts")
+        .attr("aria-live", "polite")
+        .keyed_children_from(toasts.iter(), |toast| u64::from(toast.id), |toast| {
+            let toast_div = Dom::elem("div").attr("class", toast.level.class());
+            let toast_div = match toast.level {
+                Level::Success => toast_div,
+                Level::Error => toast_div.attr("role", "alert"),
+            };
+            toast_div
+                .push(Dom::elem("span").push(Dom::text(toast.text.as_str())))
+                .push(labelled_button("×", locale.t("dismiss"), Msg::DismissToast(toast.id)))
+        })
+}
+
+/// Why the last click didn't change the count as far as it should have, with a button to close it.
+fn warning(limit: Limit, locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("div")
+        .attr("class", "warning")
+        .attr("role", "alert")
+        .push(Dom::elem("span").push(Dom::text(locale.t(limit.label()))))
+        .push(labelled_button("×", locale.t("dismiss"), Msg::DismissWarning))
+}
+
+fn notifications(permission: Permission, locale: &Locale) -> Option<Dom<Msg, Cmd>> {
+    let status = match permission {
+        Permission::Default => {
+            return Some(button(locale.t("enable_notifications"), Msg::EnableNotifications));
+        }
+        Permission::Granted => "notifications_on",
+        Permission::Denied => "notifications_blocked",
+        Permission::Unsupported => return None,
+    };
+
+    Some(Dom::elem("span")
+        .attr("class", "notifications")
+        .push(Dom::text(locale.t(status))))
+}
+
+/// A banner offering to reload the page with the new version of the app.
+#[cfg(feature = "pwa")]
+fn update_banner(locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("div")
+        .attr("class", "banner")
+        .attr("role", "status")
+        .push(Dom::elem("span").push(Dom::text(locale.t("update_available"))))
+        .push(button(locale.t("reload"), Msg::ReloadForUpdate))
+}
+
+#[cfg(feature = "voice")]
+fn voice_controls(voice: &voice::Voice, locale: &Locale) -> Vec<Dom<Msg, Cmd>> {
+    if !voice.supported {
+        return vec![];
+    }
+
+    let mut dom = vec![];
+    if voice.listening {
+        dom.push(button(locale.t("stop_voice_commands"), Msg::ToggleVoice));
+        dom.push(Dom::elem("span")
+            .attr("class", "listening")
+            .attr("role", "status")
+            .push(Dom::text(locale.t("listening"))));
+    }
+    else {
+        dom.push(button(locale.t("voice_commands"), Msg::ToggleVoice));
+    }
+
+    if let Some(ref e) = voice.error {
+        dom.push(error(e));
+    }
+
+    dom
+}
+
+fn pending(changes: usize, locale: &Locale) -> Dom<Msg, Cmd> {
+    let text = match changes {
+        1 => locale.t("pending_one").to_owned(),
+        n => locale.t1("pending_many", &n.to_string()),
+    };
+
+    Dom::elem("div")
+        .attr("class", "pending")
+        .attr("role", "status")
+        .push(Dom::text(text))
+}
+
+fn login_form(username: &str, busy: bool, locale: &Locale) -> Dom<Msg, Cmd> {
+    let mut submit = Dom::elem("button")
+        .attr("type", "submit")
+        .push(Dom::text(locale.t("log_in")));
+    if busy {
+        submit = submit.attr("disabled", "");
+    }
+
+    Dom::elem("form")
+        .attr("class", "login")
+        .on("submit", Handler::Event(|e| {
+            e.prevent_default();
+            Some(Msg::LogIn)
+        }))
+        .push(Dom::elem("input")
+            .attr("name", "username")
+            .attr("autocomplete", "username")
+            .attr("placeholder", locale.t("username"))
+            .attr("aria-label", locale.t("username"))
+            .attr("value", username)
+            .on("input", Handler::InputValue(|value| Some(Msg::SetUsername(value))))
+        )
+        .push(Dom::elem("input")
+            .attr("name", "password")
+            .attr("type", "password")
+            .attr("autocomplete", "current-password")
+            .attr("placeholder", locale.t("password"))
+            .attr("aria-label", locale.t("password"))
+            .on("input", Handler::InputValue(|value| Some(Msg::SetPassword(value))))
+        )
+        .push(submit)
+}
+
+/// A file input to import exported state from.
+fn import(locale: &Locale) -> Dom<Msg, Cmd> {
+    Dom::elem("label")
+        .attr("class", "import")
+        .push(text!("{} ", locale.t("import_state")))
+        .push(Dom::elem("input")
+            .attr("type", "file")
+            .attr("accept", "application/json,.json")
+            .on("change", Handler::Event(|e| {
+                let input: web_sys::HtmlInputElement = e.target()?.dyn_into().ok()?;
+                let file = input.files()?.get(0)?;
+                // so picking the same file again imports it again
+                input.set_value("");
+                Some(Msg::ImportState(file))
+            }))
+        )
+}
+
+fn error(text: &str) -> Dom<Msg, Cmd> {
+    Dom::elem("div")
+        .attr("class", "error banner")
+        .attr("role", "alert")
+        .push(Dom::text(text))
+}
+
+impl Validate for Model {
+    fn validate(&self) -> Result<(), String> {
+        if self.preferences.step == 0 {
+            return Err("the step is 0".to_owned());
+        }
+        if self.preferences.step.unsigned_abs() > preferences::MAX_STEP as u32 {
+            return Err(format!("the step is {}", self.preferences.step));
+        }
+        if self.preferences.places > decimal::MAX_PLACES {
+            return Err(format!("the count has {} decimal places", self.preferences.places));
+        }
+        if self.countdown.is_some() && self.count < 0 {
+            return Err(format!("the countdown is at {}", self.count));
+        }
+        if let Some(goal) = self.goal.filter(|&goal| goal <= 0) {
+            return Err(format!("the goal is {}", goal));
+        }
+        if self.history.len() > history::MAX_ENTRIES {
+            return Err(format!("the history has {} entries", self.history.len()));
+        }
+        if self.history_page_size == Some(0) {
+            return Err("the history has pages of 0 entries".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+impl Render<DomVec<Msg, Cmd>> for Model {
+    fn render(&self) -> DomVec<Msg, Cmd> {
+        let dom = self.render_root();
+
+        // rendering the same model again has to come out the same
+        #[cfg(all(feature = "strict", debug_assertions))]
+        strict::check(&dom, &self.render_root());
+
+        // point out anything a screen reader couldn't name while developing
+        #[cfg(debug_assertions)]
+        a11y::warn(&dom);
+
+        dom
+    }
+}
+
+impl Model {
+    /// Everything the counter shows, under the root node.
+    fn render_root(&self) -> DomVec<Msg, Cmd> {
+        let root = root(self.preferences.theme, self.high_contrast(), self.idle, self.view())
+            .attr("dir", self.direction.as_str());
+        vec![root].into()
+    }
+
+    /// Controls for how the counter looks.
+    fn settings(&self) -> Dom<Msg, Cmd> {
+        let locale = self.locale();
+        let scheme = match self.scheme() {
+            Scheme::Light => "dark_theme",
+            Scheme::Dark => "light_theme",
+        };
+        let contrast = if self.high_contrast() { "normal_contrast" } else { "high_contrast" };
+
+        let themes = theme::THEMES.iter()
+            .map(|theme| (theme.name, theme.name))
+            .collect();
+        let directions = Direction::ALL.iter()
+            .map(|direction| (direction.as_str(), locale.t(direction.label())))
+            .collect();
+        let locales = locale::LOCALES.iter()
+            .map(|locale| (locale.tag, locale.name))
+            .collect();
+        // each number of places shown as what one looks like with them
+        let examples: Vec<(String, String)> = (0..=decimal::MAX_PLACES)
+            .map(|places| (places.to_string(), locale.decimal(decimal::scale(places), places)))
+            .collect();
+        let places_options = examples.iter()
+            .map(|(value, text)| (value.as_str(), text.as_str()))
+            .collect();
+        let places = self.preferences.places;
+        let current_places = places.to_string();
+        let policies = Overflow::ALL.iter()
+            .map(|overflow| (overflow.as_str(), locale.t(overflow.label())))
+            .collect();
+        let overflow = self.preferences.overflow.as_str();
+        let sounds = || {
+            let checkbox = Dom::elem("input")
+                .attr("type", "checkbox")
+                .attr(focus::KEY, "sounds")
+                .event("change", Msg::ToggleMute);
+            let checkbox = if self.preferences.muted {
+                checkbox
+            }
+            else {
+                checkbox.attr("checked", "")
+            };
+            Dom::elem("label")
+                .push(checkbox)
+                .push(text!(" {}", locale.t("sounds")))
+        };
+
+        Dom::elem("fieldset")
+            .attr("class", "settings")
+            .push(Dom::elem("legend").push(Dom::text(locale.t("settings"))))
+            .push(button(locale.t(scheme), Msg::ToggleTheme))
+            .push(picker(locale.t("theme"), themes, self.preferences.theme.name, |name| {
+                Some(Msg::PickTheme(name))
+            }))
+            .push(button(locale.t(contrast), Msg::ToggleContrast))
+            .push(picker(locale.t("direction"), directions, self.direction.as_str(), |dir| {
+                Direction::parse(&dir).map(Msg::SetDirection)
+            }))
+            .push(picker(locale.t("language"), locales, locale.tag, |tag| {
+                Some(Msg::PickLocale(tag))
+            }))
+            .push(picker(locale.t("decimal_places"), places_options, &current_places, |places| {
+                places.parse().ok().map(Msg::SetPlaces)
+            }))
+            .push(step_input(self.preferences.step, places, self.step_invalid, locale))
+            .maybe(self.step_invalid, |invalid| invalid_message(invalid, places, locale))
+            .push(picker(locale.t("overflow"), policies, overflow, |name| {
+                Overflow::parse(&name).map(Msg::SetOverflow)
+            }))
+            .if_then(self.sounds, sounds)
+            .push(auto_increment_input(self.preferences.auto_increment, locale))
+            .push(button(locale.t("reset_daily"), Msg::ToggleDailyReset)
+                .attr("aria-pressed", if self.daily_reset.is_some() { "true" } else { "false" }))
+            .push(button(locale.t("export_state"), Msg::ExportState))
+            .push(button(locale.t("export_image"), Msg::ExportImage))
+            .push(button(locale.t("print"), Msg::Print))
+            .push(import(locale))
+    }
+
+    fn view(&self) -> Vec<Dom<Msg, Cmd>> {
+        let locale = self.locale();
+        if self.needs_login() {
+            let mut dom = vec![login_form(&self.username, self.logging_in, locale)];
+            if let Some(ref e) = self.login_error {
+                dom.push(error(&e.message(locale)));
+            }
+            return dom;
+        }
+
+        if self.printing {
+            return vec![self.print_summary()];
+        }
+
+        // only the tab shown is rendered
+        let panel = match self.tab {
+            Tab::Counter => self.counter_tab(),
+            Tab::Stopwatch => vec![stopwatch(&self.stopwatch, locale)],
+            Tab::History => self.history_tab(),
+            Tab::Settings => vec![self.settings()],
+        };
+        let class = match self.layout {
+            Mode::Horizontal => "panel",
+            Mode::Vertical => "panel vertical",
+        };
+        let mut dom = vec![
+            tab_list(self.tab, locale),
+            Dom::elem("div")
+                .attr("class", class)
+                .attr("role", "tabpanel")
+                .attr("aria-label", locale.t(self.tab.label()))
+                .extend(panel),
+        ];
+
+        if let Some(ref e) = self.sync_error {
+            dom.push(error(e));
+        }
+        if let Some(ref e) = self.import_error {
+            dom.push(error(&e.message(locale)));
+        }
+
+        #[cfg(feature = "pwa")]
+        {
+            if self.service_worker == pwa::Status::UpdateReady {
+                dom.push(update_banner(locale));
+            }
+            if self.install == pwa::Install::Available {
+                dom.push(button(locale.t("install_app"), Msg::Install));
+            }
+        }
+
+        if self.login_url.is_some() {
+            dom.push(button(locale.t("log_out"), Msg::LoggedOut));
+        }
+
+        dom.push(toasts(&self.toasts, locale));
+
+        if let Some(ref thrown) = self.confetti {
+            dom.push(confetti(thrown));
+        }
+
+        if self.diagnostics {
+            dom.push(diagnostics_overlay(self.memory, self.fps.rate(), locale));
+        }
+
+        if let Some(question) = self.confirming {
+            let (cancel, confirm) = (locale.t("cancel"), locale.t(question.confirm()));
+            dom.push(modal::view(locale.t(question.text()), cancel, confirm, question.msg()));
+        }
+
+        dom
+    }
+
+    /// The count, statistics, and history, laid out to be printed.
+    fn print_summary(&self) -> Dom<Msg, Cmd> {
+        let locale = self.locale();
+        let places = self.preferences.places;
+        let history = if self.history.is_empty() {
+            Dom::elem("p").push(Dom::text(locale.t("no_history")))
+        }
+        else {
+            history_table(&self.history, places, locale)
+        };
+
+        Dom::elem("div")
+            .attr("class", "summary")
+            .push(Dom::elem("h1").push(Dom::text(title(self.count, places, locale))))
+            .push(Dom::elem("h2").push(Dom::text(locale.t("statistics"))))
+            .push(stats_list(&self.stats, locale))
+            .push(Dom::elem("h2").push(Dom::text(locale.t("history"))))
+            .push(history)
+    }
+
+    /// The count and everything for changing it.
+    fn counter_tab(&self) -> Vec<Dom<Msg, Cmd>> {
+        let locale = self.locale();
+        let places = self.preferences.places;
+        let mut dom = if self.loading {
+            vec![loading(self.in_flight.busy(Task::Fetch), locale)]
+        }
+        else {
+            let mut dom = vec![
+                step_button(true, locale),
+                counter(self.count, self.displayed(), self.changed, places, locale)
+                    .maybe(self.changed_at.filter(|_| self.hovering), |changed_at| {
+                        last_change(self.last_delta, changed_at, self.now, places, locale)
+                    }),
+                step_button(false, locale),
+            ];
+            dom.push(copy_button(self.in_flight.busy(Task::Copy), locale));
+            dom.push(announcement(self.count, places, locale));
+            if self.finished {
+                dom.push(finished(locale));
+            }
+            dom
+        };
+
+        // holds the warning's place, so the count's text box isn't replaced when it comes and goes
+        dom.push(match self.warning {
+            Some(limit) => warning(limit, locale),
+            None => Dom::empty(),
+        });
+
+        if let Some(position) = self.menu {
+            let ticking = self.preferences.auto_increment.is_some() && !self.paused;
+            dom.push(context_menu(position, ticking, locale));
+        }
+
+        if !self.loading {
+            dom.push(count_input(self.count, places, self.count_invalid, locale));
+            if let Some(invalid) = self.count_invalid {
+                dom.push(invalid_message(invalid, places, locale));
+            }
+            dom.push(goal_input(self.goal, places, locale));
+            if let Some(goal) = self.goal {
+                dom.push(goal_progress(self.count, goal, places, locale));
+            }
+            if let Some(changed_at) = self.changed_at {
+                dom.push(Dom::elem("span")
+                    .attr("class", "updated")
+                    .push(Dom::text(locale.updated_ago(self.now - changed_at))));
+            }
+        }
+
+        if self.in_flight.busy(Task::Save) {
+            dom.push(saving(locale));
+        }
+        if let Some(ref queued) = self.pending {
+            dom.push(pending(queued.changes, locale));
+        }
+
+        if !self.milestones.is_empty() {
+            dom.extend(notifications(self.notifications, locale));
+        }
+
+        if self.pinch.is_some() {
+            dom.push(Dom::elem("span")
+                .attr("class", "step")
+                .attr("role", "status")
+                .push(Dom::text(locale.t1("step", &locale.decimal(self.step(), places)))));
+        }
+
+        if self.sounds {
+            let text = if self.preferences.muted { "unmute" } else { "mute" };
+            dom.push(button(locale.t(text), Msg::ToggleMute));
+        }
+
+        if self.haptics && self.can_vibrate {
+            let text = if self.vibrate { "vibration_off" } else { "vibration_on" };
+            dom.push(button(locale.t(text), Msg::ToggleVibration));
+        }
+
+        if self.speech && self.can_speak {
+            let text = if self.speak { "stop_speaking" } else { "speak" };
+            dom.push(button(locale.t(text), Msg::ToggleSpeech));
+        }
+
+        #[cfg(feature = "voice")]
+        dom.extend(voice_controls(&self.voice, locale));
+
+        dom.extend(statistics(&self.stats, self.stats_open, locale));
+        dom
+    }
+
+    /// The changes to the count, charted and listed.
+    fn history_tab(&self) -> Vec<Dom<Msg, Cmd>> {
+        let locale = self.locale();
+        let places = self.preferences.places;
+        if self.history.is_empty() {
+            return vec![Dom::elem("p")
+                .attr("class", "history")
+                .push(Dom::text(locale.t("no_history")))];
+        }
+
+        let mut dom = vec![];
+        if self.history.len() > 1 {
+            // SAFETY: the chart is markup built from numbers and escaped text
+            dom.push(unsafe {
+                Dom::elem("div")
+                    .attr("class", "chart")
+                    .inner_html(self.chart.get(|| chart::svg(&self.history, places, locale))
+                        .to_string())
+            });
+        }
+        dom.push(history_filter(&self.history_filter, &self.search, locale));
+
+        let entries = self.filtered_history();
+        match self.history_page_size {
+            _ if entries.is_empty() => {
+                dom.push(Dom::elem("p")
+                    .attr("class", "history")
+                    .push(Dom::text(locale.t("no_matches"))));
+            }
+            Some(size) => {
+                dom.extend(paged_history(&entries, size, self.history_page, places, locale));
+            }
+            None => dom.extend(history(&entries, self.history_viewport, places, locale)),
+        }
+
+        dom.push(button(locale.t("download_csv"), Msg::ExportHistory));
+        dom.push(Dom::elem("button")
+            .attr(focus::KEY, "clear-history")
+            .event("click", Msg::Ask(Question::ClearHistory))
+            .push(Dom::text(locale.t("clear_history"))));
+        dom
+    }
+}
+
+cfg_if! {
+    if #[cfg(feature = "console_error_panic_hook")] {
+        fn set_panic_hook() {
+            console_error_panic_hook::set_once();
+        }
+    }
+    else {
+        fn set_panic_hook() {}
+    }
+}
+
+cfg_if! {
+    if #[cfg(feature = "console_log")] {
+        fn init_log() {
+            // this fails if a logger was already set up by an earlier call to `run()`, which is
+            // fine
+            let _ = console_log::init_with_level(log::Level::Trace);
+        }
+    }
+    else {
+        fn init_log() {}
+    }
+}
+
+/// Throw away the stashed models and start over, used to recover from a panic.
+fn reset_app() {
+    storage::remove_session_prefixed(STASH_KEY);
+
+    if let Some(window) = web_sys::window() {
+        if let Err(e) = window.location().reload() {
+            log::error!("error reloading page: {:?}", e);
+        }
+    }
+}
+
+/// Common setup needed before attaching any apps.
+fn setup() -> Result<(), JsValue> {
+    set_panic_hook();
+    init_log();
+    boundary::install(reset_app);
+
+    if boundary::is_poisoned() {
+        return Err("the app crashed, reload the page to restart it".into());
+    }
+
+    Ok(())
+}
+
+fn document() -> Result<web_sys::Document, JsValue> {
+    web_sys::window()
+        .ok_or("couldn't get window handle")?
+        .document()
+        .ok_or_else(|| "couldn't get document handle".into())
+}
+
+/// A running app.
+type App = Rc<RefCell<Box<dyn Application<Msg, Cmd>>>>;
+
+/// Attach a new, independent app to `parent`.
+///
+/// The app's model is stashed under `stash_key`, so reloading the page restores its count.
+/// Settings in `data-*` attributes on the `host` element take precedence over the given config,
+/// and initial state embedded in the page for the host takes precedence over both. The `host` is
+/// also where `countchanged` events are fired. Usually the host is the parent, but custom
+/// elements render into a container inside themselves.
+fn mount(host: &web_sys::Element, parent: web_sys::Element, stash_key: String, config: &AppConfig)
+-> (App, Shared)
+{
+    boundary::watch(&parent);
+
+    let mut config = config.clone().with_data_attributes(host);
+    if let Some(state) = InitialState::load(host) {
+        config = config.with_initial_state(&state);
+    }
+
+    let mut model = Model::configure(&config, stash_key).restore();
+    model.offline = !sync::is_online();
+    model.replica = tabs::replica_id(&model.shared, &model.replica_key());
+    model.notifications = notify::permission();
+    model.can_vibrate = haptics::supported();
+    model.can_speak = speech::supported();
+    model.preferred_scheme = theme::preferred();
+    model.prefers_contrast = theme::prefers_high_contrast();
+    model.reduced_motion = theme::prefers_reduced_motion();
+    model.layout = layout::current();
+    if model.hash_routing {
+        model.tab = route::current().unwrap_or(Tab::Counter);
+    }
+    model.preferred_locale = locale::preferred();
+    #[cfg(feature = "voice")]
+    {
+        model.voice.supported = voice::supported();
+    }
+    let shared = model.shared.clone();
+    shared.set_element(host.clone());
+
+    let app = AppBuilder::default()
+        .attach(parent, model);
+
+    Dispatcher::from(&app).dispatch(Msg::Init);
+
+    (app, shared)
+}
+
+/// Attach an app to an element found with a selector.
+///
+/// The app's stash key is derived from the selector and the element's position among the
+/// matches.
+fn mount_selected(parent: web_sys::Element, selector: &str, index: u32, config: &AppConfig)
+-> CounterHandle
+{
+    let stash_key = format!("{}:{}:{}", STASH_KEY, selector, index);
+    let (_, shared) = mount(&parent.clone(), parent, stash_key, config);
+    CounterHandle::new(shared)
+}
+
+/// Attach the counter to the `<main>` element.
+#[wasm_bindgen]
+pub fn run() -> Result<CounterHandle, JsValue> {
+    run_with_selector("main")
+}
+
+/// Attach the counter to the first element matching the given CSS selector.
+#[wasm_bindgen]
+pub fn run_with_selector(selector: &str) -> Result<CounterHandle, JsValue> {
+    run_with_config(selector, &AppConfig::default())
+}
+
+/// Attach a counter configured by the host page to the first element matching the given CSS
+/// selector.
+#[wasm_bindgen]
+pub fn run_with_config(selector: &str, config: &AppConfig) -> Result<CounterHandle, JsValue> {
+    setup()?;
+
+    let parent = document()?
+        .query_selector(selector)
+        .map_err(|e| format!("error querying for {}: {:?}", selector, e))?
+        .ok_or_else(|| format!("no element matches {}", selector))?;
+
+    Ok(mount_selected(parent, selector, 0, config))
+}
+
+/// Attach a separate counter to every element matching the given CSS selector.
+///
+/// Returns a handle for each counter attached.
+#[wasm_bindgen]
+pub fn run_all(selector: &str) -> Result<Vec<CounterHandle>, JsValue> {
+    setup()?;
+
+    let parents = document()?
+        .query_selector_all(selector)
+        .map_err(|e| format!("error querying for {}: {:?}", selector, e))?;
+
+    (0..parents.length())
+        .map(|i| {
+            let parent = parents.item(i)
+                .and_then(|node| node.dyn_into::<web_sys::Element>().ok())
+                .ok_or_else(|| format!("match {} for {} is not an element", i, selector))?;
+
+            Ok(mount_selected(parent, selector, i, &AppConfig::default()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // we can test the model in isolation by initializing it, then sending it the messages we want,
+    // and checking that it's state is as expected. This can be done by checking individual
+    // elements in the model or if the model implements PartialEq, we can check the whole model at
+    // once.
+    #[test]
+    fn increment() {
+        let mut model = Model::new();
+        model.update(Msg::Increment, &mut Commands::default());
+        assert_eq!(model.count, 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let mut model = Model::new();
+        model.update(Msg::Decrement, &mut Commands::default());
+        assert_eq!(model.count, -1);
+    }
+
+    #[test]
+    fn set_count() {
+        let mut model = Model::new();
+        model.update(Msg::SetCount(42), &mut Commands::default());
+        assert_eq!(model.count, 42);
+    }
+
+    #[test]
+    fn fetch_count() {
+        let mut config = AppConfig::new();
+        config.set_count_url(Some("/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(model.loading);
+        assert!(cmds.immediate.contains(&Cmd::FetchCount {
+            shared: model.shared.clone(),
+            url: "/count".to_owned(),
+            token: None,
+        }));
+
+        model.update(Msg::CountLoaded(Ok(7)), &mut Commands::default());
+        assert!(!model.loading);
+        assert_eq!(model.count, 7);
+    }
+
+    #[test]
+    fn fetch_count_failed() {
+        let mut model = Model::new();
+        model.loading = true;
+        model.max_retries = 0;
+        let msg = Msg::CountLoaded(Err("server error: 500".to_owned()));
+        model.update(msg, &mut Commands::default());
+        assert!(!model.loading);
+        assert_eq!(model.count, 0);
+        assert_eq!(model.sync_error.as_deref(), Some("server error: 500"));
+    }
+
+    /// Pull any delayed messages out of the given commands, other than the ones ending a change's
+    /// `changed` class.
+    fn delayed(cmds: &Commands<Cmd>) -> Vec<Msg> {
+        cmds.immediate.iter()
+            .filter_map(|cmd| match cmd {
+                Cmd::Delay(_, _, Msg::ChangeShown(_)) => None,
+                Cmd::Delay(_, _, Msg::DismissToast(_)) => None,
+                Cmd::Delay(_, _, msg) => Some(msg.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn save_debounced() {
+        let mut config = AppConfig::new();
+        config.set_save_url(Some("/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        // click three times quickly, collecting the delayed saves
+        let mut due = vec![];
+        for _ in 0..3 {
+            let mut cmds = Commands::default();
+            model.update(Msg::Increment, &mut cmds);
+            due.extend(delayed(&cmds));
+        }
+        assert_eq!(due.len(), 3);
+
+        // only the last pending save actually saves
+        let saves: Vec<Cmd> = due.into_iter()
+            .flat_map(|msg| {
+                let mut cmds = Commands::default();
+                model.update(msg, &mut cmds);
+                cmds.immediate
+            })
+            .collect();
+
+        assert_eq!(saves, vec![Cmd::SaveCount {
+            shared: model.shared.clone(),
+            url: "/count".to_owned(),
+            token: None,
+            count: 3,
+            previous: 0,
+            generation: 3,
+        }]);
+    }
+
+    #[test]
+    fn fetch_count_retried() {
+        let mut config = AppConfig::new();
+        config.set_count_url(Some("/count".to_owned()));
+        config.set_max_retries(2);
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        model.update(Msg::Init, &mut Commands::default());
+
+        for attempt in 1..=2 {
+            let mut cmds = Commands::default();
+            model.update(Msg::CountLoaded(Err("offline".to_owned())), &mut cmds);
+            let retry = Cmd::Retry(model.shared.clone(), attempt, Msg::FetchDue);
+            assert_eq!(cmds.immediate, vec![retry]);
+            assert!(model.loading);
+            assert_eq!(model.retries, attempt);
+        }
+
+        // out of retries
+        let mut cmds = Commands::default();
+        model.update(Msg::CountLoaded(Err("offline".to_owned())), &mut cmds);
+        assert!(cmds.immediate.is_empty());
+        assert!(!model.loading);
+        assert_eq!(model.sync_error.as_deref(), Some("offline"));
+    }
+
+    #[test]
+    fn in_flight() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut config = AppConfig::new();
+        config.set_count_url(Some("/count".to_owned()));
+        config.set_save_url(Some("/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        // the spinner shows until the count is loaded, not while waiting to retry
+        model.update(Msg::Init, &mut Commands::default());
+        assert!(model.in_flight.busy(Task::Fetch));
+        model.update(Msg::CountLoaded(Err("offline".to_owned())), &mut Commands::default());
+        assert!(model.loading);
+        assert!(!model.in_flight.busy(Task::Fetch));
+        model.update(Msg::FetchDue, &mut Commands::default());
+        assert!(model.in_flight.busy(Task::Fetch));
+        model.update(Msg::CountLoaded(Ok(2)), &mut Commands::default());
+        assert!(!model.in_flight.busy(Task::Fetch));
+
+        // saving shows from the save being sent until the server answers
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert!(!model.in_flight.busy(Task::Save));
+        for msg in delayed(&cmds) {
+            model.update(msg, &mut Commands::default());
+        }
+        assert!(model.in_flight.busy(Task::Save));
+        let saving = DomItem::Text("Saving…");
+        assert!(model.render().dom_iter().any(|item| item == saving));
+        model.update(Msg::Saved(3), &mut Commands::default());
+        assert!(!model.in_flight.busy(Task::Save));
+        assert!(!model.render().dom_iter().any(|item| item == saving));
+
+        // the copy button is disabled until the copy is done
+        let mut cmds = Commands::default();
+        model.update(Msg::Copy, &mut cmds);
+        assert_eq!(cmds.immediate.len(), 1);
+        let disabled = DomItem::Attr { name: "disabled", value: "" };
+        assert!(model.render().dom_iter().any(|item| item == disabled));
+        let mut cmds = Commands::default();
+        model.update(Msg::Copy, &mut cmds);
+        assert!(cmds.immediate.is_empty());
+        model.update(Msg::Copied, &mut Commands::default());
+        assert!(!model.render().dom_iter().any(|item| item == disabled));
+    }
+
+    fn unavailable() -> net::Error {
+        net::Error::Status(503, "Service Unavailable".to_owned())
+    }
+
+    #[test]
+    fn save_retried() {
+        let mut config = AppConfig::new();
+        config.set_save_url(Some("/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        model.update(Msg::Increment, &mut Commands::default());
+
+        let failed = Msg::SaveFailed {
+            generation: model.timers.generation(Timer::Save),
+            previous: 0,
+            error: unavailable(),
+        };
+
+        let mut cmds = Commands::default();
+        model.update(failed, &mut cmds);
+        let due = Msg::SaveDue(model.timers.generation(Timer::Save));
+        let retry = Cmd::Retry(model.shared.clone(), 1, due);
+        assert_eq!(cmds.immediate, vec![retry]);
+        assert_eq!(model.count, 1);
+        let error = "server error: 503 Service Unavailable, retrying (1 of 3)";
+        assert_eq!(model.sync_error.as_deref(), Some(error));
+
+        // a failure for changes that were superseded is left to the newer save
+        let mut cmds = Commands::default();
+        let stale = Msg::SaveFailed { generation: 0, previous: 0, error: unavailable() };
+        model.update(stale, &mut cmds);
+        assert!(cmds.immediate.is_empty());
+        assert_eq!(model.retries, 1);
+    }
+
+    #[test]
+    fn save_failed_rolls_back() {
+        let mut config = AppConfig::new();
+        config.set_save_url(Some("/count".to_owned()));
+        config.set_max_retries(0);
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        model.preferences.places = 2;
+
+        model.update(Msg::Increment, &mut Commands::default());
+        model.update(Msg::Saved(1), &mut Commands::default());
+        model.update(Msg::Increment, &mut Commands::default());
+        assert_eq!(model.count, 2);
+
+        let mut cmds = Commands::default();
+        let failed = Msg::SaveFailed {
+            generation: model.timers.generation(Timer::Save),
+            previous: 1,
+            error: unavailable(),
+        };
+        model.update(failed, &mut cmds);
+        assert_eq!(model.count, 1);
+        let error = "Couldn't save the count, it was reset to 0.01: server error: 503 Service \
+            Unavailable";
+        assert_eq!(model.sync_error.as_deref(), Some(error));
+        assert!(delayed(&cmds).is_empty());
+
+        assert_eq!(toast_texts(&model), vec!["Couldn't save the count."]);
+
+        // the next successful save clears the error
+        model.update(Msg::Saved(1), &mut Commands::default());
+        assert!(model.sync_error.is_none());
+        assert_eq!(toast_texts(&model), vec!["Couldn't save the count.", "Saved."]);
+    }
+
+    #[test]
+    fn offline_changes_queued() {
+        let mut config = AppConfig::new();
+        config.set_save_url(Some("/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        model.update(Msg::Offline, &mut Commands::default());
+
+        let pending_key = format!("{}:pending", STASH_KEY);
+        for count in 1..=2 {
+            model.update(Msg::Increment, &mut Commands::default());
+            let mut cmds = Commands::default();
+            model.update(Msg::SaveDue(model.timers.generation(Timer::Save)), &mut cmds);
+            let queued = Some(Pending { count, changes: count as usize });
+            assert_eq!(cmds.immediate, vec![Cmd::StorePending(pending_key.clone(), queued)]);
+        }
+
+        // coming back online saves the latest change
+        let mut cmds = Commands::default();
+        model.update(Msg::Online, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::SaveCount {
+            shared: model.shared.clone(),
+            url: "/count".to_owned(),
+            token: None,
+            count: 2,
+            previous: 0,
+            generation: model.timers.generation(Timer::Save),
+        }]);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Saved(2), &mut cmds);
+        assert_eq!(model.pending, None);
+        let unwatch = Cmd::UnwatchUnload(model.shared.clone());
+        assert_eq!(cmds.immediate, vec![Cmd::StorePending(pending_key, None), unwatch]);
+    }
+
+    #[test]
+    fn unsaved_changes_guarded() {
+        let mut config = AppConfig::new();
+        config.set_save_url(Some("/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        let watch = Cmd::WatchUnload(model.shared.clone());
+        let unwatch = Cmd::UnwatchUnload(model.shared.clone());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(!cmds.immediate.contains(&watch));
+
+        // the listener is registered once, by the first change
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert!(cmds.immediate.contains(&watch));
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        model.update(Msg::SaveDue(model.timers.generation(Timer::Save)), &mut cmds);
+        assert!(!cmds.immediate.contains(&watch));
+
+        // a save of an older count leaves the newer change unsaved
+        let mut cmds = Commands::default();
+        model.update(Msg::Saved(1), &mut cmds);
+        assert!(!cmds.immediate.contains(&unwatch));
+        model.update(Msg::Saved(2), &mut cmds);
+        assert!(cmds.immediate.contains(&unwatch));
+
+        // changes queued from before the page was reloaded are guarded from the start
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        model.pending = Some(Pending { count: 3, changes: 1 });
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::WatchUnload(model.shared.clone())));
+    }
+
+    #[test]
+    fn network_error_queued() {
+        let mut config = AppConfig::new();
+        config.set_save_url(Some("/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        model.update(Msg::Increment, &mut Commands::default());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::SaveFailed {
+            generation: model.timers.generation(Timer::Save),
+            previous: 0,
+            error: net::Error::Network("Failed to fetch".to_owned()),
+        }, &mut cmds);
+
+        // the change is kept and queued rather than rolled back
+        assert_eq!(model.count, 1);
+        assert_eq!(model.pending, Some(Pending { count: 1, changes: 1 }));
+        assert!(cmds.immediate.contains(&Cmd::Retry(model.shared.clone(), 1, Msg::Flush)));
+    }
+
+    #[test]
+    fn live_sync() {
+        let mut config = AppConfig::new();
+        config.set_socket_url(Some("ws://localhost/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        let connect = Cmd::Connect {
+            shared: model.shared.clone(),
+            url: "ws://localhost/count".to_owned(),
+            format: Format::Json,
+        };
+        assert!(cmds.immediate.contains(&connect));
+
+        // local changes are sent, remote ones aren't sent back
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::Send(model.shared.clone(), 1)));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::RemoteSet(5), &mut cmds);
+        assert_eq!(model.count, 5);
+        assert!(!cmds.immediate.contains(&Cmd::Send(model.shared.clone(), 5)));
+    }
+
+    #[test]
+    fn reconnect_backs_off() {
+        let mut config = AppConfig::new();
+        config.set_socket_url(Some("ws://localhost/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        for attempt in 1..=2 {
+            let mut cmds = Commands::default();
+            model.update(Msg::Disconnected, &mut cmds);
+            let retry = Cmd::Retry(model.shared.clone(), attempt, Msg::Reconnect);
+            assert_eq!(cmds.immediate, vec![retry]);
+        }
+
+        model.update(Msg::Connected, &mut Commands::default());
+        assert_eq!(toast_texts(&model), vec!["Reconnected."]);
+        let mut cmds = Commands::default();
+        model.update(Msg::Disconnected, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::Retry(model.shared.clone(), 1, Msg::Reconnect)]);
+    }
+
+    #[test]
+    fn event_stream() {
+        let mut config = AppConfig::new();
+        config.set_events_url(Some("/count/events".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        let subscribe = Cmd::Subscribe {
+            shared: model.shared.clone(),
+            url: "/count/events".to_owned(),
+        };
+        assert!(cmds.immediate.contains(&subscribe));
+
+        model.update(Msg::RemoteSet(8), &mut Commands::default());
+        assert_eq!(model.count, 8);
+
+        // the stream is resubscribed after backing off
+        let mut cmds = Commands::default();
+        model.update(Msg::Unsubscribed, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::Retry(model.shared.clone(), 1, Msg::Resubscribe)]);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Resubscribe, &mut cmds);
+        assert_eq!(cmds.immediate, vec![subscribe]);
+    }
+
+    #[test]
+    fn sync_tabs() {
+        let mut config = AppConfig::new();
+        config.set_sync_tabs(true);
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        let open = Cmd::OpenChannel(model.shared.clone(), STASH_KEY.to_owned(), Format::Json);
+        assert!(cmds.immediate.contains(&open));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        let mut tally = PnCounter::default();
+        tally.add("local", 1);
+        assert!(cmds.immediate.contains(&Cmd::Broadcast(model.shared.clone(), tally.clone())));
+
+        // changes made in another tab at the same time are merged in
+        let mut theirs = PnCounter::default();
+        theirs.add("other", 3);
+        let mut cmds = Commands::default();
+        model.update(Msg::Merge(theirs.clone()), &mut cmds);
+        assert_eq!(model.count, 4);
+
+        // and they're told about ours
+        tally.merge(&theirs);
+        assert!(cmds.immediate.contains(&Cmd::Broadcast(model.shared.clone(), tally.clone())));
+
+        // hearing about the same changes again does nothing
+        let mut cmds = Commands::default();
+        model.update(Msg::Merge(tally.clone()), &mut cmds);
+        assert_eq!(model.count, 4);
+        assert!(cmds.immediate.is_empty());
+
+        // a merged change stops at the largest count, the same as a click would
+        model.count = Count::MAX - 1;
+        theirs.add("other", 2);
+        model.update(Msg::Merge(theirs), &mut Commands::default());
+        assert_eq!(model.count, Count::MAX);
+    }
+
+    #[test]
+    fn login() {
+        let mut config = AppConfig::new();
+        config.set_login_url(Some("/login".to_owned()));
+        config.set_count_url(Some("/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        // nothing is loaded until we're logged in
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(!cmds.immediate.iter().any(|cmd| matches!(cmd, Cmd::FetchCount { .. })));
+
+        model.update(Msg::SetUsername("me".to_owned()), &mut Commands::default());
+        model.update(Msg::SetPassword("secret".to_owned()), &mut Commands::default());
+        let mut cmds = Commands::default();
+        model.update(Msg::LogIn, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::LogIn {
+            shared: model.shared.clone(),
+            url: "/login".to_owned(),
+            username: "me".to_owned(),
+            password: "secret".to_owned(),
+        }]);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::LoggedIn("abc".to_owned()), &mut cmds);
+        assert!(model.password.is_empty());
+        assert_eq!(cmds.immediate, vec![Cmd::FetchCount {
+            shared: model.shared.clone(),
+            url: "/count".to_owned(),
+            token: Some("abc".to_owned()),
+        }]);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::LoggedOut, &mut cmds);
+        assert!(model.needs_login());
+        assert_eq!(cmds.immediate, vec![Cmd::StoreToken(format!("{}:token", STASH_KEY), None)]);
+    }
+
+    #[test]
+    fn loaded_count_not_saved() {
+        let mut config = AppConfig::new();
+        config.set_save_url(Some("/count".to_owned()));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::CountLoaded(Ok(5)), &mut cmds);
+        assert!(delayed(&cmds).is_empty());
+    }
+
+    #[test]
+    fn set_step() {
+        let mut model = Model::new();
+        let mut cmds = Commands::default();
+        model.update(Msg::SetStep(3), &mut cmds);
+        model.update(Msg::Increment, &mut Commands::default());
+        assert_eq!(model.count, 3);
+        let reflect = Cmd::Reflect(model.shared.clone(), "step", "3".to_owned());
+        let store = Cmd::StorePreferences(model.preferences_key(), model.kept_preferences());
+        assert_eq!(cmds.immediate, vec![reflect, store]);
+    }
+
+    #[test]
+    fn preferences() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut config = AppConfig::new();
+        config.set_sounds(true);
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        model.tab = Tab::Settings;
+
+        // the form shows what's picked, sounds are on to begin with
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        assert!(items.contains(&DomItem::Attr { name: "value", value: "1" }));
+        assert!(items.contains(&DomItem::Attr { name: "checked", value: "" }));
+        assert!(items.contains(&DomItem::Attr { name: "placeholder", value: "Off" }));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleMute, &mut cmds);
+        model.update(Msg::SetAutoIncrement(Some(250)), &mut cmds);
+        assert!(model.preferences.muted);
+        assert!(cmds.immediate.contains(&Cmd::Delay(model.shared.clone(), 250, Msg::Tick)));
+        let store = Cmd::StorePreferences(model.preferences_key(), model.kept_preferences());
+        assert_eq!(cmds.immediate.last(), Some(&store));
+
+        // a tick is already on its way, changing the interval again doesn't start another
+        let mut cmds = Commands::default();
+        model.update(Msg::SetAutoIncrement(Some(500)), &mut cmds);
+        assert_eq!(delayed(&cmds), vec![]);
+        model.update(Msg::SetAutoIncrement(None), &mut cmds);
+        model.update(Msg::Tick, &mut cmds);
+        assert_eq!(model.count, 0);
+        assert_eq!(delayed(&cmds), vec![]);
+
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        assert!(!items.contains(&DomItem::Attr { name: "checked", value: "" }));
+    }
+
+    #[test]
+    fn validation() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        let mut cmds = Commands::default();
+        model.update(Msg::Typed(Field::Count, "12a".to_owned()), &mut cmds);
+        assert_eq!(model.count_invalid, Some(Invalid::NotANumber));
+
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        assert!(items.contains(&DomItem::Attr { name: "aria-invalid", value: "true" }));
+        assert!(items.contains(&DomItem::Text("Enter a whole number.")));
+
+        // entering it leaves the count as it was
+        model.update(Msg::Entered(Field::Count, "12a".to_owned()), &mut cmds);
+        assert_eq!(model.count, 0);
+        assert_eq!(model.count_invalid, Some(Invalid::NotANumber));
+
+        // fixing it clears the error, entering it sets the count
+        model.update(Msg::Typed(Field::Count, "12".to_owned()), &mut cmds);
+        assert_eq!(model.count_invalid, None);
+        let too_large = "3000000000000000000000000000000000000000";
+        model.update(Msg::Entered(Field::Count, too_large.to_owned()), &mut cmds);
+        assert_eq!(model.count_invalid, Some(Invalid::TooLarge(Count::MAX)));
+        model.update(Msg::Entered(Field::Count, "12".to_owned()), &mut cmds);
+        assert_eq!(model.count, 12);
+        assert_eq!(model.history.back().map(|entry| entry.action), Some(Action::Set));
+
+        // the count changing some other way replaces what was typed, and the error with it
+        model.update(Msg::Typed(Field::Count, "-".to_owned()), &mut cmds);
+        model.update(Msg::Increment, &mut cmds);
+        assert_eq!(model.count_invalid, None);
+
+        model.tab = Tab::Settings;
+        model.update(Msg::Entered(Field::Step, "0".to_owned()), &mut cmds);
+        assert_eq!(model.preferences.step, 1);
+        let rendered = model.render();
+        assert!(rendered.dom_iter().any(|item| item == DomItem::Text("The step can't be zero.")));
+        model.update(Msg::Entered(Field::Step, "4".to_owned()), &mut cmds);
+        assert_eq!(model.preferences.step, 4);
+        assert_eq!(model.step_invalid, None);
+    }
+
+    #[cfg(feature = "wide")]
+    #[test]
+    fn wide() {
+        let mut model = Model::new();
+        model.count = Count::from(i32::MAX);
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert_eq!(model.count, 2_147_483_648);
+        assert!(cmds.immediate.contains(&Cmd::SetTitle("Count: 2,147,483,648".to_owned())));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Entered(Field::Count, "-99999999999999999999".to_owned()), &mut cmds);
+        assert_eq!(model.count, -99_999_999_999_999_999_999);
+    }
+
+    #[test]
+    fn invariants() {
+        let mut model = Model::new();
+        assert_eq!(model.validate(), Ok(()));
+        model.countdown = Some(10);
+        model.count = -1;
+        assert_eq!(model.validate(), Err("the countdown is at -1".to_owned()));
+        model.count = 1;
+        model.goal = Some(0);
+        assert_eq!(model.validate(), Err("the goal is 0".to_owned()));
+        model.goal = None;
+        model.preferences.step = -1_000_001;
+        assert_eq!(model.validate(), Err("the step is -1000001".to_owned()));
+
+        // a config can't break them
+        let mut config = AppConfig::new();
+        config.set_step(0);
+        let model = Model::configure(&config, STASH_KEY.to_owned());
+        assert_eq!(model.preferences.step, 1);
+        config.set_step(1_000_001);
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        assert_eq!(model.preferences.step, 1);
+
+        // nor can the host's attributes
+        model.update(Msg::SetStep(i32::MIN), &mut Commands::default());
+        assert_eq!(model.preferences.step, 1);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "the step is 0")]
+    fn invariants_checked() {
+        let mut model = Model::new();
+        model.preferences.step = 0;
+        model.update(Msg::Increment, &mut Commands::default());
+    }
+
+    #[test]
+    fn overflow() {
+        let mut model = Model::new();
+        model.count = Count::MAX - 1;
+        model.preferences.step = 2;
+        model.update(Msg::Increment, &mut Commands::default());
+        assert_eq!(model.count, Count::MAX);
+        model.update(Msg::Increment, &mut Commands::default());
+        assert_eq!(model.count, Count::MAX);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::SetOverflow(Overflow::Wrap), &mut cmds);
+        let store = Cmd::StorePreferences(model.preferences_key(), model.kept_preferences());
+        assert_eq!(cmds.immediate, vec![store]);
+        model.update(Msg::Increment, &mut Commands::default());
+        assert_eq!(model.count, Count::MIN + 1);
+        model.update(Msg::Nudge(-1), &mut Commands::default());
+        assert_eq!(model.count, Count::MAX);
+
+        model.update(Msg::SetOverflow(Overflow::Saturate), &mut Commands::default());
+        model.count = Count::MIN + 1;
+        model.update(Msg::Nudge(-10), &mut Commands::default());
+        assert_eq!(model.count, Count::MIN);
+    }
+
+    #[test]
+    fn overflow_warning() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.count = Count::MAX;
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert_eq!(model.warning, Some(Limit::Max));
+        assert!(delayed(&cmds).contains(&Msg::WarningExpired(1)));
+        let text = DomItem::Text("The count can't go any higher.");
+        assert!(model.render().dom_iter().any(|item| item == text));
+
+        // a second warning stays up when the first one's expires
+        model.count = Count::MIN;
+        model.update(Msg::Decrement, &mut Commands::default());
+        assert_eq!(model.warning, Some(Limit::Min));
+        model.update(Msg::WarningExpired(1), &mut Commands::default());
+        assert_eq!(model.warning, Some(Limit::Min));
+        model.update(Msg::WarningExpired(2), &mut Commands::default());
+        assert_eq!(model.warning, None);
+
+        // wrapping round isn't stopping short
+        model.preferences.overflow = Overflow::Wrap;
+        model.update(Msg::Decrement, &mut Commands::default());
+        assert_eq!((model.count, model.warning), (Count::MAX, None));
+    }
+
+    #[test]
+    fn formatted_text() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let node: Dom<Msg, Cmd> = text!("{} of {}", 1, 2);
+        assert_eq!(node.dom_iter().next(), Some(DomItem::Text("1 of 2")));
+    }
+
+    #[test]
+    fn step() {
+        let mut model = Model::new();
+        model.preferences.step = 5;
+        model.update(Msg::Increment, &mut Commands::default());
+        model.update(Msg::Increment, &mut Commands::default());
+        model.update(Msg::Decrement, &mut Commands::default());
+        assert_eq!(model.count, 5);
+    }
+
+    #[test]
+    fn decimal_places() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.count = 3;
+        model.goal = Some(5);
+        model.update(Msg::SetPlaces(2), &mut Commands::default());
+        assert_eq!((model.count, model.goal, model.preferences.step), (300, Some(500), 100));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Entered(Field::Step, "0.25".to_owned()), &mut cmds);
+        assert_eq!(model.preferences.step, 25);
+        for _ in 0..3 {
+            model.update(Msg::Increment, &mut cmds);
+        }
+        assert_eq!(model.count, 375);
+        assert!(cmds.immediate.contains(&Cmd::SetTitle("Count: 3.75".to_owned())));
+
+        model.update(Msg::Entered(Field::Count, "1.005".to_owned()), &mut cmds);
+        assert_eq!(model.count_invalid, Some(Invalid::TooPrecise));
+        model.update(Msg::Entered(Field::Goal, "10.5".to_owned()), &mut cmds);
+        assert_eq!(model.goal, Some(1050));
+
+        // fewer places round, a step too fine for them becomes the finest there is
+        model.update(Msg::SetPlaces(0), &mut Commands::default());
+        assert_eq!((model.count, model.goal, model.preferences.step), (4, Some(11), 1));
+        assert_eq!(model.history.back().map(|entry| entry.count), Some(4));
+
+        model.update(Msg::SetPlaces(1), &mut Commands::default());
+        model.tab = Tab::Settings;
+        let rendered = model.render();
+        assert!(rendered.dom_iter().any(|item| item == DomItem::Text("1.0")));
+        assert!(rendered.dom_iter().any(|item| item == DomItem::Text("Decimal places ")));
+        model.tab = Tab::Counter;
+        let rendered = model.render();
+        assert!(rendered.dom_iter().any(|item| item == DomItem::Text("4.0")));
+    }
+
+    #[test]
+    fn auto_increment() {
+        let mut config = AppConfig::new();
+        config.set_auto_increment(Some(1000));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::Delay(model.shared.clone(), 1000, Msg::Tick)));
+        assert_eq!(model.count, 0);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Tick, &mut cmds);
+        assert_eq!(model.count, 1);
+        assert!(cmds.immediate.contains(&Cmd::Delay(model.shared.clone(), 1000, Msg::Tick)));
+    }
+
+    #[test]
+    fn idle() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut config = AppConfig::new();
+        config.set_auto_increment(Some(1000));
+        config.set_idle_timeout(Some(60_000));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::WatchActivity(model.shared.clone())));
+        let idle_due = Cmd::Delay(model.shared.clone(), 60_000, Msg::IdleDue(0));
+        assert!(cmds.immediate.contains(&idle_due));
+
+        // idle, the ticks keep coming but don't count
+        model.update(Msg::IdleDue(0), &mut Commands::default());
+        assert!(model.idle);
+        let mut cmds = Commands::default();
+        model.update(Msg::Tick, &mut cmds);
+        assert_eq!(model.count, 0);
+        assert!(cmds.immediate.contains(&Cmd::Delay(model.shared.clone(), 1000, Msg::Tick)));
+        let dimmed = DomItem::Attr { name: "class", value: "counter idle" };
+        assert!(model.render().dom_iter().any(|item| item == dimmed));
+
+        // activity wakes it up and starts the timer over, the earlier timer is dropped
+        let mut cmds = Commands::default();
+        model.update(Msg::Active, &mut cmds);
+        assert!(!model.idle);
+        assert_eq!(delayed(&cmds), vec![Msg::IdleDue(1)]);
+        model.update(Msg::IdleDue(0), &mut Commands::default());
+        assert!(!model.idle);
+        model.update(Msg::Tick, &mut Commands::default());
+        assert_eq!(model.count, 1);
+    }
+
+    #[test]
+    fn memory_sampled() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut config = AppConfig::new();
+        config.set_diagnostics(true);
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::SampleMemory(model.shared.clone())));
+
+        // each sample schedules the next
+        let memory = Memory { linear: 2 * 1024 * 1024, heap: None };
+        let mut cmds = Commands::default();
+        model.update(Msg::MemorySampled(memory), &mut cmds);
+        assert_eq!(delayed(&cmds), vec![Msg::SampleMemory]);
+        let shown = |model: &Model, text: &str| {
+            model.render().dom_iter().any(|item| item == DomItem::Text(text))
+        };
+        assert!(shown(&model, "2.0 MB"));
+        assert!(!shown(&model, "JS heap"));
+
+        let memory = Memory { linear: 2 * 1024 * 1024, heap: Some((1024 * 1024, 4 * 1024 * 1024)) };
+        model.update(Msg::MemorySampled(memory), &mut Commands::default());
+        assert!(shown(&model, "1.0 MB / 4.0 MB"));
+
+        // nothing is sampled unless it's asked for
+        let mut model = Model::configure(&AppConfig::new(), STASH_KEY.to_owned());
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(!cmds.immediate.contains(&Cmd::SampleMemory(model.shared.clone())));
+    }
+
+    #[test]
+    fn frame_rate_shown() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut config = AppConfig::new();
+        config.set_diagnostics(true);
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        let shown = |model: &Model| {
+            model.render().dom_iter().any(|item| item == DomItem::Text("FPS"))
+        };
+
+        // measured while the count moves along
+        model.update(Msg::Increment, &mut Commands::default());
+        for frame in 0..5 {
+            model.update(Msg::Frame(f64::from(frame) * 20.0), &mut Commands::default());
+        }
+        assert!(model.tween.is_some());
+        assert_eq!(model.fps.rate(), Some(50.0));
+        assert!(shown(&model));
+
+        // and gone once it stops
+        model.update(Msg::Frame(tween::DURATION), &mut Commands::default());
+        assert_eq!(model.tween, None);
+        assert_eq!(model.fps.rate(), None);
+        assert!(!shown(&model));
+    }
+
+    #[test]
+    fn count_scrolled_into_view() {
+        let mut model = Model::new();
+        let scroll = |model: &Model, smooth| {
+            Cmd::ScrollIntoView(model.shared.clone(), "count".to_owned(), smooth)
+        };
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert!(cmds.post_render.contains(&scroll(&model, true)));
+
+        // not for changes the user didn't make, or with the count on another tab
+        let mut cmds = Commands::default();
+        model.update(Msg::RemoteSet(5), &mut cmds);
+        model.tab = Tab::History;
+        model.update(Msg::Decrement, &mut cmds);
+        assert!(!cmds.post_render.iter().any(|cmd| matches!(cmd, Cmd::ScrollIntoView(..))));
+
+        // no gliding for browsers that want less motion
+        model.tab = Tab::Counter;
+        model.reduced_motion = true;
+        let mut cmds = Commands::default();
+        model.update(Msg::Nudge(1), &mut cmds);
+        assert!(cmds.post_render.contains(&scroll(&model, false)));
+    }
+
+    #[test]
+    fn tabs() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        let mut cmds = Commands::default();
+        model.update(Msg::SelectTab(Tab::History), &mut cmds);
+        assert_eq!(model.tab, Tab::History);
+        assert!(cmds.immediate.is_empty());
+
+        // only the tab shown is rendered
+        let rendered = model.render();
+        let empty = DomItem::Text("Nothing has changed the count yet.");
+        assert!(rendered.dom_iter().any(|item| item == empty));
+        assert!(!rendered.dom_iter().any(|item| item == DomItem::Text("Copy")));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::CycleTab(1), &mut cmds);
+        assert_eq!(model.tab, Tab::Settings);
+        let focus = Cmd::MoveFocus(model.shared.clone(), "tab-settings".to_owned());
+        assert_eq!(cmds.post_render[0], focus);
+
+        // with hash routing the hash follows the tab
+        let mut config = AppConfig::new();
+        config.set_hash_routing(true);
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::WatchHash(model.shared.clone())));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::SelectTab(Tab::Stopwatch), &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::SetHash(Tab::Stopwatch)]);
+
+        // selecting the tab shown, like when the hash changes to match it, changes nothing
+        let mut cmds = Commands::default();
+        model.update(Msg::SelectTab(Tab::Stopwatch), &mut cmds);
+        assert!(cmds.immediate.is_empty());
+    }
+
+    #[test]
+    fn stopwatch_alongside() {
+        let mut model = Model::new();
+        model.clock = || 1000.0;
+        model.reduced_motion = true;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Stopwatch(stopwatch::Msg::Start), &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::NextFrame(model.shared.clone())));
+
+        model.clock = || 3500.0;
+        let mut cmds = Commands::default();
+        model.update(Msg::Frame(0.0), &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::NextFrame(model.shared.clone())]);
+        model.update(Msg::Stopwatch(stopwatch::Msg::Lap), &mut Commands::default());
+
+        // the counter carries on as it was
+        model.update(Msg::Increment, &mut Commands::default());
+        assert_eq!(model.count, 1);
+        assert_eq!(model.stopwatch.laps, vec![2500.0]);
+
+        use euca::vdom::{DomIter, DomItem};
+        model.tab = Tab::Stopwatch;
+        let rendered = model.render();
+        let texts: Vec<_> = rendered.dom_iter()
+            .filter_map(|item| match item {
+                DomItem::Text(text) => Some(text),
+                _ => None,
+            })
+            .skip_while(|text| *text != "0:02.50")
+            .collect();
+        assert_eq!(texts[..6], ["0:02.50", "Stop", "Lap", "Reset", "Lap 1 ", "0:02.50"]);
+
+        model.update(Msg::Stopwatch(stopwatch::Msg::Stop), &mut Commands::default());
+        let mut cmds = Commands::default();
+        model.update(Msg::Frame(16.0), &mut cmds);
+        assert!(cmds.immediate.is_empty());
+    }
+
+    #[test]
+    fn countdown() {
+        let mut config = AppConfig::new();
+        config.set_countdown(Some(2));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        assert_eq!(model.count, 2);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(delayed(&cmds).contains(&Msg::CountdownTick(1)));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::CountdownTick(1), &mut cmds);
+        assert_eq!(model.count, 1);
+        assert_eq!(delayed(&cmds), vec![Msg::CountdownTick(1)]);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::CountdownTick(1), &mut cmds);
+        assert_eq!(model.count, 0);
+        assert_eq!(delayed(&cmds), vec![Msg::Finished]);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Finished, &mut cmds);
+        assert!(model.finished);
+        let alarm = Cmd::PlayTone { shared: model.shared.clone(), freq: FINISHED_TONE, ms: 600 };
+        assert_eq!(cmds.immediate, vec![alarm]);
+
+        use euca::vdom::{DomIter, DomItem};
+        assert!(model.render().dom_iter().any(|item| item == DomItem::Text("Time's up!")));
+
+        // it stays at zero, saying so when clicked past it
+        model.update(Msg::Decrement, &mut Commands::default());
+        assert_eq!(model.warning, Some(Limit::Zero));
+        model.update(Msg::DismissWarning, &mut Commands::default());
+        model.update(Msg::SetCount(-5), &mut Commands::default());
+        assert_eq!(model.count, 0);
+        assert_eq!(model.warning, None);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::RestartCountdown, &mut cmds);
+        assert_eq!(model.count, 2);
+        assert!(!model.finished);
+        assert_eq!(delayed(&cmds), vec![Msg::CountdownTick(2)]);
+
+        // ticks from before the restart are dropped
+        model.update(Msg::CountdownTick(1), &mut Commands::default());
+        assert_eq!(model.count, 2);
+
+        // reaching zero and leaving it again before it's finished
+        model.update(Msg::SetCount(0), &mut Commands::default());
+        model.update(Msg::SetCount(1), &mut Commands::default());
+        let mut cmds = Commands::default();
+        model.update(Msg::Finished, &mut cmds);
+        assert!(!model.finished);
+        assert!(cmds.immediate.is_empty());
+    }
+
+    #[test]
+    fn update_stashes_count() {
+        let mut model = Model::new();
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::Stash(STASH_KEY.to_owned(), 1)));
+        assert!(cmds.immediate.contains(&Cmd::Publish(model.shared.clone(), 1)));
+        assert!(cmds.immediate.contains(&Cmd::SetFavicon(1, 0)));
+
+        // the favicon shows the count at its decimal places, 1.25 rather than 125
+        model.preferences.places = 2;
+        model.count = 124;
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::SetFavicon(125, 2)));
+    }
+
+    #[test]
+    fn update_sets_title() {
+        let mut model = Model::new();
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::SetTitle("Count: 0".to_owned())));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Decrement, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::SetTitle("Count: -1".to_owned())));
+
+        // no change, no new title
+        let mut cmds = Commands::default();
+        model.update(Msg::SetCount(-1), &mut cmds);
+        assert!(!cmds.immediate.iter().any(|cmd| matches!(cmd, Cmd::SetTitle(_))));
+    }
+
+    #[test]
+    fn copy() {
+        let mut model = Model::new();
+        model.count = 7;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Copy, &mut cmds);
+        let copy = Cmd::CopyToClipboard(model.shared.clone(), "7".to_owned());
+        assert_eq!(cmds.immediate, vec![copy]);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Copied, &mut cmds);
+        assert_eq!(toast_texts(&model), vec!["Copied!"]);
+        let expire = Cmd::Delay(model.shared.clone(), TOAST, Msg::DismissToast(0));
+        assert!(cmds.immediate.contains(&expire));
+
+        // a second copy's toast stays up when the first one's expires
+        model.update(Msg::CopyFailed("denied".to_owned()), &mut cmds);
+        assert_eq!(toast_texts(&model), vec!["Copied!", "Copy failed"]);
+        model.update(Msg::DismissToast(0), &mut cmds);
+        assert_eq!(toast_texts(&model), vec!["Copy failed"]);
+        model.update(Msg::DismissToast(1), &mut cmds);
+        assert!(model.toasts.is_empty());
+    }
+
+    fn toast_texts(model: &Model) -> Vec<&str> {
+        model.toasts.iter().map(|toast| toast.text.as_str()).collect()
+    }
+
+    #[test]
+    fn milestone_notifications() {
+        let mut model = Model::new();
+        model.milestones = vec![2];
+        model.notifications = Permission::Default;
+        model.count = 1;
+
+        // ask first, then notify
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        let request = Cmd::Spawn(model.shared.clone(), notify::request_permission());
+        assert!(cmds.immediate.contains(&request));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::NotificationPermission(Permission::Granted), &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::Notify("The count reached 2!".to_owned())]);
+
+        // changes from elsewhere aren't ours to notify about
+        let mut cmds = Commands::default();
+        model.update(Msg::RemoteSet(0), &mut cmds);
+        model.update(Msg::RemoteSet(2), &mut cmds);
+        assert!(!cmds.immediate.iter().any(|cmd| matches!(cmd, Cmd::Notify(_))));
+    }
+
+    #[test]
+    fn milestone_confetti() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.milestones = vec![2];
+        model.count = 1;
+        let layer = |model: &Model| {
+            let class = DomItem::Attr { name: "class", value: "confetti" };
+            let rendered = model.render();
+            let layer = rendered.dom_iter().any(|item| item == class);
+            layer
+        };
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert!(model.confetti.is_some());
+        assert!(layer(&model));
+        // the count and the confetti share frames
+        let frames = cmds.immediate.iter().filter(|cmd| matches!(cmd, Cmd::NextFrame(_)));
+        assert_eq!(frames.count(), 1);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Frame(0.0), &mut cmds);
+        model.update(Msg::Frame(tween::DURATION), &mut cmds);
+        assert_eq!(model.tween, None);
+        assert!(model.confetti.is_some());
+        model.update(Msg::Frame(confetti::DURATION), &mut cmds);
+        assert_eq!(model.confetti, None);
+        assert!(!layer(&model));
+        assert_eq!(cmds.immediate, vec![
+            Cmd::NextFrame(model.shared.clone()),
+            Cmd::NextFrame(model.shared.clone()),
+        ]);
+
+        // no confetti for browsers that want less motion
+        model.reduced_motion = true;
+        model.update(Msg::SetCount(0), &mut cmds);
+        model.update(Msg::SetCount(2), &mut cmds);
+        assert_eq!(model.confetti, None);
+    }
+
+    #[test]
+    fn tones() {
+        let mut model = Model::new();
+        model.sounds = true;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        model.update(Msg::Decrement, &mut cmds);
+        let tones: Vec<f32> = cmds.immediate.iter()
+            .filter_map(|cmd| match cmd {
+                Cmd::PlayTone { freq, .. } => Some(*freq),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tones, vec![INCREMENT_TONE, DECREMENT_TONE]);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleMute, &mut cmds);
+        model.update(Msg::Increment, &mut cmds);
+        assert!(!cmds.immediate.iter().any(|cmd| matches!(cmd, Cmd::PlayTone { .. })));
+    }
+
+    #[test]
+    fn vibration() {
+        let mut model = Model::new();
+        model.haptics = true;
+
+        // not on devices that can't
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert!(!cmds.immediate.contains(&Cmd::Vibrate(VIBRATION)));
+
+        model.can_vibrate = true;
+        let mut cmds = Commands::default();
+        model.update(Msg::Decrement, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::Vibrate(VIBRATION)));
+
+        // or when the user turned it off
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleVibration, &mut cmds);
+        model.update(Msg::Increment, &mut cmds);
+        assert!(!cmds.immediate.contains(&Cmd::Vibrate(VIBRATION)));
+    }
+
+    #[test]
+    fn speak_changes() {
+        let mut model = Model::new();
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert!(!cmds.immediate.iter().any(|cmd| matches!(cmd, Cmd::Speak(_))));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleSpeech, &mut cmds);
+        assert!(cmds.immediate.is_empty());
+        model.update(Msg::RemoteSet(5), &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::Speak("5".to_owned())));
+    }
+
+    #[test]
+    fn toggle_theme() {
+        let mut model = Model::new();
+        model.preferred_scheme = Scheme::Dark;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::ApplyScheme(model.shared.clone(), Scheme::Dark)));
+
+        // the user's choice is remembered
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleTheme, &mut cmds);
+        assert_eq!(model.scheme, Some(Scheme::Light));
+        assert!(cmds.immediate.contains(&Cmd::ApplyScheme(model.shared.clone(), Scheme::Light)));
+        assert!(cmds.immediate.contains(&Cmd::StoreScheme(model.scheme_key(), Scheme::Light)));
+    }
+
+    #[test]
+    fn change_shown() {
+        let mut model = Model::new();
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        model.update(Msg::Increment, &mut cmds);
+        assert!(model.changed);
+        let shown = Cmd::Delay(model.shared.clone(), CHANGED, Msg::ChangeShown(2));
+        assert!(cmds.immediate.contains(&shown));
+
+        // the class stays until the latest change has been shown
+        model.update(Msg::ChangeShown(1), &mut cmds);
+        assert!(model.changed);
+        model.update(Msg::ChangeShown(2), &mut cmds);
+        assert!(!model.changed);
+    }
+
+    #[test]
+    fn count_up() {
+        let mut model = Model::new();
+        model.count = 97;
+        let shared = model.shared.clone();
+        let frame = || Cmd::NextFrame(shared.clone());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert_eq!(model.count, 98);
+        assert_eq!(model.displayed(), 97);
+        assert!(cmds.immediate.contains(&frame()));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Frame(0.0), &mut cmds);
+        model.update(Msg::Frame(100.0), &mut cmds);
+        assert_eq!(model.displayed(), 98);
+        assert_eq!(cmds.immediate, vec![frame(), frame()]);
+
+        // a second change picks up from the count displayed, without asking for more frames
+        let mut cmds = Commands::default();
+        model.update(Msg::SetCount(0), &mut cmds);
+        assert_eq!(model.displayed(), 98);
+        assert!(!cmds.immediate.contains(&frame()));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Frame(200.0), &mut cmds);
+        model.update(Msg::Frame(400.0), &mut cmds);
+        assert_eq!(model.displayed(), 0);
+        assert_eq!(model.tween, None);
+        assert_eq!(cmds.immediate, vec![frame()]);
+
+        // the count jumps for browsers that want less motion
+        model.reduced_motion = true;
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        assert_eq!(model.displayed(), 1);
+        assert!(!cmds.immediate.contains(&frame()));
+    }
+
+    #[test]
+    fn pick_theme() {
+        let mut model = Model::new();
 
-struct Model(i32);
+        let mut cmds = Commands::default();
+        model.update(Msg::PickTheme("Large".to_owned()), &mut cmds);
+        assert_eq!(model.preferences.theme.name, "Large");
+        let store = Cmd::StorePreferences(model.preferences_key(), model.kept_preferences());
+        assert_eq!(cmds.immediate, vec![store]);
 
-impl Model {
-    fn new() -> Self {
-        Model(0)
+        // unknown themes are ignored
+        let mut cmds = Commands::default();
+        model.update(Msg::PickTheme("Plaid".to_owned()), &mut cmds);
+        assert_eq!(model.preferences.theme.name, "Large");
+        assert!(cmds.immediate.is_empty());
     }
-}
 
+    #[test]
+    fn high_contrast() {
+        let mut model = Model::new();
+        model.prefers_contrast = true;
+        assert!(model.high_contrast());
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum Msg {
-    Increment,
-    Decrement,
-}
+        // the user can override what the browser asks for
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleContrast, &mut cmds);
+        assert!(!model.high_contrast());
+        assert_eq!(cmds.immediate, vec![Cmd::StoreContrast(model.contrast_key(), false)]);
 
-#[derive(Debug, PartialEq)]
-pub enum Cmd { }
+        use euca::vdom::{DomIter, DomItem};
+        let rendered = model.render();
+        let class = rendered.dom_iter().find_map(|item| match item {
+            DomItem::Attr { name: "class", value } => Some(value),
+            _ => None,
+        });
+        assert_eq!(class, Some("counter"));
+    }
 
-impl SideEffect<Msg> for Cmd {
-    fn process(self, _: &Dispatcher<Msg, Self>) { }
-}
+    #[test]
+    fn right_to_left() {
+        let mut model = Model::new();
 
-impl Update<Msg, Cmd> for Model {
-    fn update(&mut self, msg: Msg, _: &mut Commands<Cmd>) {
-        match msg {
-            Msg::Increment => self.0 += 1,
-            Msg::Decrement => self.0 -= 1,
-        }
+        let mut cmds = Commands::default();
+        model.update(Msg::SetDirection(Direction::Rtl), &mut cmds);
+        let store = Cmd::StoreDirection(model.direction_key(), Direction::Rtl);
+        assert_eq!(cmds.immediate, vec![store]);
+
+        use euca::vdom::{DomIter, DomItem};
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        assert!(items.contains(&DomItem::Attr { name: "dir", value: "rtl" }));
+
+        let labels: Vec<_> = items.iter()
+            .filter_map(|item| match item {
+                DomItem::Attr { name: "aria-label", value } => Some(*value),
+                _ => None,
+            })
+            // skipping the panel's label
+            .skip(1)
+            .take(3)
+            .collect();
+        // the page's styles put them where they go, the dom is in reading order
+        assert_eq!(labels, vec!["Increment", "Count", "Decrement"]);
     }
-}
 
-fn button(text: &str, msg: Msg) -> Dom<Msg, Cmd> {
-    Dom::elem("button")
-        .event("click", msg)
-        .push(Dom::text(text))
-}
+    #[test]
+    fn responsive_layout() {
+        use euca::vdom::{DomIter, DomItem};
 
-fn counter(count: i32) -> Dom<Msg, Cmd> {
-    Dom::elem("div")
-        .push(Dom::text(count.to_string()))
-}
+        let mut model = Model::new();
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::WatchLayout(model.shared.clone())));
 
-impl Render<DomVec<Msg, Cmd>> for Model {
-    fn render(&self) -> DomVec<Msg, Cmd> {
-        vec![
-            button("+", Msg::Increment),
-            counter(self.0),
-            button("-", Msg::Decrement),
-        ].into()
+        let stacked = DomItem::Attr { name: "class", value: "panel vertical" };
+        assert!(!model.render().dom_iter().any(|item| item == stacked));
+
+        // narrow windows stack the controls, in the same order for rtl
+        model.update(Msg::Resized(Mode::Vertical), &mut Commands::default());
+        model.direction = Direction::Rtl;
+        let rendered = model.render();
+        assert!(rendered.dom_iter().any(|item| item == stacked));
+        let labels: Vec<_> = rendered.dom_iter()
+            .filter_map(|item| match item {
+                DomItem::Attr { name: "aria-label", value } => Some(value),
+                _ => None,
+            })
+            .skip(1)
+            .take(3)
+            .collect();
+        assert_eq!(labels, vec!["Increment", "Count", "Decrement"]);
+
+        model.update(Msg::Resized(Mode::Horizontal), &mut Commands::default());
+        assert!(!model.render().dom_iter().any(|item| item == stacked));
     }
-}
 
-cfg_if! {
-    if #[cfg(feature = "console_error_panic_hook")] {
-        fn set_panic_hook() {
-            console_error_panic_hook::set_once();
+    #[test]
+    fn pick_locale() {
+        let mut model = Model::new();
+        model.count = 12345;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::PickLocale("de-DE".to_owned()), &mut cmds);
+        assert_eq!(model.locale().tag, "de-DE");
+        let store = Cmd::StorePreferences(model.preferences_key(), model.kept_preferences());
+        assert!(cmds.immediate.contains(&store));
+        assert!(cmds.immediate.contains(&Cmd::SetTitle("Zählerstand: 12.345".to_owned())));
+
+        // the count is formatted and everything else translated
+        use euca::vdom::{DomIter, DomItem};
+        let rendered = model.render();
+        assert!(rendered.dom_iter().any(|item| item == DomItem::Text("12.345")));
+        assert!(rendered.dom_iter().any(|item| item == DomItem::Text("Kopieren")));
+        assert!(rendered.dom_iter().any(|item| {
+            item == DomItem::Attr { name: "aria-label", value: "Erhöhen" }
+        }));
+    }
+
+    #[test]
+    fn statistics() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.clock = || 1000.0;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        model.update(Msg::Increment, &mut cmds);
+        model.update(Msg::Decrement, &mut cmds);
+        model.update(Msg::SetCount(10), &mut cmds);
+        assert_eq!(model.stats.clicks(), 3);
+        assert_eq!(model.stats.per_minute(), 3);
+
+        // only shown when expanded
+        let panel = DomItem::Attr { name: "class", value: "stats" };
+        assert!(!model.render().dom_iter().any(|item| item == panel));
+        model.update(Msg::ToggleStats, &mut cmds);
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        assert!(items.contains(&panel));
+        assert!(items.contains(&DomItem::Attr { name: "aria-expanded", value: "true" }));
+        assert!(items.contains(&DomItem::Text("Total clicks")));
+    }
+
+    #[test]
+    fn history() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.tab = Tab::History;
+        model.clock = || 1000.0;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        model.update(Msg::SetStep(5), &mut cmds);
+        model.update(Msg::Nudge(-1), &mut cmds);
+        model.update(Msg::RemoteSet(-4), &mut cmds);
+        assert_eq!(model.history, vec![
+            Entry { time: 1000.0, action: Action::Increment, count: 1 },
+            Entry { time: 1000.0, action: Action::Nudge, count: -4 },
+        ]);
+
+        let rendered = model.render();
+        let texts: Vec<_> = rendered.dom_iter()
+            .filter_map(|item| match item {
+                DomItem::Text(text) => Some(text),
+                _ => None,
+            })
+            .skip_while(|text| *text != "00:00:01")
+            .take(6)
+            .collect();
+        assert_eq!(texts, vec!["00:00:01", " Stepped ", "-4", "00:00:01", " Incremented ", "1"]);
+        let chart = chart::svg(&model.history, 0, model.locale());
+        assert!(rendered.dom_iter().any(|item| item == DomItem::UnsafeInnerHtml(&chart)));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ExportHistory, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::DownloadFile {
+            name: "history.csv".to_owned(),
+            mime: "text/csv",
+            data: history::csv(&model.history, 0),
+        }]);
+
+        model.update(Msg::ClearHistory, &mut cmds);
+        assert!(model.history.is_empty());
+    }
+
+    #[test]
+    fn daily_reset() {
+        const DAY: f64 = 86_400_000.0;
+        let mut model = Model::new();
+        model.clock = || 1_000_000_000_000.0;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleDailyReset, &mut cmds);
+        let store = Cmd::StoreDailyReset(model.daily_reset_key(), Some(1_000_000_000_000.0));
+        assert_eq!(cmds.immediate, vec![store]);
+
+        model.update(Msg::SetCount(5), &mut Commands::default());
+        model.update(Msg::RefreshClock, &mut Commands::default());
+        assert_eq!(model.count, 5);
+
+        model.clock = || 1_000_000_000_000.0 + DAY;
+        let mut cmds = Commands::default();
+        model.update(Msg::RefreshClock, &mut cmds);
+        assert_eq!(model.count, 0);
+        let time = 1_000_000_000_000.0 + DAY;
+        let reset = Entry { time, action: Action::DailyReset, count: 0 };
+        assert_eq!(model.history.back(), Some(&reset));
+        let store = Cmd::StoreDailyReset(model.daily_reset_key(), Some(time));
+        assert!(cmds.immediate.contains(&store));
+        assert!(cmds.immediate.contains(&Cmd::Stash(STASH_KEY.to_owned(), 0)));
+
+        // and not again until the next day
+        model.update(Msg::SetCount(2), &mut Commands::default());
+        model.update(Msg::Init, &mut Commands::default());
+        assert_eq!(model.count, 2);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleDailyReset, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::StoreDailyReset(model.daily_reset_key(), None)]);
+    }
+
+    #[test]
+    fn export_import() {
+        let mut model = Model::new();
+        model.clock = || 1000.0;
+        model.update(Msg::Increment, &mut Commands::default());
+        model.goal = Some(10);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ExportState, &mut cmds);
+        let json = model.snapshot().to_json();
+        assert_eq!(cmds.immediate, vec![Cmd::DownloadFile {
+            name: "counter.json".to_owned(),
+            mime: "application/json",
+            data: json.clone(),
+        }]);
+
+        let mut imported = Model::new();
+        let mut cmds = Commands::default();
+        imported.update(Msg::StateImported(Snapshot::parse(&json)), &mut cmds);
+        assert_eq!(imported.count, 1);
+        assert_eq!(imported.goal, Some(10));
+        assert_eq!(imported.history, model.history);
+        assert!(cmds.immediate.contains(&Cmd::StoreGoal(imported.goal_key(), Some(10))));
+        let store = Cmd::StorePreferences(imported.preferences_key(), imported.kept_preferences());
+        assert!(cmds.immediate.contains(&store));
+        // the imported history already has the change
+        assert_eq!(imported.history.len(), 1);
+
+        let mut cmds = Commands::default();
+        let invalid = Snapshot::parse(r#"{ "count": 1, "step": 0 }"#);
+        imported.update(Msg::StateImported(invalid), &mut cmds);
+        assert_eq!(imported.count, 1);
+        assert_eq!(imported.preferences.step, 1);
+
+        use euca::vdom::{DomIter, DomItem};
+        let error = "Couldn't import the file: the step can't be 0";
+        assert!(imported.render().dom_iter().any(|item| item == DomItem::Text(error)));
+    }
+
+    #[test]
+    fn export_image() {
+        let mut model = Model::new();
+        model.clock = || 1000.0;
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        model.update(Msg::Increment, &mut cmds);
+        model.update(Msg::Decrement, &mut cmds);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ExportImage, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::DownloadImage {
+            name: "counter.png".to_owned(),
+            picture: Picture { count: "1".to_owned(), line: vec![0.0, 1.0, 0.0] },
+        }]);
+    }
+
+    #[test]
+    fn print_summary() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.clock = || 1000.0;
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::WatchPrint(model.shared.clone())));
+        model.update(Msg::Increment, &mut Commands::default());
+        model.update(Msg::Increment, &mut Commands::default());
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Print, &mut cmds);
+        assert!(cmds.post_render.contains(&Cmd::Print));
+
+        // the summary is all that's shown, with the whole history in a table
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        assert!(items.contains(&DomItem::Text("Count: 2")));
+        assert!(items.contains(&DomItem::Text("Total clicks")));
+        let rows = items.iter()
+            .filter(|item| matches!(item, DomItem::Element { name: "tr", .. }))
+            .count();
+        assert_eq!(rows, 3);
+        assert!(!items.iter().any(|item| matches!(item, DomItem::Element { name: "button", .. })));
+
+        model.update(Msg::Printed, &mut Commands::default());
+        let rendered = model.render();
+        let rows = rendered.dom_iter()
+            .filter(|item| matches!(item, DomItem::Element { name: "tr", .. }))
+            .count();
+        assert_eq!(rows, 0);
+    }
+
+    #[test]
+    fn paged_history() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut config = AppConfig::new();
+        config.set_history_page_size(Some(10));
+        let mut model = Model::configure(&config, STASH_KEY.to_owned());
+        model.tab = Tab::History;
+        model.clock = || 0.0;
+        let mut cmds = Commands::default();
+        for _ in 0..25 {
+            model.update(Msg::Increment, &mut cmds);
         }
+
+        model.update(Msg::TurnHistoryPage(Turn::Last), &mut cmds);
+        assert_eq!(model.history_page, 2);
+        model.update(Msg::TurnHistoryPage(Turn::Previous), &mut cmds);
+        assert_eq!(model.history_page, 1);
+
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        let rows = items.iter()
+            .filter(|item| matches!(item, DomItem::Element { name: "li", .. }))
+            .count();
+        assert_eq!(rows, 10);
+        assert!(items.contains(&DomItem::Text("Page 2 of 3")));
+        // keyed by where the entry is in the history, the 15th is at 14
+        assert!(items.contains(&DomItem::Element { name: "li", key: Some(14) }));
+        assert!(items.contains(&DomItem::Text("15")));
+        assert!(!items.contains(&DomItem::Text("16")));
+        assert!(!items.contains(&DomItem::Attr { name: "disabled", value: "" }));
+
+        // the page is kept in range as the history shrinks, outside an update what was filtered
+        // from it before has to be forgotten by hand
+        model.history.truncate(5);
+        model.filtered.clear();
+        assert!(model.render().dom_iter().any(|item| item == DomItem::Text("Page 1 of 1")));
+        model.update(Msg::TurnHistoryPage(Turn::Next), &mut cmds);
+        assert_eq!(model.history_page, 0);
     }
-    else {
-        fn set_panic_hook() {}
+
+    /// Type `query` into the history's search box and wait for the history to be filtered by it.
+    fn search(model: &mut Model, query: &str) {
+        let mut cmds = Commands::default();
+        model.update(Msg::SearchHistory(query.to_owned()), &mut cmds);
+        let due = Msg::SearchDue(model.timers.generation(Timer::Search));
+        model.update(due, &mut cmds);
     }
-}
 
-cfg_if! {
-    if #[cfg(feature = "console_log")] {
-        fn init_log() {
-            console_log::init_with_level(log::Level::Trace)
-                .expect("error initializing log");
+    #[test]
+    fn filtered_history() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.tab = Tab::History;
+        model.clock = || 1000.0;
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        model.update(Msg::Decrement, &mut cmds);
+        model.update(Msg::SetCount(7), &mut cmds);
+
+        // the history is filtered once typing stops
+        let mut typed = Commands::default();
+        model.update(Msg::SearchHistory("de".to_owned()), &mut typed);
+        model.update(Msg::SearchHistory("decr".to_owned()), &mut typed);
+        assert_eq!(model.filtered_history().len(), 3);
+        for msg in delayed(&typed) {
+            model.update(msg, &mut cmds);
+        }
+        assert_eq!(model.filtered_history(), vec![&model.history[1]]);
+        search(&mut model, "7");
+        assert_eq!(model.filtered_history(), vec![&model.history[2]]);
+
+        model.update(Msg::ToggleIncrementsOnly, &mut cmds);
+        assert!(model.filtered_history().is_empty());
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        assert!(items.contains(&DomItem::Text("No matching changes")));
+        assert!(items.contains(&DomItem::Attr { name: "aria-pressed", value: "true" }));
+
+        search(&mut model, "");
+        assert_eq!(model.filtered_history(), vec![&model.history[0]]);
+
+        // yesterday's changes aren't today's
+        model.update(Msg::ToggleToday, &mut cmds);
+        model.clock = || 2.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+        assert!(model.filtered_history().is_empty());
+    }
+
+    #[test]
+    fn virtual_history() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.tab = Tab::History;
+        model.clock = || 0.0;
+        let mut cmds = Commands::default();
+        for _ in 0..5000 {
+            model.update(Msg::Increment, &mut cmds);
         }
+
+        let viewport = Viewport { scroll_top: 2000.0, height: 100.0, row_height: 20.0 };
+        model.update(Msg::HistoryScrolled(viewport), &mut cmds);
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        let rows = items.iter()
+            .filter(|item| matches!(item, DomItem::Element { name: "li", .. }))
+            .count();
+        assert_eq!(rows, 16);
+
+        // newest first, starting a few rows above the 100th
+        assert!(items.contains(&DomItem::Attr { name: "aria-posinset", value: "96" }));
+        assert!(!items.contains(&DomItem::Attr { name: "aria-posinset", value: "95" }));
+        assert!(items.contains(&DomItem::Text("4,905")));
+        let style = format!("padding-top: 1900px; padding-bottom: {}px", 4889 * 20);
+        assert!(items.contains(&DomItem::Attr { name: "style", value: &style }));
     }
-    else {
-        fn init_log() {}
+
+    #[test]
+    fn updated_ago() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.clock = || 1000.0;
+        let mut cmds = Commands::default();
+        model.update(Msg::Increment, &mut cmds);
+        let text = |model: &Model, text| {
+            model.render().dom_iter().any(|item| item == DomItem::Text(text))
+        };
+        assert!(text(&model, "Updated just now"));
+
+        model.clock = || 61_000.0;
+        let mut cmds = Commands::default();
+        model.update(Msg::RefreshClock, &mut cmds);
+        assert_eq!(delayed(&cmds), vec![Msg::RefreshClock]);
+        assert!(text(&model, "Updated a minute ago"));
     }
-}
 
-#[wasm_bindgen]
-pub fn run() -> Result<(), JsValue> {
-    set_panic_hook();
-    init_log();
+    #[test]
+    fn last_change_tooltip() {
+        use euca::vdom::{DomIter, DomItem};
 
-    let parent = web_sys::window()
-        .expect("couldn't get window handle")
-        .document()
-        .expect("couldn't get document handle")
-        .query_selector("main")
-        .expect("error querying for element")
-        .expect("expected <main></main>");
+        let mut model = Model::new();
+        model.clock = || 1000.0;
+        let tooltip = |model: &Model| {
+            let class = DomItem::Attr { name: "class", value: "tooltip" };
+            let rendered = model.render();
+            let texts: Vec<_> = rendered.dom_iter()
+                .skip_while(|item| *item != class)
+                .filter_map(|item| match item {
+                    DomItem::Text(text) => Some(text.to_owned()),
+                    _ => None,
+                })
+                .take(3)
+                .collect();
+            texts
+        };
 
-    AppBuilder::default()
-        .attach(parent, Model::new());
+        // nothing to say until the count changes
+        model.update(Msg::HoverCount(true), &mut Commands::default());
+        assert!(tooltip(&model).is_empty());
 
-    Ok(())
-}
+        model.update(Msg::Nudge(-10), &mut Commands::default());
+        model.clock = || 6000.0;
+        model.update(Msg::HoverCount(true), &mut Commands::default());
+        assert_eq!(tooltip(&model), ["-10", "00:00:01", "Updated 5 seconds ago"]);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        model.update(Msg::Increment, &mut Commands::default());
+        assert_eq!(tooltip(&model)[0], "+1");
+
+        // going round past the largest count is still a step up
+        model.update(Msg::SetOverflow(Overflow::Wrap), &mut Commands::default());
+        model.count = Count::MAX;
+        model.update(Msg::Increment, &mut Commands::default());
+        assert_eq!(model.count, Count::MIN);
+        assert_eq!(tooltip(&model)[0], "+1");
+
+        // counting in tenths is the same change
+        model.count = 0;
+        model.update(Msg::SetPlaces(1), &mut Commands::default());
+        assert_eq!(model.last_delta, 10);
+
+        model.update(Msg::HoverCount(false), &mut Commands::default());
+        assert!(tooltip(&model).is_empty());
+    }
 
-    // we can test the model in isolation by initializing it, then sending it the messages we want,
-    // and checking that it's state is as expected. This can be done by checking individual
-    // elements in the model or if the model implements PartialEq, we can check the whole model at
-    // once.
     #[test]
-    fn increment() {
+    fn goal() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        model.count = 15;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::SetGoal(Some(20)), &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::StoreGoal(model.goal_key(), Some(20))]);
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        assert!(items.contains(&DomItem::Attr { name: "max", value: "20" }));
+        assert!(items.contains(&DomItem::Attr { name: "value", value: "15" }));
+        assert!(items.contains(&DomItem::Attr { name: "class", value: "goal" }));
+
+        // past the goal the bar stays full
+        model.count = 25;
+        let rendered = model.render();
+        let items: Vec<_> = rendered.dom_iter().collect();
+        assert!(items.contains(&DomItem::Attr { name: "value", value: "20" }));
+        assert!(items.contains(&DomItem::Attr { name: "class", value: "goal exceeded" }));
+        assert!(items.contains(&DomItem::Text("Goal exceeded by 5")));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::SetGoal(None), &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::StoreGoal(model.goal_key(), None)]);
+        let rendered = model.render();
+        assert!(!rendered.dom_iter().any(|item| item == DomItem::Element {
+            name: "progress",
+            key: None,
+        }));
+    }
+
+    #[cfg(feature = "voice")]
+    #[test]
+    fn voice_commands() {
+        let mut model = Model::new();
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleVoice, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::Listen(model.shared.clone())]);
+
+        model.update(Msg::VoiceStarted, &mut cmds);
+        assert!(model.voice.listening);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ToggleVoice, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::StopListening(model.shared.clone())]);
+
+        model.update(Msg::VoiceFailed("nope".to_owned()), &mut cmds);
+        model.update(Msg::VoiceStopped, &mut cmds);
+        assert!(!model.voice.listening);
+        assert_eq!(model.voice.error.as_deref(), Some("nope"));
+    }
+
+    #[cfg(feature = "pwa")]
+    #[test]
+    fn service_worker_update() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::RegisterServiceWorker(model.shared.clone())));
+
+        let banner = DomItem::Text("Update available");
+        model.update(Msg::ServiceWorkerChanged(pwa::Status::Updating), &mut cmds);
+        assert!(!model.render().dom_iter().any(|item| item == banner));
+
+        // the banner only shows once the new version is ready to take over
+        model.update(Msg::ServiceWorkerChanged(pwa::Status::UpdateReady), &mut cmds);
+        assert!(model.render().dom_iter().any(|item| item == banner));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ReloadForUpdate, &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::ActivateUpdate(model.shared.clone())]);
+    }
+
+    #[cfg(feature = "pwa")]
+    #[test]
+    fn install_prompt() {
+        use euca::vdom::{DomIter, DomItem};
+
+        let mut model = Model::new();
+        let mut cmds = Commands::default();
+        model.update(Msg::Init, &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::WatchInstall(model.shared.clone())));
+
+        let install = DomItem::Text("Install app");
+        assert!(!model.render().dom_iter().any(|item| item == install));
+        model.update(Msg::InstallChanged(pwa::Install::Available), &mut cmds);
+        assert!(model.render().dom_iter().any(|item| item == install));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Install, &mut cmds);
+        assert_eq!(model.install, pwa::Install::Prompting);
+        assert_eq!(cmds.immediate, vec![Cmd::PromptInstall(model.shared.clone())]);
+        assert!(!model.render().dom_iter().any(|item| item == install));
+
+        // the prompt can't be shown again, whatever the user chose
+        model.update(Msg::InstallChanged(pwa::Install::Dismissed), &mut cmds);
+        assert_eq!(model.install, pwa::Install::Dismissed);
+        assert!(!model.render().dom_iter().any(|item| item == install));
+    }
+
+    #[test]
+    fn swipe() {
+        let mut model = Model::new();
+        model.preferences.step = 2;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::TouchStart(0, 0), &mut cmds);
+        model.update(Msg::TouchEnd(80, 0), &mut cmds);
+        assert_eq!(model.count, 2);
+
+        // a tap isn't a swipe
+        model.update(Msg::TouchStart(0, 0), &mut cmds);
+        model.update(Msg::TouchEnd(2, 2), &mut cmds);
+        assert_eq!(model.count, 2);
+        assert_eq!(model.touch_start, None);
+    }
+
+    #[test]
+    fn scrub() {
+        let mut model = Model::new();
+        model.count = 10;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::ScrubStart(1, 100), &mut cmds);
+        model.update(Msg::ScrubMove(1, 120), &mut cmds);
+        assert_eq!(model.count, 15);
+        model.update(Msg::ScrubMove(1, 80), &mut cmds);
+        assert_eq!(model.count, 5);
+
+        // other pointers and pointers that stopped dragging are ignored
+        model.update(Msg::ScrubMove(2, 200), &mut cmds);
+        model.update(Msg::ScrubEnd(1), &mut cmds);
+        model.update(Msg::ScrubMove(1, 200), &mut cmds);
+        assert_eq!(model.count, 5);
+
+        // a finger dragging across scrubs rather than swiping, and a second finger doesn't take
+        // over
+        model.update(Msg::ScrubStart(3, 100), &mut cmds);
+        model.update(Msg::TouchStart(100, 0), &mut cmds);
+        model.update(Msg::ScrubStart(4, 300), &mut cmds);
+        model.update(Msg::ScrubMove(3, 140), &mut cmds);
+        model.update(Msg::ScrubEnd(3), &mut cmds);
+        model.update(Msg::TouchEnd(140, 0), &mut cmds);
+        assert_eq!(model.count, 15);
+    }
+
+    #[test]
+    fn hold() {
+        let mut model = Model::new();
+
+        let mut cmds = Commands::default();
+        model.update(Msg::HoldStart(1, -1), &mut cmds);
+        let repeat = Cmd::Delay(model.shared.clone(), HOLD_DELAY, Msg::HoldRepeat(1));
+        assert!(cmds.immediate.contains(&repeat));
+        // it steps as soon as it's pressed
+        assert_eq!(model.count, -1);
+
+        // held down, it keeps stepping
+        for generation in 1..=3 {
+            let mut cmds = Commands::default();
+            model.update(Msg::HoldRepeat(generation), &mut cmds);
+            assert_eq!(delayed(&cmds), vec![Msg::HoldRepeat(generation + 1)]);
+        }
+        assert_eq!(model.count, -4);
+        assert_eq!(model.history.len(), 4);
+
+        // until it's let go, by the pointer holding it
+        model.update(Msg::HoldEnd(2), &mut Commands::default());
+        model.update(Msg::HoldRepeat(4), &mut Commands::default());
+        assert_eq!(model.count, -5);
+        model.update(Msg::HoldEnd(1), &mut Commands::default());
+        model.update(Msg::HoldRepeat(5), &mut Commands::default());
+        assert_eq!(model.count, -5);
+
+        // the click after letting go doesn't step again
+        model.update(Msg::StepClicked(false), &mut Commands::default());
+        assert_eq!(model.count, -5);
+
+        // a quick press and release steps once
+        model.update(Msg::HoldStart(1, 1), &mut Commands::default());
+        model.update(Msg::HoldEnd(1), &mut Commands::default());
+        model.update(Msg::StepClicked(true), &mut Commands::default());
+        assert_eq!(model.count, -4);
+
+        // a click from the keyboard, with no pointer pressing, steps
+        model.update(Msg::StepClicked(true), &mut Commands::default());
+        assert_eq!(model.count, -3);
+    }
+
+    #[test]
+    fn pinch() {
+        let mut model = Model::new();
+        model.preferences.step = 2;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::TouchStart(0, 0), &mut cmds);
+        model.update(Msg::PinchStart(100.0), &mut cmds);
+        model.update(Msg::Pinch(150.0), &mut cmds);
+        assert_eq!(model.preferences.step, 3);
+        model.update(Msg::Pinch(250.0), &mut cmds);
+        assert_eq!(model.preferences.step, 5);
+
+        // lifting the fingers changes neither the step nor the count
+        model.update(Msg::TouchEnd(300, 0), &mut cmds);
+        model.update(Msg::Pinch(500.0), &mut cmds);
+        model.update(Msg::TouchEnd(300, 0), &mut cmds);
+        assert_eq!(model.preferences.step, 5);
+        assert_eq!(model.count, 0);
+    }
+
+    #[test]
+    fn confirmation() {
+        use euca::vdom::{DomIter, DomItem};
+
         let mut model = Model::new();
         model.update(Msg::Increment, &mut Commands::default());
-        assert_eq!(model.0, 1);
+
+        let mut cmds = Commands::default();
+        model.update(Msg::Ask(Question::ClearHistory), &mut cmds);
+        let shared = model.shared.clone();
+        let focus = |key: &str| Cmd::MoveFocus(shared.clone(), key.to_owned());
+        assert!(cmds.post_render.contains(&focus(modal::CANCEL)));
+        let question = DomItem::Text("Forget every change to the count?");
+        assert!(model.render().dom_iter().any(|item| item == question));
+
+        // tabbing past the last button goes round to the first
+        let mut cmds = Commands::default();
+        model.update(Msg::Modal(modal::Msg::Focus(modal::CANCEL)), &mut cmds);
+        assert!(cmds.post_render.contains(&focus(modal::CANCEL)));
+
+        // cancelling leaves the history alone and focus where the dialog was opened from
+        let mut cmds = Commands::default();
+        model.update(Msg::Modal(modal::Msg::Cancel), &mut cmds);
+        assert_eq!(model.confirming, None);
+        assert_eq!(model.history.len(), 1);
+        assert!(cmds.post_render.contains(&focus("clear-history")));
+        assert!(!model.render().dom_iter().any(|item| item == question));
+
+        model.update(Msg::Ask(Question::ClearHistory), &mut Commands::default());
+        model.update(Msg::ClearHistory, &mut Commands::default());
+        assert_eq!(model.confirming, None);
+        assert!(model.history.is_empty());
     }
 
     #[test]
-    fn decrement() {
+    fn context_menu() {
         let mut model = Model::new();
-        model.update(Msg::Decrement, &mut Commands::default());
-        assert_eq!(model.0, -1);
+        model.count = 5;
+
+        let mut cmds = Commands::default();
+        model.update(Msg::OpenMenu(10, 20), &mut cmds);
+        assert_eq!(cmds.immediate, vec![Cmd::WatchMenu(model.shared.clone())]);
+        assert_eq!(model.menu, Some((10, 20)));
+
+        let mut cmds = Commands::default();
+        model.update(Msg::MenuItem(menu::Item::Reset), &mut cmds);
+        assert!(cmds.immediate.contains(&Cmd::UnwatchMenu(model.shared.clone())));
+        assert_eq!(model.menu, None);
+        // resetting is confirmed first
+        assert_eq!(model.count, 5);
+        assert_eq!(model.confirming, Some(Question::Reset));
+        model.update(Msg::Reset, &mut cmds);
+        assert_eq!(model.count, 0);
+        assert_eq!(model.confirming, None);
+
+        // starting auto increment, then pausing it
+        model.update(Msg::OpenMenu(10, 20), &mut cmds);
+        model.update(Msg::MenuItem(menu::Item::ToggleAutoIncrement), &mut cmds);
+        assert_eq!(model.preferences.auto_increment, Some(AUTO_INCREMENT));
+        model.update(Msg::Tick, &mut cmds);
+        assert_eq!(model.count, 1);
+        model.update(Msg::MenuItem(menu::Item::ToggleAutoIncrement), &mut cmds);
+        model.update(Msg::Tick, &mut cmds);
+        assert_eq!(model.count, 1);
+
+        // closing a closed menu does nothing
+        let mut cmds = Commands::default();
+        model.update(Msg::CloseMenu, &mut cmds);
+        assert!(cmds.immediate.is_empty());
+
+        // copying from the menu is the same as with the button, one copy at a time
+        let mut cmds = Commands::default();
+        model.update(Msg::MenuItem(menu::Item::Copy), &mut cmds);
+        let copy = Cmd::CopyToClipboard(model.shared.clone(), "1".to_owned());
+        assert!(cmds.immediate.contains(&copy));
+        assert!(model.in_flight.busy(Task::Copy));
+        let mut cmds = Commands::default();
+        model.update(Msg::Copy, &mut cmds);
+        assert!(!cmds.immediate.contains(&copy));
+        model.update(Msg::Copied, &mut Commands::default());
+        assert!(!model.in_flight.busy(Task::Copy));
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(key_msg("ArrowUp"), Some(Msg::Increment));
+        assert_eq!(key_msg("ArrowLeft"), Some(Msg::Decrement));
+        assert_eq!(key_msg("PageDown"), Some(Msg::Nudge(-10)));
+        assert_eq!(key_msg("a"), None);
     }
 
     // we can also test the view/renering code by sending it a model and checking the dom that
@@ -138,11 +5870,25 @@ mod tests {
         let model = Model::new();
         let dom = model.render();
 
-        let reference: DomVec<Msg, Cmd> = vec![
-            button("+", Msg::Increment),
-            counter(0),
-            button("-", Msg::Decrement),
-        ].into();
+        let reference: DomVec<Msg, Cmd> = vec![root(&theme::THEMES[0], false, false, vec![
+            tab_list(Tab::Counter, &locale::LOCALES[0]),
+            Dom::elem("div")
+                .attr("class", "panel")
+                .attr("role", "tabpanel")
+                .attr("aria-label", "Counter")
+                .extend(vec![
+                    step_button(true, &locale::LOCALES[0]),
+                    counter(0, 0, false, 0, &locale::LOCALES[0]),
+                    step_button(false, &locale::LOCALES[0]),
+                    button("Copy", Msg::Copy),
+                    announcement(0, 0, &locale::LOCALES[0]),
+                    Dom::empty(),
+                    count_input(0, 0, None, &locale::LOCALES[0]),
+                    goal_input(None, 0, &locale::LOCALES[0]),
+                    button("Statistics", Msg::ToggleStats).attr("aria-expanded", "false"),
+                ]),
+            toasts(&Toasts::default(), &locale::LOCALES[0]),
+        ]).attr("dir", "ltr")].into();
 
         // here we could do this
         //
@@ -159,6 +5905,22 @@ mod tests {
         assert_eq!(dom, reference);
     }
 
+    // the audit is only built in debug builds
+    #[cfg(debug_assertions)]
+    #[test]
+    fn accessible_render() {
+        let mut model = Model::new();
+        model.sounds = true;
+        model.menu = Some((0, 0));
+        model.milestones = vec![10];
+        model.toasts.push(Level::Success, "Copied!".to_owned());
+        assert_eq!(a11y::audit(&model.render()), Vec::<String>::new());
+
+        model.login_url = Some("/login".to_owned());
+        assert!(model.needs_login());
+        assert_eq!(a11y::audit(&model.render()), Vec::<String>::new());
+    }
+
     // we can also use this technique to test individual dom generation components instead of
     // testing the entire render function if necessary
 }
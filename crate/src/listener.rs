@@ -0,0 +1,38 @@
+//! Event listeners that are removed when dropped.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// An event listener registered on a target, removed from the target when dropped.
+pub struct EventListener {
+    target: web_sys::EventTarget,
+    kind: &'static str,
+    callback: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl EventListener {
+    /// Call `callback` with every `kind` event fired on `target`.
+    pub fn new<F>(target: &web_sys::EventTarget, kind: &'static str, callback: F)
+    -> Result<Self, JsValue>
+    where
+        F: FnMut(web_sys::Event) + 'static,
+    {
+        let callback = Closure::wrap(Box::new(callback) as Box<dyn FnMut(web_sys::Event)>);
+        target.add_event_listener_with_callback(kind, callback.as_ref().unchecked_ref())?;
+
+        Ok(EventListener {
+            target: target.clone(),
+            kind,
+            callback,
+        })
+    }
+}
+
+impl Drop for EventListener {
+    fn drop(&mut self) {
+        let callback = self.callback.as_ref().unchecked_ref();
+        if let Err(e) = self.target.remove_event_listener_with_callback(self.kind, callback) {
+            log::error!("error removing {} listener: {:?}", self.kind, e);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Formatting the count and translating text for the user's locale.
+//!
+//! In the browser `Intl` does the formatting, so the count gets the locale's digits and grouping.
+//! Elsewhere, in tests, thousands are grouped with the locale's separator and times are in UTC.
+
+use crate::Count;
+use crate::decimal;
+use crate::l10n::{self, Table};
+
+/// A locale the count can be formatted for.
+#[derive(Debug, PartialEq)]
+pub struct Locale {
+    /// The BCP 47 language tag.
+    pub tag: &'static str,
+    /// What the locale picker calls it, in its own language.
+    pub name: &'static str,
+    /// What thousands are grouped with when formatting without `Intl`.
+    separator: char,
+    /// What comes before the fraction, the decimal point.
+    point: char,
+    /// The text shown in this locale.
+    messages: Table,
+}
+
+/// The locales to pick from, the first is used when the browser prefers none of them.
+pub const LOCALES: &[Locale] = &[
+    Locale { tag: "en-US", name: "English", separator: ',', point: '.', messages: l10n::EN },
+    Locale { tag: "de-DE", name: "Deutsch", separator: '.', point: ',', messages: l10n::DE },
+    Locale { tag: "ja-JP", name: "日本語", separator: ',', point: '.', messages: l10n::JA },
+];
+
+impl Locale {
+    /// The locale with the given tag.
+    pub fn find(tag: &str) -> Option<&'static Locale> {
+        LOCALES.iter().find(|locale| locale.tag == tag)
+    }
+
+    /// The text for `key` in this locale.
+    pub fn t(&self, key: &'static str) -> &'static str {
+        l10n::lookup(self.messages, key)
+    }
+
+    /// The text for `key` in this locale, with `arg` in place of its `{}`.
+    pub fn t1(&self, key: &'static str, arg: &str) -> String {
+        self.t(key).replacen("{}", arg, 1)
+    }
+
+    /// The text for `key` in this locale, with `first` and `second` in place of its `{}`s.
+    pub fn t2(&self, key: &'static str, first: &str, second: &str) -> String {
+        self.t1(key, first).replacen("{}", second, 1)
+    }
+
+    /// The text for `key` in this locale, with `first`, `second` and `third` in place of its `{}`s.
+    pub fn t3(&self, key: &'static str, first: &str, second: &str, third: &str) -> String {
+        self.t2(key, first, second).replacen("{}", third, 1)
+    }
+
+    /// Format a number with `Intl.NumberFormat`.
+    #[cfg(target_arch = "wasm32")]
+    pub fn format(&self, n: Count) -> String {
+        let locales = js_sys::Array::of1(&self.tag.into());
+        let format = js_sys::Intl::NumberFormat::new(&locales, &js_sys::Object::new()).format();
+        format.call1(&wasm_bindgen::JsValue::UNDEFINED, &n.into())
+            .ok()
+            .and_then(|formatted| formatted.as_string())
+            .unwrap_or_else(|| self.fallback(n))
+    }
+
+    /// Format a number without `Intl.NumberFormat`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn format(&self, n: Count) -> String {
+        self.fallback(n)
+    }
+
+    /// Format a count of units at `places` decimal places, like 1,234.50.
+    pub fn decimal(&self, units: Count, places: u32) -> String {
+        decimal::join(units, places, |whole| self.format(whole), self.point)
+    }
+
+    /// Format the time of day, given in milliseconds since the epoch, with `Intl.DateTimeFormat`.
+    #[cfg(target_arch = "wasm32")]
+    pub fn time(&self, ms: f64) -> String {
+        js_sys::Date::new(&ms.into()).to_locale_time_string(self.tag).into()
+    }
+
+    /// Format the time of day, given in milliseconds since the epoch, in UTC.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn time(&self, ms: f64) -> String {
+        let seconds = (ms / 1000.0).floor().rem_euclid(24.0 * 60.0 * 60.0) as u32;
+        format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    }
+
+    /// When the count was updated, given the milliseconds since.
+    pub fn updated_ago(&self, elapsed: f64) -> String {
+        let seconds = (elapsed / 1000.0).max(0.0) as u64;
+        let (one, many, n) = match seconds {
+            0..=4 => return self.t("updated_just_now").to_owned(),
+            5..=59 => return self.t1("updated_seconds", &seconds.to_string()),
+            60..=3599 => ("updated_minute", "updated_minutes", seconds / 60),
+            3600..=86399 => ("updated_hour", "updated_hours", seconds / 3600),
+            _ => ("updated_day", "updated_days", seconds / 86400),
+        };
+
+        match n {
+            1 => self.t(one).to_owned(),
+            n => self.t1(many, &n.to_string()),
+        }
+    }
+
+    /// Format a number grouping thousands with the locale's separator.
+    fn fallback(&self, n: Count) -> String {
+        let digits = n.unsigned_abs().to_string();
+        let mut formatted = String::new();
+        if n < 0 {
+            formatted.push('-');
+        }
+
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                formatted.push(self.separator);
+            }
+            formatted.push(digit);
+        }
+
+        formatted
+    }
+}
+
+/// The first of the browser's preferred languages we have a locale for.
+pub fn preferred() -> &'static Locale {
+    let languages: Vec<String> = web_sys::window()
+        .map(|window| window.navigator().languages())
+        .map(|languages| languages.iter().filter_map(|language| language.as_string()).collect())
+        .unwrap_or_default();
+
+    matching(&languages)
+}
+
+/// The first locale with the same language as one of `languages`, in order.
+fn matching(languages: &[String]) -> &'static Locale {
+    let language = |tag: &str| tag.split('-').next().unwrap_or("").to_lowercase();
+
+    languages.iter()
+        .find_map(|wanted| LOCALES.iter().find(|locale| language(locale.tag) == language(wanted)))
+        .unwrap_or(&LOCALES[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_format() {
+        let en = Locale::find("en-US").unwrap();
+        let de = Locale::find("de-DE").unwrap();
+        assert_eq!(en.format(0), "0");
+        assert_eq!(en.format(999), "999");
+        assert_eq!(en.format(1000), "1,000");
+        assert_eq!(en.format(-1234567), "-1,234,567");
+        assert_eq!(de.format(1234567), "1.234.567");
+        assert_eq!(en.format(Count::from(i32::MIN)), "-2,147,483,648");
+    }
+
+    #[test]
+    fn decimal_format() {
+        let en = Locale::find("en-US").unwrap();
+        let de = Locale::find("de-DE").unwrap();
+        assert_eq!(en.decimal(123_456_750, 2), "1,234,567.50");
+        assert_eq!(de.decimal(123_456_750, 2), "1.234.567,50");
+        assert_eq!(en.decimal(-5, 3), "-0.005");
+        assert_eq!(en.decimal(-1234, 0), "-1,234");
+    }
+
+    #[cfg(feature = "wide")]
+    #[test]
+    fn wide_format() {
+        let en = Locale::find("en-US").unwrap();
+        assert_eq!(en.format(Count::from(i32::MAX) + 1), "2,147,483,648");
+        assert_eq!(en.format(i64::MIN.into()), "-9,223,372,036,854,775,808");
+        assert_eq!(en.format(Count::MIN), "-170,141,183,460,469,231,731,687,303,715,884,105,728");
+    }
+
+    #[test]
+    fn fallback_time() {
+        let en = Locale::find("en-US").unwrap();
+        assert_eq!(en.time(0.0), "00:00:00");
+        assert_eq!(en.time(((13.0 * 60.0 + 4.0) * 60.0 + 5.5) * 1000.0), "13:04:05");
+        assert_eq!(en.time(-1000.0), "23:59:59");
+    }
+
+    #[test]
+    fn updated_ago() {
+        let en = Locale::find("en-US").unwrap();
+        assert_eq!(en.updated_ago(-10.0), "Updated just now");
+        assert_eq!(en.updated_ago(4999.0), "Updated just now");
+        assert_eq!(en.updated_ago(5000.0), "Updated 5 seconds ago");
+        assert_eq!(en.updated_ago(90_000.0), "Updated a minute ago");
+        assert_eq!(en.updated_ago(150_000.0), "Updated 2 minutes ago");
+        assert_eq!(en.updated_ago(3_600_000.0), "Updated an hour ago");
+        assert_eq!(en.updated_ago(86_400_000.0 * 3.0), "Updated 3 days ago");
+        assert_eq!(Locale::find("de-DE").unwrap().updated_ago(7_200_000.0),
+            "Vor 2 Stunden aktualisiert");
+    }
+
+    #[test]
+    fn preferred_locale() {
+        let languages = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        assert_eq!(matching(&languages(&["fr-FR", "de"])).tag, "de-DE");
+        assert_eq!(matching(&languages(&["ja"])).tag, "ja-JP");
+        assert_eq!(matching(&languages(&["en-GB"])).tag, "en-US");
+        assert_eq!(matching(&languages(&["fr"])).tag, "en-US");
+        assert_eq!(matching(&[]).tag, "en-US");
+    }
+}
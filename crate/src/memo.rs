@@ -0,0 +1,54 @@
+//! Values worked out from the model once per change, rather than on every render.
+//!
+//! A memo keeps its value from the first time it's asked for until it's cleared. The model clears
+//! its memos at the end of every update, so a render sees values worked out from the model as it
+//! is, and views asking for the same value more than once between updates share the work.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A value kept until it's cleared.
+pub struct Memo<T>(RefCell<Option<Rc<T>>>);
+
+impl<T> Memo<T> {
+    /// The value, worked out with `compute` if there isn't one kept.
+    pub fn get(&self, compute: impl FnOnce() -> T) -> Rc<T> {
+        let mut value = self.0.borrow_mut();
+        value.get_or_insert_with(|| Rc::new(compute())).clone()
+    }
+
+    /// Forget the value, what it was worked out from changed.
+    pub fn clear(&mut self) {
+        *self.0.get_mut() = None;
+    }
+}
+
+impl<T> Default for Memo<T> {
+    fn default() -> Self {
+        Memo(RefCell::new(None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn kept_until_cleared() {
+        let computed = Cell::new(0);
+        let compute = || {
+            computed.set(computed.get() + 1);
+            computed.get() * 10
+        };
+
+        let mut memo = Memo::default();
+        assert_eq!(*memo.get(compute), 10);
+        assert_eq!(*memo.get(compute), 10);
+        assert_eq!(computed.get(), 1);
+
+        memo.clear();
+        assert_eq!(*memo.get(compute), 20);
+        assert_eq!(computed.get(), 2);
+    }
+}
@@ -0,0 +1,67 @@
+//! A context menu for the counter, opened by right clicking the count.
+
+use wasm_bindgen::JsCast;
+use log::error;
+use crate::Msg;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+
+/// The class of the menu's element, clicks inside it don't close it.
+pub const CLASS: &str = "context-menu";
+
+/// The things the menu can do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Item {
+    Reset,
+    Copy,
+    ToggleAutoIncrement,
+}
+
+/// Whether an event happened inside the menu, even if the menu is in a shadow root.
+fn inside_menu(event: &web_sys::Event) -> bool {
+    let selector = format!(".{}", CLASS);
+    event.composed_path()
+        .iter()
+        .filter_map(|target| target.dyn_into::<web_sys::Element>().ok())
+        .any(|element| element.matches(&selector).unwrap_or(false))
+}
+
+/// Send `Msg::CloseMenu` to the app on clicks outside the menu and when Escape is pressed.
+///
+/// The listeners are kept until `Shared::unwatch_menu()` is called.
+pub fn watch(shared: Shared) {
+    let document = match crate::document() {
+        Ok(document) => document,
+        Err(e) => {
+            error!("error watching for clicks outside the menu: {:?}", e);
+            return;
+        }
+    };
+
+    let on_pointer_down = {
+        let shared = shared.clone();
+        EventListener::new(&document, "pointerdown", move |event| {
+            if !inside_menu(&event) {
+                shared.dispatch(Msg::CloseMenu);
+            }
+        })
+    };
+
+    let on_key_down = {
+        let shared = shared.clone();
+        EventListener::new(&document, "keydown", move |event| {
+            let escape = event.dyn_ref::<web_sys::KeyboardEvent>()
+                .is_some_and(|event| event.key() == "Escape");
+            if escape {
+                shared.dispatch(Msg::CloseMenu);
+            }
+        })
+    };
+
+    match (on_pointer_down, on_key_down) {
+        (Ok(on_pointer_down), Ok(on_key_down)) => {
+            shared.watch_menu(vec![on_pointer_down, on_key_down]);
+        }
+        _ => error!("error watching for clicks outside the menu"),
+    }
+}
@@ -0,0 +1,112 @@
+//! Upgrading what older versions of the counter kept or exported.
+//!
+//! Each format has its migrations, one for each version after the first, upgrading from the
+//! version before it. Exported snapshots say which version they are, and so does everything kept
+//! in storage, in an envelope around it:
+//!
+//! ```json
+//! { "version": 1, "value": { "count": 42, "changes": 3 } }
+//! ```
+//!
+//! Values kept before they had an envelope are version 0.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use crate::Count;
+
+/// Upgrades a value to one version of its format from the version before it.
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// The count stashed in session storage.
+pub const STASH: &[Migration] = &[enveloped];
+
+/// The user's preferences.
+pub const PREFERENCES: &[Migration] = &[enveloped];
+
+/// The changes waiting to be saved.
+pub const PENDING: &[Migration] = &[pending_from_counts];
+
+/// The tally of changes made in each tab.
+pub const TALLY: &[Migration] = &[enveloped];
+
+/// Upgrade `value` from version `from` to the latest version `migrations` upgrade to.
+pub fn upgrade(mut value: Value, from: u64, migrations: &[Migration]) -> Result<Value, String> {
+    if from > migrations.len() as u64 {
+        return Err(format!("it's from a newer version of the counter (version {})", from));
+    }
+
+    for migration in &migrations[from as usize..] {
+        value = migration(value)?;
+    }
+    Ok(value)
+}
+
+/// `value` as JSON to keep in storage, in an envelope saying it's the latest version of its format.
+pub fn envelope<T: Serialize>(value: &T, migrations: &[Migration]) -> String {
+    json!({ "version": migrations.len(), "value": value }).to_string()
+}
+
+/// Take a value kept by `envelope` back out, upgrading it from the version it was kept in.
+pub fn open<T: DeserializeOwned>(json: &str, migrations: &[Migration]) -> Result<T, String> {
+    let (version, value) = match serde_json::from_str(json).map_err(|e| e.to_string())? {
+        Value::Object(mut object) if object.contains_key("version") => {
+            let version = object["version"].as_u64()
+                .ok_or_else(|| "the version isn't a number".to_owned())?;
+            (version, object.remove("value").unwrap_or(Value::Null))
+        }
+        value => (0, value),
+    };
+    serde_json::from_value(upgrade(value, version, migrations)?).map_err(|e| e.to_string())
+}
+
+/// Version 0 was the value on its own, version 1 is the same in an envelope.
+fn enveloped(value: Value) -> Result<Value, String> {
+    Ok(value)
+}
+
+/// Version 0 of the pending changes was also every count queued, rather than the latest and how
+/// many there were.
+fn pending_from_counts(value: Value) -> Result<Value, String> {
+    let counts: Vec<Count> = match value {
+        Value::Array(_) => serde_json::from_value(value).map_err(|e| e.to_string())?,
+        value => return Ok(value),
+    };
+    match counts.last() {
+        Some(count) => Ok(json!({ "count": count, "changes": counts.len() })),
+        None => Err("no changes are waiting".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::Pending;
+
+    #[test]
+    fn round_trip() {
+        let pending = Pending { count: 2, changes: 2 };
+        let json = envelope(&pending, PENDING);
+        assert_eq!(json, r#"{"value":{"changes":2,"count":2},"version":1}"#);
+        assert_eq!(open(&json, PENDING), Ok(pending));
+        assert_eq!(open::<Count>(&envelope(&42, STASH), STASH), Ok(42));
+    }
+
+    #[test]
+    fn older_formats() {
+        // kept before there were envelopes
+        assert_eq!(open::<Count>("42", STASH), Ok(42));
+        let pending = Pending { count: 3, changes: 3 };
+        assert_eq!(open(r#"{ "count": 3, "changes": 3 }"#, PENDING), Ok(pending));
+        assert_eq!(open("[1, 2, 3]", PENDING), Ok(pending));
+        assert!(open::<Pending>("[]", PENDING).is_err());
+    }
+
+    #[test]
+    fn newer_formats() {
+        let json = r#"{ "version": 2, "value": 42 }"#;
+        let newer = "it's from a newer version of the counter (version 2)".to_owned();
+        assert_eq!(open::<Count>(json, STASH), Err(newer));
+        assert!(open::<Count>(r#"{ "version": "one", "value": 42 }"#, STASH).is_err());
+    }
+}
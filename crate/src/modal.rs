@@ -0,0 +1,142 @@
+//! A modal dialog asking the user to confirm something before it's done.
+//!
+//! The dialog covers the page with an overlay and keeps focus inside itself while it's open, Tab
+//! past the last button goes round to the first and Shift+Tab back round to the last. Escape
+//! cancels, as does clicking the overlay around the dialog. Its own messages are wrapped in
+//! `crate::Msg::Modal`, the confirm button sends whatever message it was given.
+
+use wasm_bindgen::JsCast;
+use euca::dom::{Dom, Handler};
+use crate::{focus, Cmd};
+
+/// The focus key of the button cancelling, first in tab order and focused when the dialog opens.
+pub const CANCEL: &str = "modal-cancel";
+
+/// The focus key of the button confirming.
+pub const CONFIRM: &str = "modal-confirm";
+
+/// The id of the question, which names the dialog.
+const QUESTION: &str = "modal-question";
+
+/// What the dialog can be asked to do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Msg {
+    /// Close without doing anything.
+    Cancel,
+    /// Move focus to the button with the given focus key, Tab went past the other end.
+    Focus(&'static str),
+}
+
+/// Something the user is asked about before it's done.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Question {
+    Reset,
+    ClearHistory,
+}
+
+impl Question {
+    /// The translation key for the question.
+    pub fn text(self) -> &'static str {
+        match self {
+            Question::Reset => "confirm_reset",
+            Question::ClearHistory => "confirm_clear_history",
+        }
+    }
+
+    /// The translation key for the button confirming.
+    pub fn confirm(self) -> &'static str {
+        match self {
+            Question::Reset => "reset",
+            Question::ClearHistory => "clear_history",
+        }
+    }
+
+    /// The message doing it once it's confirmed.
+    pub fn msg(self) -> crate::Msg {
+        match self {
+            Question::Reset => crate::Msg::Reset,
+            Question::ClearHistory => crate::Msg::ClearHistory,
+        }
+    }
+
+    /// The focus key of the element focus goes back to when the dialog closes.
+    pub fn return_focus(self) -> &'static str {
+        match self {
+            Question::Reset => "count",
+            Question::ClearHistory => "clear-history",
+        }
+    }
+}
+
+/// The dialog asking `question`, with buttons labelled `cancel` and `confirm`. Confirming sends
+/// `msg`.
+pub fn view(question: &str, cancel: &str, confirm: &str, msg: crate::Msg)
+-> Dom<crate::Msg, Cmd>
+{
+    let button = |key, text| Dom::elem("button")
+        .attr("type", "button")
+        .attr(focus::KEY, key)
+        .push(Dom::text(text));
+
+    Dom::elem("div")
+        .attr("class", "modal-overlay")
+        .on("click", Handler::Event(overlay_clicked))
+        .push(Dom::elem("div")
+            .attr("class", "modal")
+            .attr("role", "alertdialog")
+            .attr("aria-modal", "true")
+            .attr("aria-labelledby", QUESTION)
+            .on("keydown", Handler::Event(key_down))
+            .push(Dom::elem("p").attr("id", QUESTION).push(Dom::text(question)))
+            .push(button(CANCEL, cancel).event("click", crate::Msg::Modal(Msg::Cancel)))
+            .push(button(CONFIRM, confirm).event("click", msg)))
+}
+
+/// Cancel on clicks on the overlay itself, not ones inside the dialog bubbling up to it.
+fn overlay_clicked(event: web_sys::Event) -> Option<crate::Msg> {
+    match event.target() == event.current_target() {
+        true => Some(crate::Msg::Modal(Msg::Cancel)),
+        false => None,
+    }
+}
+
+fn key_down(event: web_sys::Event) -> Option<crate::Msg> {
+    let event: web_sys::KeyboardEvent = event.dyn_into().ok()?;
+    let focused = event.target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .and_then(|target| target.get_attribute(focus::KEY));
+    let msg = key_msg(&event.key(), event.shift_key(), focused.as_deref())?;
+    event.prevent_default();
+    Some(crate::Msg::Modal(msg))
+}
+
+/// The message for a key pressed in the dialog, with the button with focus key `focused` focused.
+///
+/// Tab is left to the browser except where it'd leave the dialog.
+fn key_msg(key: &str, shift: bool, focused: Option<&str>) -> Option<Msg> {
+    match (key, shift, focused) {
+        ("Escape", _, _) => Some(Msg::Cancel),
+        ("Tab", false, Some(CONFIRM)) => Some(Msg::Focus(CANCEL)),
+        ("Tab", true, Some(CANCEL)) => Some(Msg::Focus(CONFIRM)),
+        // somewhere that isn't a button, like the question after a click on it
+        ("Tab", false, None) => Some(Msg::Focus(CANCEL)),
+        ("Tab", true, None) => Some(Msg::Focus(CONFIRM)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        assert_eq!(key_msg("Escape", false, Some(CONFIRM)), Some(Msg::Cancel));
+        assert_eq!(key_msg("Tab", false, Some(CANCEL)), None);
+        assert_eq!(key_msg("Tab", false, Some(CONFIRM)), Some(Msg::Focus(CANCEL)));
+        assert_eq!(key_msg("Tab", true, Some(CONFIRM)), None);
+        assert_eq!(key_msg("Tab", true, Some(CANCEL)), Some(Msg::Focus(CONFIRM)));
+        assert_eq!(key_msg("Tab", false, None), Some(Msg::Focus(CANCEL)));
+        assert_eq!(key_msg("Enter", false, Some(CONFIRM)), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! HTTP requests using `fetch`.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use std::fmt;
+
+/// An HTTP request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl Request {
+    /// A `POST` request sending JSON to the given url.
+    pub fn post_json(url: impl Into<String>, body: String) -> Self {
+        Request {
+            method: "POST",
+            url: url.into(),
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: Some(body),
+        }
+    }
+
+    /// Add a header to the request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// A `GET` request for the given url.
+    #[cfg_attr(feature = "graphql", allow(dead_code))]
+    pub fn get(url: impl Into<String>) -> Self {
+        Request {
+            method: "GET",
+            url: url.into(),
+            headers: vec![],
+            body: None,
+        }
+    }
+}
+
+/// Why a request failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The request didn't make it to the server or the response didn't make it back.
+    Network(String),
+    /// The server responded with an error status.
+    Status(u16, String),
+    /// The server responded, but not with what we asked for.
+    Response(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Network(e) => write!(f, "network error: {}", e),
+            Error::Status(code, text) => write!(f, "server error: {} {}", code, text),
+            Error::Response(e) => write!(f, "invalid response: {}", e),
+        }
+    }
+}
+
+/// Send a request and return the body of the response.
+pub async fn fetch(request: Request) -> Result<String, Error> {
+    let window = web_sys::window()
+        .ok_or_else(|| Error::Network("no window to fetch from".into()))?;
+
+    let init = web_sys::RequestInit::new();
+    init.set_method(request.method);
+    if let Some(ref body) = request.body {
+        init.set_body(&JsValue::from_str(body));
+    }
+
+    let headers = web_sys::Headers::new().map_err(network)?;
+    for (name, value) in &request.headers {
+        headers.append(name, value).map_err(network)?;
+    }
+    init.set_headers(&headers);
+
+    let response = JsFuture::from(window.fetch_with_str_and_init(&request.url, &init))
+        .await
+        .map_err(network)?
+        .dyn_into::<web_sys::Response>()
+        .map_err(network)?;
+
+    if !response.ok() {
+        return Err(Error::Status(response.status(), response.status_text()));
+    }
+
+    let body = JsFuture::from(response.text().map_err(network)?)
+        .await
+        .map_err(network)?;
+
+    body.as_string()
+        .ok_or_else(|| Error::Network("response body was not text".into()))
+}
+
+fn network(e: JsValue) -> Error {
+    let message = match e.dyn_ref::<js_sys::Error>() {
+        Some(e) => e.message().into(),
+        None => format!("{:?}", e),
+    };
+
+    Error::Network(message)
+}
@@ -0,0 +1,110 @@
+//! Browser notifications when the count reaches a milestone.
+
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Notification, NotificationPermission};
+use log::error;
+use crate::{Count, Msg};
+use crate::spawn::Job;
+
+/// Whether we may show notifications.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Permission {
+    /// The browser doesn't support notifications.
+    Unsupported,
+    /// The user hasn't decided yet, we have to ask.
+    Default,
+    Granted,
+    Denied,
+}
+
+impl Permission {
+    fn parse(permission: &str) -> Self {
+        match permission {
+            "granted" => Permission::Granted,
+            "denied" => Permission::Denied,
+            _ => Permission::Default,
+        }
+    }
+}
+
+/// Whether we may currently show notifications.
+pub fn permission() -> Permission {
+    let supported = web_sys::window()
+        .and_then(|window| js_sys::Reflect::has(&window, &"Notification".into()).ok())
+        .unwrap_or(false);
+
+    if !supported {
+        return Permission::Unsupported;
+    }
+
+    match Notification::permission() {
+        NotificationPermission::Granted => Permission::Granted,
+        NotificationPermission::Denied => Permission::Denied,
+        _ => Permission::Default,
+    }
+}
+
+/// Ask the user to let us show notifications, finishing with `Msg::NotificationPermission` with
+/// their answer.
+pub fn request_permission() -> Job {
+    Job::new("request_permission", async {
+        let promise = match Notification::request_permission() {
+            Ok(promise) => promise,
+            Err(e) => {
+                error!("error requesting notification permission: {:?}", e);
+                return Msg::NotificationPermission(permission());
+            }
+        };
+
+        let permission = match JsFuture::from(promise).await {
+            Ok(permission) => permission.as_string()
+                .map_or(Permission::Default, |permission| Permission::parse(&permission)),
+            Err(e) => {
+                error!("error requesting notification permission: {:?}", e);
+                permission()
+            }
+        };
+
+        Msg::NotificationPermission(permission)
+    })
+}
+
+/// Show a notification with the given text.
+pub fn show(text: &str) {
+    if let Err(e) = Notification::new(text) {
+        error!("error showing notification: {:?}", e);
+    }
+}
+
+/// The furthest milestone reached when the count went from `old` to `new`, if any.
+///
+/// Milestones are reached going up and going down, landing on one counts.
+pub fn reached(milestones: &[Count], old: Count, new: Count) -> Option<Count> {
+    let passed = milestones.iter().copied();
+    if new > old {
+        passed.filter(|&m| old < m && m <= new).max()
+    }
+    else {
+        passed.filter(|&m| new <= m && m < old).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn milestones_reached() {
+        let milestones = [-10, 10, 100];
+        assert_eq!(reached(&milestones, 9, 10), Some(10));
+        assert_eq!(reached(&milestones, 10, 11), None);
+        assert_eq!(reached(&milestones, 5, 500), Some(100));
+        assert_eq!(reached(&milestones, 120, 50), Some(100));
+        assert_eq!(reached(&milestones, 0, -10), Some(-10));
+        assert_eq!(reached(&milestones, 3, 3), None);
+        assert_eq!(reached(&[], 0, 10), None);
+
+        assert_eq!(Permission::parse("granted"), Permission::Granted);
+        assert_eq!(Permission::parse("default"), Permission::Default);
+    }
+}
@@ -0,0 +1,156 @@
+//! What happens when a step would take the count past the largest or smallest it can be.
+
+use std::convert::TryFrom;
+use crate::Count;
+
+/// How the count changes by a step it has no room for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    /// Stop at the largest or smallest count.
+    Saturate,
+    /// Go round to the other end, like an odometer.
+    Wrap,
+}
+
+impl Overflow {
+    pub const ALL: [Overflow; 2] = [Overflow::Saturate, Overflow::Wrap];
+
+    /// What's stored for the policy.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Overflow::Saturate => "saturate",
+            Overflow::Wrap => "wrap",
+        }
+    }
+
+    /// The policy stored as `name`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "saturate" => Some(Overflow::Saturate),
+            "wrap" => Some(Overflow::Wrap),
+            _ => None,
+        }
+    }
+
+    /// The translation key for what the settings panel calls it.
+    pub fn label(self) -> &'static str {
+        match self {
+            Overflow::Saturate => "saturate",
+            Overflow::Wrap => "wrap",
+        }
+    }
+
+    /// `count` changed by `steps` of `step`.
+    ///
+    /// Saturating, a change too large to work out stops the count at the end it was heading for.
+    pub fn add(self, count: Count, steps: i32, step: Count) -> Count {
+        match (self, past(count, steps, step)) {
+            (Overflow::Saturate, Some(Limit::Max)) => Count::MAX,
+            (Overflow::Saturate, Some(Limit::Min)) => Count::MIN,
+            _ => count.wrapping_add(Count::from(steps).wrapping_mul(step)),
+        }
+    }
+
+    /// `count` changed by `delta`, as merged in from another tab's changes.
+    pub fn add_delta(self, count: Count, delta: i64) -> Count {
+        let sum = i128::from(count).checked_add(i128::from(delta));
+        match (self, sum.and_then(|sum| Count::try_from(sum).ok())) {
+            (_, Some(sum)) => sum,
+            (Overflow::Saturate, None) if delta > 0 => Count::MAX,
+            (Overflow::Saturate, None) => Count::MIN,
+            (Overflow::Wrap, None) => i128::from(count).wrapping_add(i128::from(delta)) as Count,
+        }
+    }
+}
+
+/// A limit that stopped the count short of where a click would have taken it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    /// The largest count there is.
+    Max,
+    /// The smallest count there is.
+    Min,
+    /// Zero, in a countdown.
+    Zero,
+}
+
+impl Limit {
+    /// The translation key for why the count stopped there.
+    pub fn label(self) -> &'static str {
+        match self {
+            Limit::Max => "limit_max",
+            Limit::Min => "limit_min",
+            Limit::Zero => "limit_zero",
+        }
+    }
+}
+
+/// The end `steps` of `step` from `count` would go past, if there's no room for them.
+pub fn past(count: Count, steps: i32, step: Count) -> Option<Limit> {
+    let steps = Count::from(steps);
+    match steps.checked_mul(step).and_then(|change| count.checked_add(change)) {
+        Some(_) => None,
+        None if (steps < 0) == (step < 0) => Some(Limit::Max),
+        None => Some(Limit::Min),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        for overflow in &Overflow::ALL {
+            assert_eq!(Overflow::parse(overflow.as_str()), Some(*overflow));
+        }
+        assert_eq!(Overflow::parse("explode"), None);
+    }
+
+    #[test]
+    fn saturating() {
+        let add = |count, steps, step| Overflow::Saturate.add(count, steps, step);
+        assert_eq!(add(5, 2, 3), 11);
+        assert_eq!(add(Count::MAX - 1, 1, 1), Count::MAX);
+        assert_eq!(add(Count::MAX, 1, 1), Count::MAX);
+        assert_eq!(add(Count::MAX, -1, 1), Count::MAX - 1);
+        assert_eq!(add(Count::MIN + 1, -1, 1), Count::MIN);
+        assert_eq!(add(Count::MIN, -1, 1), Count::MIN);
+        assert_eq!(add(Count::MIN, 1, -1), Count::MIN);
+        assert_eq!(add(Count::MIN, 1, 1), Count::MIN + 1);
+        // too many steps to multiply out
+        assert_eq!(add(0, i32::MAX, Count::MAX), Count::MAX);
+        assert_eq!(add(0, i32::MIN, Count::MAX), Count::MIN);
+        assert_eq!(add(0, -2, Count::MIN), Count::MAX);
+
+        let add = |count, delta| Overflow::Saturate.add_delta(count, delta);
+        assert_eq!(add(5, -7), -2);
+        assert_eq!(add(Count::MAX - 1, 2), Count::MAX);
+        assert_eq!(add(Count::MIN, i64::MIN), Count::MIN);
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(past(Count::MAX - 1, 1, 1), None);
+        assert_eq!(past(Count::MAX - 1, 1, 2), Some(Limit::Max));
+        assert_eq!(past(Count::MIN, -1, 1), Some(Limit::Min));
+        assert_eq!(past(Count::MIN, 1, -1), Some(Limit::Min));
+        assert_eq!(past(Count::MIN, 0, Count::MAX), None);
+    }
+
+    #[test]
+    fn wrapping() {
+        let add = |count, steps, step| Overflow::Wrap.add(count, steps, step);
+        assert_eq!(add(5, -2, 3), -1);
+        assert_eq!(add(Count::MAX, 1, 1), Count::MIN);
+        assert_eq!(add(Count::MAX, 1, 3), Count::MIN + 2);
+        assert_eq!(add(Count::MIN, -1, 1), Count::MAX);
+        assert_eq!(add(Count::MIN, 1, -1), Count::MAX);
+        assert_eq!(add(Count::MAX - 1, 1, 1), Count::MAX);
+
+        let add = |count, delta| Overflow::Wrap.add_delta(count, delta);
+        assert_eq!(add(5, -7), -2);
+        assert_eq!(add(Count::MAX, 1), Count::MIN);
+        assert_eq!(add(Count::MIN, -2), Count::MAX - 1);
+    }
+}
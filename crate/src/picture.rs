@@ -0,0 +1,138 @@
+//! The counter as a PNG image, to share.
+//!
+//! The count is drawn on an offscreen canvas with a sparkline of its history under it, and the
+//! canvas is offered for download once the browser has encoded it, which it does asynchronously.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use log::error;
+use crate::Count;
+use crate::download;
+
+/// The size of the image in pixels.
+const WIDTH: u32 = 600;
+const HEIGHT: u32 = 300;
+
+/// Where the sparkline goes, under the count.
+const LEFT: f64 = 40.0;
+const RIGHT: f64 = 560.0;
+const TOP: f64 = 200.0;
+const BOTTOM: f64 = 270.0;
+
+/// The most points in the sparkline, longer histories are sampled down to about this many.
+const MAX_POINTS: usize = 260;
+
+/// What's drawn in the image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Picture {
+    /// The count, as it's shown.
+    pub count: String,
+    /// The sparkline, oldest first, each point's height from 0 for the lowest count to 1 for the
+    /// highest. Empty when there aren't two counts to draw a line between.
+    pub line: Vec<f64>,
+}
+
+impl Picture {
+    /// A picture of the `count`, with a sparkline of the `counts` it's been.
+    pub fn new(count: String, counts: &[Count]) -> Self {
+        let line = if counts.len() < 2 { vec![] } else { heights(counts) };
+        Picture { count, line }
+    }
+}
+
+/// How high each of the `counts` is between the lowest and the highest, halfway if they're all the
+/// same.
+fn heights(counts: &[Count]) -> Vec<f64> {
+    let stride = counts.len().div_ceil(MAX_POINTS).max(1);
+    let mut sampled: Vec<f64> = counts.iter().step_by(stride).map(|&count| count as f64).collect();
+    // always end on the count as it is now
+    if (counts.len() - 1) % stride != 0 {
+        sampled.extend(counts.last().map(|&count| count as f64));
+    }
+
+    let low = sampled.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = sampled.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    sampled.into_iter()
+        .map(|count| if high > low { (count - low) / (high - low) } else { 0.5 })
+        .collect()
+}
+
+/// Download `picture` as a PNG file called `name`.
+pub fn save(name: &str, picture: &Picture) {
+    if let Err(e) = try_save(name, picture) {
+        error!("error exporting {}: {:?}", name, e);
+    }
+}
+
+fn try_save(name: &str, picture: &Picture) -> Result<(), JsValue> {
+    let canvas = draw(picture)?;
+    let name = name.to_owned();
+    let encoded = Closure::once_into_js(move |blob: Option<web_sys::Blob>| match blob {
+        Some(blob) => download::save_blob(&name, &blob),
+        None => error!("error encoding {}", name),
+    });
+    canvas.to_blob(encoded.unchecked_ref())
+}
+
+/// Draw the picture on a canvas of its own.
+fn draw(picture: &Picture) -> Result<web_sys::HtmlCanvasElement, JsValue> {
+    let canvas: web_sys::HtmlCanvasElement = crate::document()?
+        .create_element("canvas")?
+        .dyn_into()?;
+    canvas.set_width(WIDTH);
+    canvas.set_height(HEIGHT);
+
+    let context: web_sys::CanvasRenderingContext2d = canvas.get_context("2d")?
+        .ok_or("canvas has no 2d context")?
+        .dyn_into()?;
+
+    // a transparent background shows up black in some image viewers
+    context.set_fill_style_str("#fff");
+    context.fill_rect(0.0, 0.0, f64::from(WIDTH), f64::from(HEIGHT));
+
+    context.set_fill_style_str("#222");
+    context.set_font("bold 96px sans-serif");
+    context.set_text_align("center");
+    context.set_text_baseline("middle");
+    context.fill_text(&picture.count, f64::from(WIDTH) / 2.0, 110.0)?;
+
+    if picture.line.len() > 1 {
+        let last = (picture.line.len() - 1) as f64;
+        context.set_stroke_style_str("#1565c0");
+        context.set_line_width(3.0);
+        context.set_line_join("round");
+        context.begin_path();
+        for (i, height) in picture.line.iter().enumerate() {
+            let x = LEFT + i as f64 / last * (RIGHT - LEFT);
+            let y = BOTTOM - height * (BOTTOM - TOP);
+            if i == 0 {
+                context.move_to(x, y);
+            }
+            else {
+                context.line_to(x, y);
+            }
+        }
+        context.stroke();
+    }
+
+    Ok(canvas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparkline() {
+        assert_eq!(Picture::new("5".to_owned(), &[5]).line, Vec::<f64>::new());
+        assert_eq!(Picture::new("3".to_owned(), &[1, 5, 3]).line, vec![0.0, 1.0, 0.5]);
+        assert_eq!(Picture::new("2".to_owned(), &[2, 2]).line, vec![0.5, 0.5]);
+
+        // long histories are sampled, ending on the count as it is now
+        let counts: Vec<Count> = (0..1000).collect();
+        let line = Picture::new("999".to_owned(), &counts).line;
+        assert!(line.len() <= MAX_POINTS + 1);
+        assert_eq!(line.first(), Some(&0.0));
+        assert_eq!(line.last(), Some(&1.0));
+    }
+}
@@ -0,0 +1,66 @@
+//! Pointer events, one binding for mice, touches and pens alike.
+//!
+//! `on_pointer!` listens for a pointer being pressed over an element, moving, and being let go,
+//! and hands its handler which of those happened to which pointer, so a gesture is written once
+//! rather than once for mouse events and again for touch events. A pressed pointer is captured,
+//! its moves and release keep coming to the element when it strays off it. Moves of pointers that
+//! aren't pressed are dropped rather than rendered for.
+
+use wasm_bindgen::JsCast;
+use log::warn;
+use crate::Msg;
+
+/// What a pointer did.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    /// It was pressed, a mouse button went down or a finger or pen touched.
+    Down,
+    /// It moved while pressed.
+    Move,
+    /// It was let go, or the browser took it over.
+    Up,
+}
+
+/// A pointer, and where it is across the page.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pointer {
+    pub id: i32,
+    /// The client x coordinate.
+    pub x: i32,
+}
+
+/// The message `handler` has for a pointer `event` in `phase`, see `on_pointer!`.
+pub fn handle(
+    event: web_sys::Event,
+    phase: Phase,
+    handler: impl FnOnce(Phase, Pointer) -> Option<Msg>,
+) -> Option<Msg> {
+    let event: web_sys::PointerEvent = event.dyn_into().ok()?;
+    match phase {
+        // the main button only, the others have menus and such of their own
+        Phase::Down if event.button() != 0 => return None,
+        Phase::Move if event.buttons() == 0 => return None,
+        _ => {}
+    }
+
+    let pointer = Pointer {
+        id: event.pointer_id(),
+        x: event.client_x(),
+    };
+    let msg = handler(phase, pointer)?;
+
+    if phase == Phase::Down {
+        // keep getting the pointer's events when it leaves the element
+        let target = event.current_target()
+            .and_then(|target| target.dyn_into::<web_sys::Element>().ok());
+        if let Some(target) = target {
+            if let Err(e) = target.set_pointer_capture(pointer.id) {
+                warn!("error capturing pointer: {:?}", e);
+            }
+        }
+
+        // don't select text while dragging
+        event.prevent_default();
+    }
+    Some(msg)
+}
@@ -0,0 +1,187 @@
+//! The settings the user picks, remembered together in local storage.
+//!
+//! ```json
+//! {
+//!   "step": 2,
+//!   "places": 0,
+//!   "theme": "Forest",
+//!   "locale": "de-DE",
+//!   "muted": false,
+//!   "auto_increment": 500,
+//!   "overflow": "saturate"
+//! }
+//! ```
+//!
+//! Only the settings that differ from the config's are kept, so the rest follow the config when
+//! it changes, e.g. a new `data-step` on the host element.
+//!
+//! Storage can hold anything, a record from an older version or one edited by hand. Each setting
+//! in it is checked on its own, anything missing or invalid is left at its default rather than
+//! throwing the rest away.
+
+use serde_json::{json, Map, Value};
+use crate::decimal::MAX_PLACES;
+use crate::locale::Locale;
+use crate::overflow::Overflow;
+use crate::theme::{self, Theme};
+
+/// The longest step the count can be changed by, in units of the count.
+pub const MAX_STEP: i32 = 1_000_000;
+
+/// The fewest milliseconds between automatic increments, any faster and the count is a blur.
+pub const MIN_INTERVAL: u32 = 50;
+
+/// Whether `step` changes the count, by no more than `MAX_STEP`.
+pub fn is_valid_step(step: i32) -> bool {
+    step != 0 && step.unsigned_abs() <= MAX_STEP as u32
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preferences {
+    /// In units of the count, at `places` decimal places.
+    pub step: i32,
+    /// How many decimal places the count has.
+    pub places: u32,
+    pub theme: &'static Theme,
+    /// The locale the user picked, if they picked one.
+    pub locale: Option<&'static Locale>,
+    pub muted: bool,
+    /// Milliseconds between automatic increments, if enabled.
+    pub auto_increment: Option<u32>,
+    /// What a step past the largest or smallest count does.
+    pub overflow: Overflow,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            step: 1,
+            places: 0,
+            theme: &theme::THEMES[0],
+            locale: None,
+            muted: false,
+            auto_increment: None,
+            overflow: Overflow::Saturate,
+        }
+    }
+}
+
+impl Preferences {
+    /// The settings that differ from `defaults`, the config's.
+    pub fn to_value(&self, defaults: &Preferences) -> Value {
+        let mut value = Map::new();
+        let mut keep = |key: &str, setting: Value, default: Value| {
+            if setting != default {
+                value.insert(key.to_owned(), setting);
+            }
+        };
+        keep("step", json!(self.step), json!(defaults.step));
+        keep("places", json!(self.places), json!(defaults.places));
+        keep("theme", json!(self.theme.name), json!(defaults.theme.name));
+        let tag = |locale: Option<&Locale>| locale.map(|locale| locale.tag);
+        keep("locale", json!(tag(self.locale)), json!(tag(defaults.locale)));
+        keep("muted", json!(self.muted), json!(defaults.muted));
+        // 0 for off, so it isn't mistaken for being left out
+        let interval = |preferences: &Preferences| preferences.auto_increment.unwrap_or(0);
+        keep("auto_increment", json!(interval(self)), json!(interval(defaults)));
+        keep("overflow", json!(self.overflow.as_str()), json!(defaults.overflow.as_str()));
+        Value::Object(value)
+    }
+
+    /// Read stored preferences over `defaults`, keeping the default for anything invalid.
+    pub fn load(value: &Value, defaults: Preferences) -> Self {
+        let step = value["step"].as_i64()
+            .filter(|&step| step != 0 && step.abs() <= MAX_STEP as i64)
+            .map_or(defaults.step, |step| step as i32);
+        let places = value["places"].as_u64()
+            .filter(|&places| places <= u64::from(MAX_PLACES))
+            .map_or(defaults.places, |places| places as u32);
+        let theme = value["theme"].as_str()
+            .and_then(Theme::find)
+            .unwrap_or(defaults.theme);
+        let locale = value["locale"].as_str()
+            .and_then(Locale::find)
+            .or(defaults.locale);
+        let muted = value["muted"].as_bool().unwrap_or(defaults.muted);
+        let auto_increment = match value["auto_increment"].as_u64() {
+            Some(0) => None,
+            Some(ms) if ms >= MIN_INTERVAL as u64 => Some(ms.min(u32::MAX as u64) as u32),
+            _ => defaults.auto_increment,
+        };
+        let overflow = value["overflow"].as_str()
+            .and_then(Overflow::parse)
+            .unwrap_or(defaults.overflow);
+
+        Preferences { step, places, theme, locale, muted, auto_increment, overflow }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let preferences = Preferences {
+            step: -3,
+            places: 2,
+            theme: &theme::THEMES[1],
+            locale: Some(&crate::locale::LOCALES[1]),
+            muted: true,
+            auto_increment: Some(250),
+            overflow: Overflow::Wrap,
+        };
+        let defaults = Preferences::default();
+        let loaded = Preferences::load(&preferences.to_value(&defaults), defaults.clone());
+        assert_eq!(loaded, preferences);
+
+        let off = Preferences { auto_increment: None, ..preferences };
+        let defaults = Preferences { auto_increment: Some(1000), ..Preferences::default() };
+        assert_eq!(Preferences::load(&off.to_value(&defaults), defaults), off);
+    }
+
+    #[test]
+    fn defaults_are_left_out() {
+        let defaults = Preferences { step: 5, ..Preferences::default() };
+        assert_eq!(defaults.to_value(&defaults), json!({}));
+        let muted = Preferences { muted: true, ..defaults.clone() };
+        assert_eq!(muted.to_value(&defaults), json!({ "muted": true }));
+
+        // the step wasn't picked, so it follows the config
+        let config = Preferences { step: 2, ..Preferences::default() };
+        let loaded = Preferences::load(&muted.to_value(&defaults), config.clone());
+        assert_eq!(loaded, Preferences { muted: true, ..config });
+    }
+
+    #[test]
+    fn defaults() {
+        let defaults = Preferences {
+            step: 5,
+            auto_increment: Some(1000),
+            ..Preferences::default()
+        };
+        assert_eq!(Preferences::load(&Value::Null, defaults.clone()), defaults);
+        assert_eq!(Preferences::load(&json!("corrupt"), defaults.clone()), defaults);
+        assert_eq!(Preferences::load(&json!([1, 2]), defaults.clone()), defaults);
+        assert_eq!(Preferences::load(&json!({}), defaults.clone()), defaults);
+    }
+
+    #[test]
+    fn invalid_settings_are_left_alone() {
+        let value = json!({
+            "step": 0,
+            "places": 4,
+            "theme": "Nowhere",
+            "locale": "xx",
+            "muted": "yes",
+            "auto_increment": 1,
+            "overflow": "explode"
+        });
+        assert_eq!(Preferences::load(&value, Preferences::default()), Preferences::default());
+
+        // the valid ones are still loaded
+        let value = json!({ "step": 10000000, "muted": true });
+        let loaded = Preferences::load(&value, Preferences::default());
+        assert_eq!(loaded, Preferences { muted: true, ..Preferences::default() });
+    }
+}
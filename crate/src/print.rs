@@ -0,0 +1,46 @@
+//! Printing a summary of the counter.
+//!
+//! While the app is printing it renders a summary, the count, statistics, and the whole history as
+//! a table, in place of everything else. The print dialog is opened once that's been rendered,
+//! and the app goes back to the normal view when the browser fires `afterprint` as the dialog
+//! closes.
+
+use log::error;
+use crate::Msg;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+
+/// Send `Msg::Printed` to the app whenever the print dialog closes.
+pub fn watch(shared: Shared) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => {
+            error!("error watching for printing: no window");
+            return;
+        }
+    };
+
+    let target = shared.clone();
+    let listener = EventListener::new(&window, "afterprint", move |_| {
+        target.dispatch(Msg::Printed);
+    });
+    match listener {
+        Ok(listener) => shared.listen(listener),
+        Err(e) => error!("error watching for printing: {:?}", e),
+    }
+}
+
+/// Open the print dialog for the page as it's rendered now.
+///
+/// Some browsers return from `print()` before the dialog closes, so the app waits for
+/// `afterprint` to go back to the normal view.
+pub fn print() {
+    match web_sys::window() {
+        Some(window) => {
+            if let Err(e) = window.print() {
+                error!("error printing: {:?}", e);
+            }
+        }
+        None => error!("error printing: no window"),
+    }
+}
@@ -0,0 +1,251 @@
+//! Offline use through a service worker, with the `pwa` feature.
+//!
+//! The counter registers `sw.js` when it starts. The browser checks the script for changes on
+//! every load, and installs a new version alongside the one serving the page, where it waits until
+//! every page using the old one is closed. Rather than leave it waiting, the counter offers to
+//! reload with it: the waiting worker is asked to take over, and the page reloads once it has.
+//!
+//! Browsers that can install the app as one of its own fire `beforeinstallprompt` instead of
+//! offering to themselves. The counter keeps the event and shows an "Install app" button, which
+//! shows the browser's prompt and reports what the user chose.
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{ServiceWorker, ServiceWorkerContainer, ServiceWorkerRegistration, ServiceWorkerState};
+use log::{error, warn};
+use crate::Msg;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+
+/// The service worker's script, next to the page.
+const SCRIPT: &str = "sw.js";
+
+/// What the message asking a waiting worker to take over says, `sw.js` listens for it.
+const SKIP_WAITING: &str = "skipWaiting";
+
+/// Where the service worker is at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    /// Not registered yet, or it can't be.
+    Unregistered,
+    /// Registered, with nothing newer to switch to.
+    Registered,
+    /// A new version is installing.
+    Updating,
+    /// A new version is installed and waiting to take over.
+    UpdateReady,
+}
+
+/// Whether the app can be installed, and how asking to went.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Install {
+    /// The browser hasn't offered to install the app, it may be installed already.
+    Unavailable,
+    /// The browser will install the app when asked.
+    Available,
+    /// The browser's install prompt is showing.
+    Prompting,
+    /// The user installed the app.
+    Accepted,
+    /// The user turned the prompt down.
+    Dismissed,
+}
+
+impl Install {
+    /// How the prompt went, by the `outcome` of the user's choice.
+    pub fn for_outcome(outcome: &str) -> Self {
+        match outcome {
+            "accepted" => Install::Accepted,
+            _ => Install::Dismissed,
+        }
+    }
+}
+
+/// The page's service workers, if it can have them, they're only for secure contexts.
+fn container() -> Option<ServiceWorkerContainer> {
+    let navigator = web_sys::window()?.navigator();
+    let supported = js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false);
+    if supported { Some(navigator.service_worker()) } else { None }
+}
+
+/// Register the service worker, sending `Msg::ServiceWorkerChanged` to the app as it's registered
+/// and as new versions of it install.
+pub fn register(shared: Shared) {
+    let container = match container() {
+        Some(container) => container,
+        None => {
+            warn!("service workers aren't supported here");
+            return;
+        }
+    };
+
+    let promise = container.register(SCRIPT);
+    wasm_bindgen_futures::spawn_local(async move {
+        let registration = JsFuture::from(promise).await
+            .and_then(|registration| registration.dyn_into::<ServiceWorkerRegistration>());
+        match registration {
+            Ok(registration) => watch(&shared, registration),
+            Err(e) => error!("error registering the service worker: {:?}", e),
+        }
+    });
+}
+
+/// Report the `registration` and follow the versions it installs from now on.
+fn watch(shared: &Shared, registration: ServiceWorkerRegistration) {
+    let status = match registration.waiting() {
+        Some(_) => Status::UpdateReady,
+        None => Status::Registered,
+    };
+    shared.dispatch(Msg::ServiceWorkerChanged(status));
+    if let Some(worker) = registration.installing() {
+        track(shared, worker);
+    }
+
+    let (target, updated) = (shared.clone(), registration.clone());
+    let listener = EventListener::new(&registration, "updatefound", move |_| {
+        if let Some(worker) = updated.installing() {
+            track(&target, worker);
+        }
+    });
+    match listener {
+        Ok(listener) => shared.listen(listener),
+        Err(e) => error!("error watching for service worker updates: {:?}", e),
+    }
+}
+
+/// Follow a `worker` installing until it's waiting to take over.
+fn track(shared: &Shared, worker: ServiceWorker) {
+    // the first worker installed isn't an update, there's nothing serving the page to replace
+    if container().and_then(|container| container.controller()).is_none() {
+        return;
+    }
+    shared.dispatch(Msg::ServiceWorkerChanged(Status::Updating));
+
+    let (target, installing) = (shared.clone(), worker.clone());
+    let listener = EventListener::new(&worker, "statechange", move |_| {
+        match installing.state() {
+            ServiceWorkerState::Installed => {
+                target.dispatch(Msg::ServiceWorkerChanged(Status::UpdateReady));
+            }
+            // it failed to install
+            ServiceWorkerState::Redundant => {
+                target.dispatch(Msg::ServiceWorkerChanged(Status::Registered));
+            }
+            _ => {}
+        }
+    });
+    match listener {
+        Ok(listener) => shared.listen(listener),
+        Err(e) => error!("error following the service worker's update: {:?}", e),
+    }
+}
+
+/// Have the waiting service worker take over, then reload the page with it.
+pub fn activate_update(shared: Shared) {
+    let container = match container() {
+        Some(container) => container,
+        None => return,
+    };
+
+    let listener = EventListener::new(&container, "controllerchange", |_| {
+        let reloaded = web_sys::window()
+            .ok_or_else(|| JsValue::from_str("no window"))
+            .and_then(|window| window.location().reload());
+        if let Err(e) = reloaded {
+            error!("error reloading: {:?}", e);
+        }
+    });
+    match listener {
+        Ok(listener) => shared.listen(listener),
+        Err(e) => {
+            error!("error watching for the service worker taking over: {:?}", e);
+            return;
+        }
+    }
+
+    let promise = container.get_registration();
+    wasm_bindgen_futures::spawn_local(async move {
+        let waiting = JsFuture::from(promise).await
+            .and_then(|registration| registration.dyn_into::<ServiceWorkerRegistration>())
+            .map(|registration| registration.waiting());
+        match waiting {
+            Ok(Some(worker)) => {
+                if let Err(e) = worker.post_message(&SKIP_WAITING.into()) {
+                    error!("error activating the service worker update: {:?}", e);
+                }
+            }
+            Ok(None) => warn!("no service worker update is waiting"),
+            Err(e) => error!("error getting the service worker registration: {:?}", e),
+        }
+    });
+}
+
+/// Keep the browser's offers to install the app, sending `Msg::InstallChanged` to the app when
+/// there's one to accept.
+pub fn watch_install(shared: Shared) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+
+    let target = shared.clone();
+    let listener = EventListener::new(&window, "beforeinstallprompt", move |event| {
+        // the app shows the prompt when the user asks, the browser doesn't offer it on its own
+        event.prevent_default();
+        target.set_install_prompt(event);
+        target.dispatch(Msg::InstallChanged(Install::Available));
+    });
+    match listener {
+        Ok(listener) => shared.listen(listener),
+        Err(e) => error!("error watching for the app being installable: {:?}", e),
+    }
+}
+
+/// Show the browser's prompt to install the app, sending `Msg::InstallChanged` with what the user
+/// chose.
+pub fn prompt_install(shared: Shared) {
+    // a prompt can only be shown once
+    let event = match shared.take_install_prompt() {
+        Some(event) => event,
+        None => {
+            shared.dispatch(Msg::InstallChanged(Install::Unavailable));
+            return;
+        }
+    };
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let install = match try_prompt(&event).await {
+            Ok(outcome) => Install::for_outcome(&outcome),
+            Err(e) => {
+                error!("error prompting to install the app: {:?}", e);
+                Install::Unavailable
+            }
+        };
+        shared.dispatch(Msg::InstallChanged(install));
+    });
+}
+
+/// Call `prompt()` on the `beforeinstallprompt` event and wait for the `outcome` of the user's
+/// choice. There's no binding for the event, it isn't standard.
+async fn try_prompt(event: &web_sys::Event) -> Result<String, JsValue> {
+    let prompt: js_sys::Function = js_sys::Reflect::get(event, &"prompt".into())?.dyn_into()?;
+    prompt.call0(event)?;
+
+    let choice = js_sys::Reflect::get(event, &"userChoice".into())?;
+    let choice = JsFuture::from(choice.dyn_into::<js_sys::Promise>()?).await?;
+    js_sys::Reflect::get(&choice, &"outcome".into())?
+        .as_string()
+        .ok_or_else(|| JsValue::from_str("no outcome"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install_outcomes() {
+        assert_eq!(Install::for_outcome("accepted"), Install::Accepted);
+        assert_eq!(Install::for_outcome("dismissed"), Install::Dismissed);
+    }
+}
@@ -0,0 +1,117 @@
+//! Which tab of the counter is shown, and keeping it in the url's hash.
+//!
+//! With hash routing on, a url ending in `#history` opens the counter on its history tab, and
+//! picking a tab changes the hash. Each change adds to the browser's history, so going back goes
+//! back to the tab before.
+
+use log::error;
+use crate::Msg;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tab {
+    Counter,
+    Stopwatch,
+    History,
+    Settings,
+}
+
+impl Tab {
+    /// The tabs in the order they're shown, the first is shown to begin with.
+    pub const ALL: [Tab; 4] = [Tab::Counter, Tab::Stopwatch, Tab::History, Tab::Settings];
+
+    /// What the url's hash calls the tab.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Tab::Counter => "counter",
+            Tab::Stopwatch => "stopwatch",
+            Tab::History => "history",
+            Tab::Settings => "settings",
+        }
+    }
+
+    /// The tab named by a url's hash, with or without the `#`.
+    pub fn parse(hash: &str) -> Option<Self> {
+        let name = hash.strip_prefix('#').unwrap_or(hash);
+        Tab::ALL.iter().copied().find(|tab| tab.as_str() == name)
+    }
+
+    /// The translation key for what the tab list calls it.
+    pub fn label(self) -> &'static str {
+        match self {
+            Tab::Counter => "tab_counter",
+            Tab::Stopwatch => "tab_stopwatch",
+            Tab::History => "tab_history",
+            Tab::Settings => "tab_settings",
+        }
+    }
+
+    /// The tab `offset` tabs along from this one, wrapping around at either end.
+    pub fn cycle(self, offset: i32) -> Self {
+        let len = Tab::ALL.len() as i32;
+        let index = Tab::ALL.iter().position(|&tab| tab == self).unwrap_or(0) as i32;
+        Tab::ALL[(index + offset).rem_euclid(len) as usize]
+    }
+}
+
+/// The tab named by the page url's hash.
+pub fn current() -> Option<Tab> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    Tab::parse(&hash)
+}
+
+/// Name `tab` in the page url's hash.
+pub fn set_hash(tab: Tab) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+
+    if let Err(e) = window.location().set_hash(tab.as_str()) {
+        error!("error setting the url hash: {:?}", e);
+    }
+}
+
+/// Send `Msg::SelectTab` when the page url's hash changes to name a tab, like when going back.
+pub fn watch(shared: Shared) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+
+    let target = shared.clone();
+    let listener = EventListener::new(&window, "hashchange", move |_| {
+        if let Some(tab) = current() {
+            target.dispatch(Msg::SelectTab(tab));
+        }
+    });
+    match listener {
+        Ok(listener) => shared.listen(listener),
+        Err(e) => error!("error listening for hash changes: {:?}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes() {
+        assert_eq!(Tab::parse("#history"), Some(Tab::History));
+        assert_eq!(Tab::parse("settings"), Some(Tab::Settings));
+        assert_eq!(Tab::parse(""), None);
+        assert_eq!(Tab::parse("#nowhere"), None);
+        for &tab in &Tab::ALL {
+            assert_eq!(Tab::parse(tab.as_str()), Some(tab));
+        }
+    }
+
+    #[test]
+    fn cycled() {
+        assert_eq!(Tab::Counter.cycle(1), Tab::Stopwatch);
+        assert_eq!(Tab::Counter.cycle(-1), Tab::Settings);
+        assert_eq!(Tab::Settings.cycle(1), Tab::Counter);
+        assert_eq!(Tab::History.cycle(6), Tab::Counter);
+    }
+}
@@ -0,0 +1,358 @@
+//! The counter's state as JSON, to export to a file and import again.
+//!
+//! ```json
+//! {
+//!   "version": 2,
+//!   "count": 42,
+//!   "step": 2,
+//!   "goal": 100,
+//!   "history": [{ "time": 1546300800000, "action": "increment", "count": 42 }],
+//!   "settings": {
+//!     "scheme": "dark",
+//!     "theme": "Forest",
+//!     "direction": "ltr",
+//!     "locale": "de-DE",
+//!     "places": 0
+//!   }
+//! }
+//! ```
+//!
+//! The count, step, goal and history are in units of the count at the `places` decimal places in
+//! the settings.
+//!
+//! Imported files can come from anywhere, everything in them is checked before any of it is used.
+//!
+//! Files exported by older versions of the counter are upgraded one version at a time, each
+//! version in `MIGRATIONS` upgrades from the one before it:
+//!
+//! 0. a bare count, which is how the count on its own is stashed and saved
+//! 1. the first exports, with no version
+//! 2. the version is given
+
+use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::Count;
+use crate::decimal::MAX_PLACES;
+use crate::history::{Entry, MAX_ENTRIES};
+use crate::layout::Direction;
+use crate::locale::Locale;
+use crate::migrate::{self, Migration};
+use crate::preferences::{self, MAX_STEP};
+use crate::theme::{Scheme, Theme};
+use crate::validate::Invalid;
+
+/// The version of the format exported now.
+pub const VERSION: u64 = 2;
+
+/// The upgrades to each version, starting from version 0.
+const MIGRATIONS: &[Migration] = &[from_bare_count, versioned];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u64,
+    pub count: Count,
+    pub step: i32,
+    #[serde(default)]
+    pub goal: Option<Count>,
+    #[serde(default)]
+    pub history: VecDeque<Entry>,
+    #[serde(default)]
+    pub settings: Settings,
+}
+
+/// How the counter looks, anything left out is left as it is.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub scheme: Option<String>,
+    pub theme: Option<String>,
+    pub high_contrast: Option<bool>,
+    pub direction: Option<String>,
+    pub locale: Option<String>,
+    /// How many decimal places the count has, left out by files from before it could have any.
+    pub places: Option<u32>,
+}
+
+/// Why a file couldn't be imported.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportError {
+    /// The file with this name is bigger than anything the counter exports.
+    TooBig(String),
+    /// The browser couldn't read the file with this name.
+    Unreadable(String),
+    /// What's in the file isn't something the counter could have exported, saying why.
+    Invalid(String),
+    /// The step is further from zero than the settings allow, in units at these decimal places.
+    StepTooLarge(u32),
+}
+
+impl ImportError {
+    /// What went wrong, for showing in a banner.
+    pub fn message(&self, locale: &Locale) -> String {
+        match self {
+            ImportError::TooBig(name) => locale.t1("import_too_big", name),
+            ImportError::Unreadable(name) => locale.t1("import_unreadable", name),
+            ImportError::Invalid(e) => locale.t1("import_failed", e),
+            // said the way the settings form says it
+            ImportError::StepTooLarge(places) => {
+                let too_large = Invalid::TooLarge(Count::from(MAX_STEP)).message(*places, locale);
+                locale.t1("import_failed", &too_large)
+            }
+        }
+    }
+}
+
+impl Snapshot {
+    pub fn to_json(&self) -> String {
+        // nothing in a snapshot can fail to serialize
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Parse and check an exported snapshot, upgrading it from whichever version exported it.
+    pub fn parse(json: &str) -> Result<Self, ImportError> {
+        let parse = || {
+            let value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+            serde_json::from_value(migrate(value)?).map_err(|e| e.to_string())
+        };
+        let snapshot: Snapshot = parse().map_err(ImportError::Invalid)?;
+        snapshot.check()?;
+        Ok(snapshot)
+    }
+
+    /// Whether the snapshot is something the counter could have exported.
+    fn check(&self) -> Result<(), ImportError> {
+        if self.version != VERSION {
+            return invalid(format!("unknown version {}", self.version));
+        }
+        if self.step == 0 {
+            return invalid("the step can't be 0");
+        }
+        if self.goal.is_some_and(|goal| goal <= 0) {
+            return invalid("the goal has to be positive");
+        }
+        if self.history.len() > MAX_ENTRIES {
+            return invalid(format!("the history has more than {} entries", MAX_ENTRIES));
+        }
+        if self.history.iter().any(|entry| !entry.time.is_finite()) {
+            return invalid("the history has an invalid time");
+        }
+
+        let settings = &self.settings;
+        let unknown = |what: &str, name: &str| invalid(format!("unknown {} {:?}", what, name));
+        match settings.scheme.as_deref() {
+            Some(name) if Scheme::parse(name).is_none() => return unknown("scheme", name),
+            _ => {}
+        }
+        match settings.theme.as_deref() {
+            Some(name) if Theme::find(name).is_none() => return unknown("theme", name),
+            _ => {}
+        }
+        match settings.direction.as_deref() {
+            Some(dir) if Direction::parse(dir).is_none() => return unknown("direction", dir),
+            _ => {}
+        }
+        match settings.locale.as_deref() {
+            Some(tag) if Locale::find(tag).is_none() => return unknown("locale", tag),
+            _ => {}
+        }
+        if settings.places.is_some_and(|places| places > MAX_PLACES) {
+            return invalid(format!("the count can't have more than {} decimal places", MAX_PLACES));
+        }
+
+        // the step is in units at the snapshot's decimal places
+        if !preferences::is_valid_step(self.step) {
+            return Err(ImportError::StepTooLarge(settings.places.unwrap_or(0)));
+        }
+
+        Ok(())
+    }
+}
+
+/// A snapshot that isn't something the counter could have exported, saying why.
+fn invalid<T>(why: impl Into<String>) -> Result<T, ImportError> {
+    Err(ImportError::Invalid(why.into()))
+}
+
+/// Which version of the format `value` is in.
+fn version(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Number(_) => Ok(0),
+        Value::Object(object) => match object.get("version") {
+            None => Ok(1),
+            Some(version) => version.as_u64()
+                .ok_or_else(|| "the version isn't a number".to_owned()),
+        },
+        _ => Err("it isn't an exported counter".to_owned()),
+    }
+}
+
+/// Upgrade `value` to the current version of the format.
+fn migrate(value: Value) -> Result<Value, String> {
+    let from = version(&value)?;
+    migrate::upgrade(value, from, MIGRATIONS)
+}
+
+/// Version 0 was only a count.
+fn from_bare_count(value: Value) -> Result<Value, String> {
+    let count: Count = serde_json::from_value(value.clone())
+        .map_err(|_| format!("{} isn't a count", value))?;
+    Ok(json!({ "count": count, "step": 1 }))
+}
+
+/// Version 1 didn't say what version it was, it's otherwise the same as version 2.
+fn versioned(mut value: Value) -> Result<Value, String> {
+    value["version"] = json!(2);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Action;
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            version: VERSION,
+            count: 42,
+            step: 2,
+            goal: Some(100),
+            history: VecDeque::from(vec![
+                Entry { time: 1000.0, action: Action::RolledBack, count: 42 },
+            ]),
+            settings: Settings {
+                scheme: Some("dark".to_owned()),
+                theme: Some("Forest".to_owned()),
+                high_contrast: None,
+                direction: Some("rtl".to_owned()),
+                locale: Some("de-DE".to_owned()),
+                places: Some(1),
+            },
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let json = snapshot().to_json();
+        assert!(json.contains(r#""action": "rolled_back""#));
+        assert_eq!(Snapshot::parse(&json), Ok(snapshot()));
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(Snapshot::parse(r#"{ "version": 2, "count": 1, "step": 1 }"#), Ok(Snapshot {
+            version: VERSION,
+            count: 1,
+            step: 1,
+            goal: None,
+            history: VecDeque::new(),
+            settings: Settings::default(),
+        }));
+    }
+
+    #[test]
+    fn version_0() {
+        let snapshot = Snapshot::parse("-7").unwrap();
+        assert_eq!(snapshot, Snapshot {
+            version: VERSION,
+            count: -7,
+            step: 1,
+            goal: None,
+            history: VecDeque::new(),
+            settings: Settings::default(),
+        });
+        let error = ImportError::Invalid("1.5 isn't a count".to_owned());
+        assert_eq!(Snapshot::parse("1.5"), Err(error));
+        #[cfg(not(feature = "wide"))]
+        assert!(Snapshot::parse("4294967296").is_err());
+    }
+
+    #[test]
+    fn version_1() {
+        let json = r#"{
+            "count": 42,
+            "step": 2,
+            "goal": 100,
+            "history": [{ "time": 1000.0, "action": "rolled_back", "count": 42 }],
+            "settings": {
+                "scheme": "dark",
+                "theme": "Forest",
+                "direction": "rtl",
+                "locale": "de-DE"
+            }
+        }"#;
+        // from before the count could have decimal places
+        let mut expected = snapshot();
+        expected.settings.places = None;
+        assert_eq!(Snapshot::parse(json), Ok(expected));
+    }
+
+    #[cfg(feature = "wide")]
+    #[test]
+    fn wide() {
+        let count = Count::from(i64::MAX) * 1000;
+        let snapshot = Snapshot { count, goal: Some(count + 1), ..snapshot() };
+        assert_eq!(Snapshot::parse(&snapshot.to_json()), Ok(snapshot));
+        assert_eq!(Snapshot::parse("9223372036854775807000").map(|s| s.count), Ok(count));
+    }
+
+    #[test]
+    fn newer_version() {
+        assert_eq!(
+            Snapshot::parse(r#"{ "version": 3, "count": 1, "step": 1 }"#),
+            Err(ImportError::Invalid(
+                "it's from a newer version of the counter (version 3)".to_owned()
+            )),
+        );
+        assert!(Snapshot::parse(r#"{ "version": "2", "count": 1, "step": 1 }"#).is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        let en = Locale::find("en-US").unwrap();
+        let invalid = |snapshot: Snapshot| {
+            Snapshot::parse(&snapshot.to_json()).unwrap_err().message(en)
+        };
+
+        assert!(Snapshot::parse(r#""42""#).is_err());
+        assert!(Snapshot::parse(r#"{ "count": "many", "step": 1 }"#).is_err());
+        assert!(Snapshot::parse(r#"{ "count": 1, "step": 1, "history": [{}] }"#).is_err());
+
+        assert_eq!(invalid(Snapshot { step: 0, ..snapshot() }),
+            "Couldn't import the file: the step can't be 0");
+        assert!(invalid(Snapshot { goal: Some(-1), ..snapshot() }).contains("goal"));
+        // in the app's locale, not the de-DE of the snapshot's settings
+        let too_large = "Couldn't import the file: Enter a number from -100,000.0 to 100,000.0.";
+        assert_eq!(invalid(Snapshot { step: MAX_STEP + 1, ..snapshot() }), too_large);
+        assert_eq!(invalid(Snapshot { step: -MAX_STEP - 1, ..snapshot() }), too_large);
+        let at_most = Snapshot { step: MAX_STEP, ..snapshot() };
+        assert_eq!(Snapshot::parse(&at_most.to_json()), Ok(at_most));
+
+        let entry = Entry { time: 0.0, action: Action::Increment, count: 1 };
+        let history = VecDeque::from(vec![entry; MAX_ENTRIES + 1]);
+        assert!(invalid(Snapshot { history, ..snapshot() }).contains("more than"));
+
+        let mut settings = snapshot().settings;
+        settings.theme = Some("<script>".to_owned());
+        assert_eq!(invalid(Snapshot { settings, ..snapshot() }),
+            "Couldn't import the file: unknown theme \"<script>\"");
+        let mut settings = snapshot().settings;
+        settings.locale = Some("xx".to_owned());
+        assert!(invalid(Snapshot { settings, ..snapshot() }).contains("locale"));
+        let mut settings = snapshot().settings;
+        settings.places = Some(MAX_PLACES + 1);
+        assert!(invalid(Snapshot { settings, ..snapshot() }).contains("decimal places"));
+    }
+
+    #[test]
+    fn import_errors() {
+        let de = Locale::find("de-DE").unwrap();
+        let too_big = ImportError::TooBig("counter.json".to_owned()).message(de);
+        assert_eq!(too_big, "counter.json konnte nicht importiert werden, die Datei ist zu groß");
+        let invalid = ImportError::Invalid("the step can't be 0".to_owned()).message(de);
+        assert_eq!(invalid, "Die Datei konnte nicht importiert werden: the step can't be 0");
+        let too_large = Invalid::TooLarge(Count::from(MAX_STEP)).message(2, de);
+        let step = ImportError::StepTooLarge(2).message(de);
+        assert_eq!(step, format!("Die Datei konnte nicht importiert werden: {}", too_large));
+    }
+}
@@ -0,0 +1,129 @@
+//! Keeping the count in sync with other windows through a WebSocket.
+//!
+//! Every change is sent to the server as a JSON object with a `count` field, or as that object in
+//! the binary format, and every count the server sends back (in either format, or as a bare
+//! number) is set on the counter.
+
+use wasm_bindgen::JsCast;
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use crate::{Count, Msg};
+use crate::codec::{Binary, Codec, Format, Message};
+use crate::handle::Shared;
+use crate::listener::EventListener;
+use crate::sync;
+
+/// A count sent over the socket.
+#[derive(Serialize, Deserialize)]
+struct CountMessage {
+    count: Count,
+}
+
+/// An open WebSocket, closed when dropped.
+pub struct Socket {
+    socket: web_sys::WebSocket,
+    format: Format,
+    _listeners: Vec<EventListener>,
+}
+
+impl Socket {
+    /// Send a count to the server, if the socket is open.
+    pub fn send(&self, count: Count) {
+        if self.socket.ready_state() != web_sys::WebSocket::OPEN {
+            warn!("socket not open, dropping count {}", count);
+            return;
+        }
+
+        let result = match self.format.encode(&CountMessage { count }) {
+            Ok(Message::Text(text)) => self.socket.send_with_str(&text),
+            Ok(Message::Binary(bytes)) => self.socket.send_with_u8_array(&bytes),
+            Err(e) => {
+                error!("error encoding count: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = result {
+            error!("error sending count: {:?}", e);
+        }
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        if let Err(e) = self.socket.close() {
+            error!("error closing socket: {:?}", e);
+        }
+    }
+}
+
+/// Connect to `url`, sending `Msg::Connected`, `Msg::RemoteSet`, and `Msg::Disconnected` to the
+/// app as things happen.
+///
+/// Counts are sent in the given format. The socket replaces any socket the app had before.
+pub fn connect(shared: Shared, url: String, format: Format) {
+    let socket = match web_sys::WebSocket::new(&url) {
+        Ok(socket) => socket,
+        Err(e) => {
+            error!("error connecting to {}: {:?}", url, e);
+            shared.dispatch(Msg::Disconnected);
+            return;
+        }
+    };
+    socket.set_binary_type(web_sys::BinaryType::Arraybuffer);
+
+    let on_open = {
+        let shared = shared.clone();
+        EventListener::new(&socket, "open", move |_| shared.dispatch(Msg::Connected))
+    };
+
+    let on_message = {
+        let shared = shared.clone();
+        EventListener::new(&socket, "message", move |event| {
+            let message = event.dyn_into::<web_sys::MessageEvent>()
+                .ok()
+                .and_then(|event| Message::from_js(&event.data()));
+
+            match message.as_ref().map(decode_count) {
+                Some(Ok(count)) => shared.dispatch(Msg::RemoteSet(count)),
+                Some(Err(e)) => warn!("ignoring message from {}: {}", url, e),
+                None => warn!("ignoring unknown message from {}", url),
+            }
+        })
+    };
+
+    // an error is always followed by a close
+    let on_close = {
+        let shared = shared.clone();
+        EventListener::new(&socket, "close", move |_| shared.dispatch(Msg::Disconnected))
+    };
+
+    match (on_open, on_message, on_close) {
+        (Ok(on_open), Ok(on_message), Ok(on_close)) => shared.set_socket(Socket {
+            socket,
+            format,
+            _listeners: vec![on_open, on_message, on_close],
+        }),
+        _ => error!("error listening to socket events"),
+    }
+}
+
+fn decode_count(message: &Message) -> Result<Count, String> {
+    match message {
+        // text may be a bare number too
+        Message::Text(text) => sync::parse_count(text),
+        Message::Binary(_) => Binary.decode::<CountMessage>(message).map(|m| m.count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_in_either_format() {
+        let binary = Format::Binary.encode(&CountMessage { count: -4 }).unwrap();
+        assert_eq!(decode_count(&binary), Ok(-4));
+        assert_eq!(decode_count(&Message::Text("12".to_owned())), Ok(12));
+    }
+}
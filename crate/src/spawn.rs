@@ -0,0 +1,64 @@
+//! Commands written as async blocks.
+//!
+//! A job is a future finishing with the message to send the app. `Cmd::Spawn` runs it with
+//! `spawn_local`, so something that answers with a message later can be an async block, rather
+//! than a command of its own and a function dispatching back to the app when it's done.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use crate::Msg;
+use crate::handle::Shared;
+
+/// A future finishing with a message for the app.
+///
+/// Jobs are named, so commands spawning them can be compared in tests.
+pub struct Job {
+    name: &'static str,
+    future: Pin<Box<dyn Future<Output = Msg>>>,
+}
+
+impl Job {
+    /// A job called `name` sending the message `future` finishes with. Nothing runs until it's
+    /// spawned.
+    pub fn new(name: &'static str, future: impl Future<Output = Msg> + 'static) -> Self {
+        Job {
+            name,
+            future: Box::pin(future),
+        }
+    }
+}
+
+/// Jobs with the same name are taken to be the same, what their futures do can't be compared.
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl fmt::Debug for Job {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Job").field(&self.name).finish()
+    }
+}
+
+/// Run `job`, sending the message it finishes with to the app.
+pub fn spawn(shared: Shared, job: Job) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let msg = job.future.await;
+        shared.dispatch(msg);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named() {
+        let job = |name| Job::new(name, async { Msg::Increment });
+        assert_eq!(job("increment"), job("increment"));
+        assert_ne!(job("increment"), job("decrement"));
+        assert_eq!(format!("{:?}", job("increment")), r#"Job("increment")"#);
+    }
+}
@@ -0,0 +1,31 @@
+//! Reading text aloud with the Web Speech API.
+
+use wasm_bindgen::prelude::*;
+use web_sys::SpeechSynthesisUtterance;
+use log::error;
+
+/// Whether the browser can read text aloud.
+pub fn supported() -> bool {
+    web_sys::window()
+        .and_then(|window| js_sys::Reflect::has(&window, &"speechSynthesis".into()).ok())
+        .unwrap_or(false)
+}
+
+/// Read `text` aloud, cutting off anything still being read.
+pub fn speak(text: &str) {
+    if let Err(e) = try_speak(text) {
+        error!("error speaking: {:?}", e);
+    }
+}
+
+fn try_speak(text: &str) -> Result<(), JsValue> {
+    let synthesis = web_sys::window()
+        .ok_or("couldn't get window handle")?
+        .speech_synthesis()?;
+
+    // a quickly changing count would otherwise queue up every count in between
+    synthesis.cancel();
+    synthesis.speak(&SpeechSynthesisUtterance::new_with_text(text)?);
+
+    Ok(())
+}
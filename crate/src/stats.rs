@@ -0,0 +1,79 @@
+//! Statistics about how the counter has been clicked.
+
+use std::collections::VecDeque;
+
+/// Milliseconds in the window clicks per minute are counted over.
+const MINUTE: f64 = 60_000.0;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub increments: u32,
+    pub decrements: u32,
+    /// How many clicks in a row went the same way as the latest one.
+    pub streak: u32,
+    /// Whether the latest click was an increment.
+    up: Option<bool>,
+    /// When the clicks in the minute up to the latest one happened, oldest first.
+    recent: VecDeque<f64>,
+}
+
+impl Stats {
+    /// Count an increment, or a decrement, at `now` milliseconds.
+    pub fn click(&mut self, up: bool, now: f64) {
+        if up {
+            self.increments = self.increments.saturating_add(1);
+        }
+        else {
+            self.decrements = self.decrements.saturating_add(1);
+        }
+
+        self.streak = if self.up == Some(up) { self.streak.saturating_add(1) } else { 1 };
+        self.up = Some(up);
+
+        self.recent.push_back(now);
+        while self.recent.front().is_some_and(|&then| now - then >= MINUTE) {
+            self.recent.pop_front();
+        }
+    }
+
+    pub fn clicks(&self) -> u32 {
+        self.increments.saturating_add(self.decrements)
+    }
+
+    /// How many clicks there were in the minute up to the latest one.
+    pub fn per_minute(&self) -> usize {
+        self.recent.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks() {
+        let mut stats = Stats::default();
+        stats.click(true, 0.0);
+        stats.click(true, 1000.0);
+        stats.click(false, 2000.0);
+        assert_eq!((stats.increments, stats.decrements, stats.clicks()), (2, 1, 3));
+        assert_eq!(stats.streak, 1);
+
+        stats.click(false, 3000.0);
+        stats.click(false, 4000.0);
+        assert_eq!(stats.streak, 3);
+    }
+
+    #[test]
+    fn per_minute() {
+        let mut stats = Stats::default();
+        for second in 0..90 {
+            stats.click(true, f64::from(second) * 1000.0);
+        }
+        assert_eq!(stats.per_minute(), 60);
+
+        stats.click(false, 1_000_000.0);
+        assert_eq!(stats.per_minute(), 1);
+        assert_eq!(stats.clicks(), 91);
+    }
+}
@@ -0,0 +1,117 @@
+//! A stopwatch, alongside the counter.
+//!
+//! The stopwatch is a model of its own, with its own messages wrapped in `crate::Msg::Stopwatch`.
+//! It only knows the times it's given, so it runs on the counter's clock. While it's running,
+//! animation frames keep the time shown up to date.
+
+/// What the stopwatch can be asked to do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Msg {
+    Start,
+    Stop,
+    /// Note the time so far, keeping the stopwatch running.
+    Lap,
+    Reset,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stopwatch {
+    /// When it was last started, if it's running.
+    started: Option<f64>,
+    /// Milliseconds it ran for before it was last started.
+    banked: f64,
+    /// The time as of the latest update or frame.
+    now: f64,
+    /// The time so far at each lap, oldest first.
+    pub laps: Vec<f64>,
+}
+
+impl Stopwatch {
+    pub fn running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Milliseconds the stopwatch has run for.
+    pub fn elapsed(&self) -> f64 {
+        let running = self.started.map_or(0.0, |started| (self.now - started).max(0.0));
+        self.banked + running
+    }
+
+    /// Do what `msg` asks at `now`.
+    pub fn update(&mut self, msg: Msg, now: f64) {
+        self.now = now;
+        match msg {
+            Msg::Start => {
+                self.started.get_or_insert(now);
+            }
+            Msg::Stop => {
+                if let Some(started) = self.started.take() {
+                    self.banked += (now - started).max(0.0);
+                }
+            }
+            Msg::Lap => {
+                if self.running() {
+                    self.laps.push(self.elapsed());
+                }
+            }
+            Msg::Reset => *self = Stopwatch { now, ..Stopwatch::default() },
+        }
+    }
+
+    /// Catch up with the time for an animation frame.
+    pub fn frame(&mut self, now: f64) {
+        self.now = now;
+    }
+}
+
+/// Milliseconds as minutes, seconds, and hundredths, with hours in front once there are any.
+pub fn format(ms: f64) -> String {
+    let hundredths = (ms.max(0.0) / 10.0).floor() as u64;
+    let (seconds, hundredths) = (hundredths / 100, hundredths % 100);
+    let (minutes, seconds) = (seconds / 60, seconds % 60);
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}:{:02}.{:02}", minutes, seconds, hundredths),
+        (hours, minutes) => format!("{}:{:02}:{:02}.{:02}", hours, minutes, seconds, hundredths),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running() {
+        let mut stopwatch = Stopwatch::default();
+        stopwatch.update(Msg::Start, 1000.0);
+        stopwatch.frame(1500.0);
+        assert_eq!(stopwatch.elapsed(), 500.0);
+
+        // starting again doesn't start over
+        stopwatch.update(Msg::Start, 1600.0);
+        stopwatch.update(Msg::Lap, 2000.0);
+        stopwatch.update(Msg::Stop, 2250.0);
+        stopwatch.frame(9000.0);
+        assert_eq!(stopwatch.elapsed(), 1250.0);
+        assert!(!stopwatch.running());
+
+        // laps only count while it's running
+        stopwatch.update(Msg::Lap, 9000.0);
+        stopwatch.update(Msg::Start, 10_000.0);
+        stopwatch.update(Msg::Lap, 10_250.0);
+        assert_eq!(stopwatch.laps, vec![1000.0, 1500.0]);
+
+        stopwatch.update(Msg::Reset, 11_000.0);
+        assert_eq!(stopwatch.elapsed(), 0.0);
+        assert!(stopwatch.laps.is_empty());
+        assert!(!stopwatch.running());
+    }
+
+    #[test]
+    fn formatted() {
+        assert_eq!(format(0.0), "0:00.00");
+        assert_eq!(format(1234.5), "0:01.23");
+        assert_eq!(format(61_990.0), "1:01.99");
+        assert_eq!(format(3_600_000.0 + 5_000.0), "1:00:05.00");
+        assert_eq!(format(-5.0), "0:00.00");
+    }
+}
@@ -0,0 +1,93 @@
+//! Access to the browser's web storage.
+//!
+//! Session storage holds state that should survive a reload, local storage holds state that should
+//! survive closing the page.
+//!
+//! Storage is best effort, if it is unavailable (private browsing, disabled by the user, etc.)
+//! reads return nothing and writes are dropped with a warning.
+
+use log::warn;
+
+/// Get a handle to `window.sessionStorage`, if there is one.
+fn session() -> Option<web_sys::Storage> {
+    web_sys::window()?
+        .session_storage()
+        .ok()?
+}
+
+/// Get a handle to `window.localStorage`, if there is one.
+fn local() -> Option<web_sys::Storage> {
+    web_sys::window()?
+        .local_storage()
+        .ok()?
+}
+
+/// Read a value from session storage.
+pub fn load_session(key: &str) -> Option<String> {
+    session()?
+        .get_item(key)
+        .ok()?
+}
+
+/// Write a value to session storage.
+pub fn store_session(key: &str, value: &str) {
+    let result = session()
+        .map(|storage| storage.set_item(key, value));
+
+    match result {
+        Some(Ok(())) => {}
+        Some(Err(e)) => warn!("error writing {} to session storage: {:?}", key, e),
+        None => warn!("session storage unavailable, dropping {}", key),
+    }
+}
+
+/// Remove a value from session storage.
+pub fn remove_session(key: &str) {
+    if let Some(Err(e)) = session().map(|storage| storage.remove_item(key)) {
+        warn!("error removing {} from session storage: {:?}", key, e);
+    }
+}
+
+/// Remove every value whose key starts with `prefix` from session storage.
+pub fn remove_session_prefixed(prefix: &str) {
+    let storage = match session() {
+        Some(storage) => storage,
+        None => return,
+    };
+
+    let len = storage.length().unwrap_or(0);
+    let keys: Vec<String> = (0..len)
+        .filter_map(|i| storage.key(i).ok().flatten())
+        .filter(|key| key.starts_with(prefix))
+        .collect();
+
+    for key in keys {
+        remove_session(&key);
+    }
+}
+
+/// Read a value from local storage.
+pub fn load_local(key: &str) -> Option<String> {
+    local()?
+        .get_item(key)
+        .ok()?
+}
+
+/// Write a value to local storage.
+pub fn store_local(key: &str, value: &str) {
+    let result = local()
+        .map(|storage| storage.set_item(key, value));
+
+    match result {
+        Some(Ok(())) => {}
+        Some(Err(e)) => warn!("error writing {} to local storage: {:?}", key, e),
+        None => warn!("local storage unavailable, dropping {}", key),
+    }
+}
+
+/// Remove a value from local storage.
+pub fn remove_local(key: &str) {
+    if let Some(Err(e)) = local().map(|storage| storage.remove_item(key)) {
+        warn!("error removing {} from local storage: {:?}", key, e);
+    }
+}
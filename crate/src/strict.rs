@@ -0,0 +1,47 @@
+//! Checking the view is a function of the model alone, with the `strict` feature in debug builds.
+//!
+//! Every render is done twice and the two compared. A view reading the clock, a random number, or
+//! anything else that isn't in the model renders differently the second time, which panics with
+//! the first difference rather than showing up later as a diff that never settles.
+
+use euca::dom::DomVec;
+use euca::vdom::DomIter;
+use crate::{Cmd, Msg};
+
+/// Panic if `second` isn't the same as `first`, both rendered from the same model.
+pub fn check(first: &DomVec<Msg, Cmd>, second: &DomVec<Msg, Cmd>) {
+    let (mut first, mut second) = (first.dom_iter(), second.dom_iter());
+    for position in 0.. {
+        match (first.next(), second.next()) {
+            (None, None) => return,
+            (a, b) if a == b => {}
+            (a, b) => panic!(
+                "the view rendered differently from the same model at item {}: {:?}, then {:?}",
+                position,
+                a,
+                b,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use euca::dom::Dom;
+
+    fn rendered(text: &'static str) -> DomVec<Msg, Cmd> {
+        vec![Dom::elem("div").push(Dom::text(text))].into()
+    }
+
+    #[test]
+    fn same() {
+        check(&rendered("1"), &rendered("1"));
+    }
+
+    #[test]
+    #[should_panic(expected = "at item 1")]
+    fn different() {
+        check(&rendered("1"), &rendered("2"));
+    }
+}
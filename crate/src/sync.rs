@@ -0,0 +1,207 @@
+//! Keeping the count in sync with a server.
+
+use cfg_if::cfg_if;
+use serde::{Deserialize, Serialize};
+use crate::{Count, Msg};
+use crate::auth;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+use crate::net;
+
+/// Milliseconds to back off before the first retry, doubled for each retry after that.
+const RETRY_DELAY: u32 = 1000;
+
+/// The longest to back off before a retry.
+const MAX_RETRY_DELAY: u32 = 30_000;
+
+/// The count reported by the server as an object with a `count` field.
+#[derive(Deserialize)]
+struct CountBody {
+    count: Count,
+}
+
+/// Parse the count out of a response body, either a bare number or a `CountBody`.
+pub fn parse_count(body: &str) -> Result<Count, String> {
+    // not an untagged enum, those can't hold a wide count
+    serde_json::from_str(body)
+        .or_else(|_| serde_json::from_str(body).map(|body: CountBody| body.count))
+        .map_err(|e| format!("invalid count from server: {}", e))
+}
+
+/// The changes made while offline that the server doesn't have yet.
+///
+/// Only the latest count is ever saved, it supersedes the rest, so that's all that's kept along
+/// with how many changes there were.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pending {
+    /// The count to save.
+    pub count: Count,
+    /// How many changes are waiting to be saved, to tell the user.
+    pub changes: usize,
+}
+
+impl Pending {
+    /// The changes waiting once `count` is queued after them.
+    pub fn queue(pending: Option<Pending>, count: Count) -> Self {
+        Pending {
+            count,
+            changes: pending.map_or(0, |pending| pending.changes) + 1,
+        }
+    }
+}
+
+/// How many milliseconds to back off before the given retry attempt, starting at 1.
+///
+/// The delay doubles with each attempt, up to a limit. Half of it is scaled by `jitter`, a random
+/// number in `[0, 1)`, so counters that failed together don't all retry at the same time.
+pub fn backoff(attempt: u32, jitter: f64) -> u32 {
+    let delay = RETRY_DELAY
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(MAX_RETRY_DELAY);
+
+    delay / 2 + (f64::from(delay / 2) * jitter) as u32
+}
+
+cfg_if! {
+    if #[cfg(feature = "graphql")] {
+        use crate::graphql::{load_request, parse_loaded, save_request, parse_saved};
+    }
+    else {
+        use serde::Serialize;
+        use crate::net::Request;
+
+        /// The body sent when saving the count.
+        #[derive(Serialize)]
+        struct SaveBody {
+            count: Count,
+        }
+
+        /// The request loading the count.
+        fn load_request(url: String) -> Request {
+            Request::get(url)
+        }
+
+        /// Parse the count out of the response to `load_request()`.
+        fn parse_loaded(body: &str) -> Result<Count, String> {
+            parse_count(body)
+        }
+
+        /// The request saving the count.
+        fn save_request(url: String, count: Count) -> Request {
+            let body = serde_json::to_string(&SaveBody { count })
+                .expect("error serializing count");
+
+            Request::post_json(url, body)
+        }
+
+        /// Check the response to `save_request()`, any successful response will do.
+        fn parse_saved(_body: &str) -> Result<(), net::Error> {
+            Ok(())
+        }
+    }
+}
+
+/// Fetch the count from `url` and send the result back to the app.
+pub fn fetch_count(shared: Shared, url: String, token: Option<String>) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let request = auth::authorize(load_request(url), token.as_deref());
+        let result = net::fetch(request)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|body| parse_loaded(&body));
+
+        shared.dispatch(Msg::CountLoaded(result));
+    });
+}
+
+/// Save the count to `url` and send the result back to the app.
+///
+/// On failure the app is told which `generation` of changes failed and the `previous` count to
+/// roll back to.
+pub fn save_count(
+    shared: Shared,
+    url: String,
+    token: Option<String>,
+    count: Count,
+    previous: Count,
+    generation: u32,
+) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let request = auth::authorize(save_request(url, count), token.as_deref());
+        let result = net::fetch(request)
+            .await
+            .and_then(|body| parse_saved(&body));
+
+        let msg = match result {
+            Ok(()) => Msg::Saved(count),
+            Err(error) => Msg::SaveFailed {
+                generation,
+                previous,
+                error,
+            },
+        };
+
+        shared.dispatch(msg);
+    });
+}
+
+/// Whether the browser thinks it's online.
+pub fn is_online() -> bool {
+    web_sys::window()
+        .map(|window| window.navigator().on_line())
+        .unwrap_or(true)
+}
+
+/// Send `Msg::Online` and `Msg::Offline` to the app as the browser's connectivity changes.
+pub fn watch_online(shared: Shared) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+
+    for &(kind, ref msg) in &[("online", Msg::Online), ("offline", Msg::Offline)] {
+        let (target, msg) = (shared.clone(), msg.clone());
+        match EventListener::new(&window, kind, move |_| target.dispatch(msg.clone())) {
+            Ok(listener) => shared.listen(listener),
+            Err(e) => log::error!("error listening for {} events: {:?}", kind, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_count_formats() {
+        assert_eq!(parse_count("42"), Ok(42));
+        assert_eq!(parse_count(r#"{ "count": -3 }"#), Ok(-3));
+        assert!(parse_count(r#"{ "total": 1 }"#).is_err());
+        assert!(parse_count("").is_err());
+    }
+
+    #[cfg(feature = "wide")]
+    #[test]
+    fn wide_counts() {
+        let count = Count::from(u64::MAX) + 1;
+        assert_eq!(parse_count("18446744073709551616"), Ok(count));
+        assert_eq!(parse_count(r#"{ "count": 18446744073709551616 }"#), Ok(count));
+    }
+
+    #[test]
+    fn pending_changes() {
+        let pending = Pending::queue(None, 1);
+        assert_eq!(pending, Pending { count: 1, changes: 1 });
+        let pending = Pending::queue(Some(pending), 2);
+        assert_eq!(pending, Pending { count: 2, changes: 2 });
+    }
+
+    #[test]
+    fn backoff_grows() {
+        assert_eq!(backoff(1, 0.0), 500);
+        assert_eq!(backoff(1, 0.99), 995);
+        assert_eq!(backoff(3, 0.0), 2000);
+        assert_eq!(backoff(100, 0.0), 15_000);
+        assert!(backoff(100, 0.99) < MAX_RETRY_DELAY);
+    }
+}
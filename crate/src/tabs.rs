@@ -0,0 +1,114 @@
+//! Keeping the count in sync with other tabs through a `BroadcastChannel`.
+//!
+//! Counters in other tabs of the same origin are on the same channel when they use the same stash
+//! key, so the same counter on the same page stays in sync without a server. Tabs broadcast their
+//! whole `PnCounter`, so changes made in several tabs at once are merged rather than lost.
+//!
+//! The tally only holds changes, not the count they were made to. Tabs that start from different
+//! counts, say one restored from before a reload and one opened fresh, stay that far apart.
+
+use wasm_bindgen::JsCast;
+use log::{error, warn};
+use crate::Msg;
+use crate::codec::{Codec, Format, Message};
+use crate::crdt::PnCounter;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+use crate::storage;
+
+/// An open broadcast channel, closed when dropped.
+pub struct Channel {
+    channel: web_sys::BroadcastChannel,
+    format: Format,
+    _listener: EventListener,
+}
+
+impl Channel {
+    /// Tell the other tabs about the changes we've seen.
+    pub fn broadcast(&self, tally: &PnCounter) {
+        let message = match self.format.encode(tally) {
+            Ok(message) => message,
+            Err(e) => {
+                error!("error encoding tally: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = self.channel.post_message(&message.to_js()) {
+            error!("error broadcasting tally: {:?}", e);
+        }
+    }
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        self.channel.close();
+    }
+}
+
+/// Join the channel called `name`, sending `Msg::Merge` to the app with the changes broadcast by
+/// other tabs. Our own changes are broadcast in the given format.
+pub fn open(shared: Shared, name: String, format: Format) {
+    let channel = match web_sys::BroadcastChannel::new(&name) {
+        Ok(channel) => channel,
+        Err(e) => {
+            error!("error opening broadcast channel {}: {:?}", name, e);
+            return;
+        }
+    };
+
+    let listener = {
+        let shared = shared.clone();
+        EventListener::new(&channel, "message", move |event| {
+            let message = event.dyn_into::<web_sys::MessageEvent>()
+                .ok()
+                .and_then(|event| Message::from_js(&event.data()));
+
+            match message.map(|message| format.decode(&message)) {
+                Some(Ok(tally)) => shared.dispatch(Msg::Merge(tally)),
+                Some(Err(e)) => warn!("ignoring broadcast on {}: {}", name, e),
+                None => warn!("ignoring unknown broadcast on {}", name),
+            }
+        })
+    };
+
+    match listener {
+        Ok(listener) => shared.set_channel(Channel { channel, format, _listener: listener }),
+        Err(e) => error!("error listening to broadcast channel: {:?}", e),
+    }
+}
+
+/// The id of this tab's replica of the tally, handed on to the next page in the tab through
+/// session storage under `key`.
+///
+/// The tally is stashed in session storage too, a reloaded tab carries on as the same replica
+/// rather than adding another to the tally every time. Browsers copy session storage into a
+/// duplicated tab, so the id is only there while the page is hidden. A live tab's copy has no id
+/// in it, and a duplicate of it picks its own rather than sharing one with the tab it came from.
+pub fn replica_id(shared: &Shared, key: &str) -> String {
+    let id = storage::load_session(key)
+        .unwrap_or_else(|| format!("{:x}", (js_sys::Math::random() * 2f64.powi(53)) as u64));
+    storage::remove_session(key);
+
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return id,
+    };
+
+    let (stored, stored_key) = (id.clone(), key.to_owned());
+    let hidden = EventListener::new(&window, "pagehide", move |_| {
+        storage::store_session(&stored_key, &stored);
+    });
+    // a page shown again from the back-forward cache is live again
+    let taken_key = key.to_owned();
+    let shown = EventListener::new(&window, "pageshow", move |_| {
+        storage::remove_session(&taken_key);
+    });
+    for listener in [hidden, shown] {
+        match listener {
+            Ok(listener) => shared.listen(listener),
+            Err(e) => error!("error keeping the replica id for the next page: {:?}", e),
+        }
+    }
+    id
+}
@@ -0,0 +1,179 @@
+//! Themes and light and dark color schemes.
+//!
+//! The host element gets a `dark` class while the dark scheme is in use, page styles key off of
+//! that. Until the user picks a scheme, the one their browser prefers is used.
+//!
+//! The counter's root node gets a `high-contrast` class when the browser asks for more contrast or
+//! forces its own colors, unless the user says otherwise.
+//!
+//! A theme is rendered as CSS custom properties on the counter's root node, for page styles to use
+//! in either scheme:
+//!
+//! ```css
+//! .counter button { background: var(--counter-accent); color: var(--counter-on-accent); }
+//! ```
+
+use log::error;
+use crate::handle::Shared;
+
+/// The class set on the host element for the dark scheme.
+const DARK: &str = "dark";
+
+/// Colors and sizes for the counter.
+#[derive(Debug, PartialEq)]
+pub struct Theme {
+    /// What the theme picker calls it.
+    pub name: &'static str,
+    /// The color of buttons.
+    pub accent: &'static str,
+    /// The color of text on buttons.
+    pub on_accent: &'static str,
+    /// The size of the count.
+    pub font_size: &'static str,
+    /// The space between controls.
+    pub spacing: &'static str,
+    /// How rounded buttons are.
+    pub radius: &'static str,
+}
+
+/// The themes to pick from, the first is the default.
+pub const THEMES: &[Theme] = &[
+    Theme {
+        name: "Default",
+        accent: "#0366d6",
+        on_accent: "#fff",
+        font_size: "2em",
+        spacing: "0.5em",
+        radius: "4px",
+    },
+    Theme {
+        name: "Forest",
+        accent: "#2e7d32",
+        on_accent: "#fff",
+        font_size: "2em",
+        spacing: "0.5em",
+        radius: "12px",
+    },
+    Theme {
+        name: "Sunset",
+        accent: "#e8590c",
+        on_accent: "#000",
+        font_size: "2em",
+        spacing: "0.5em",
+        radius: "0",
+    },
+    Theme {
+        name: "Large",
+        accent: "#0366d6",
+        on_accent: "#fff",
+        font_size: "4em",
+        spacing: "1em",
+        radius: "8px",
+    },
+];
+
+impl Theme {
+    /// The theme with the given name.
+    pub fn find(name: &str) -> Option<&'static Theme> {
+        THEMES.iter().find(|theme| theme.name == name)
+    }
+
+    /// The theme as custom properties, for a `style` attribute.
+    pub fn style(&self) -> String {
+        format!(
+            "--counter-accent: {}; --counter-on-accent: {}; --counter-font-size: {}; \
+            --counter-spacing: {}; --counter-radius: {}",
+            self.accent, self.on_accent, self.font_size, self.spacing, self.radius,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scheme {
+    Light,
+    Dark,
+}
+
+impl Scheme {
+    /// The other scheme.
+    pub fn toggled(self) -> Self {
+        match self {
+            Scheme::Light => Scheme::Dark,
+            Scheme::Dark => Scheme::Light,
+        }
+    }
+
+    /// The scheme's name, as stored.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scheme::Light => "light",
+            Scheme::Dark => "dark",
+        }
+    }
+
+    /// The scheme with the given name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "light" => Some(Scheme::Light),
+            "dark" => Some(Scheme::Dark),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the page matches a media query.
+fn matches(query: &str) -> bool {
+    web_sys::window()
+        .and_then(|window| window.match_media(query).ok().flatten())
+        .is_some_and(|query| query.matches())
+}
+
+/// The scheme the browser prefers, light if it doesn't say.
+pub fn preferred() -> Scheme {
+    if matches("(prefers-color-scheme: dark)") { Scheme::Dark } else { Scheme::Light }
+}
+
+/// Whether the browser wants high contrast, either asking for it or forcing its own colors.
+pub fn prefers_high_contrast() -> bool {
+    matches("(prefers-contrast: more)") || matches("(forced-colors: active)")
+}
+
+/// Whether the browser wants less motion.
+pub fn prefers_reduced_motion() -> bool {
+    matches("(prefers-reduced-motion: reduce)")
+}
+
+/// Set or clear the dark class on the app's host element.
+pub fn apply(shared: &Shared, scheme: Scheme) {
+    let host = match shared.element() {
+        Some(host) => host,
+        None => return,
+    };
+
+    if let Err(e) = host.class_list().toggle_with_force(DARK, scheme == Scheme::Dark) {
+        error!("error applying color scheme: {:?}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheme_names() {
+        for scheme in &[Scheme::Light, Scheme::Dark] {
+            assert_eq!(Scheme::parse(scheme.as_str()), Some(*scheme));
+        }
+        assert_eq!(Scheme::parse("blue"), None);
+        assert_eq!(Scheme::Light.toggled(), Scheme::Dark);
+    }
+
+    #[test]
+    fn theme_style() {
+        assert_eq!(Theme::find("Forest").map(|theme| theme.radius), Some("12px"));
+        assert_eq!(Theme::find("Plaid"), None);
+        assert_eq!(THEMES[0].style(), "--counter-accent: #0366d6; \
+            --counter-on-accent: #fff; --counter-font-size: 2em; --counter-spacing: 0.5em; \
+            --counter-radius: 4px");
+    }
+}
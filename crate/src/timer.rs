@@ -0,0 +1,42 @@
+//! Timers backed by `window.setTimeout` and `window.requestAnimationFrame`.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use log::error;
+
+/// Call `f` once after `ms` milliseconds.
+pub fn after(ms: u32, f: impl FnOnce() + 'static) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => {
+            error!("couldn't get window handle to set a timer");
+            return;
+        }
+    };
+
+    let callback = Closure::once_into_js(f);
+    let result = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+        callback.unchecked_ref(),
+        ms as i32,
+    );
+
+    if let Err(e) = result {
+        error!("error setting timer: {:?}", e);
+    }
+}
+
+/// Call `f` once before the browser next repaints, with the time of the frame in milliseconds.
+pub fn next_frame(f: impl FnOnce(f64) + 'static) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => {
+            error!("couldn't get window handle to request an animation frame");
+            return;
+        }
+    };
+
+    let callback = Closure::once_into_js(f);
+    if let Err(e) = window.request_animation_frame(callback.unchecked_ref()) {
+        error!("error requesting animation frame: {:?}", e);
+    }
+}
@@ -0,0 +1,158 @@
+//! Debouncing and throttling messages, so features don't each keep their own timer bookkeeping.
+//!
+//! Debouncing waits for things to settle: each message scheduled with a timer supersedes the ones
+//! scheduled with it before, and only the last is acted on. Throttling lets something through
+//! right away and then at most once an interval, holding back a message to try again with once
+//! the interval is up. Either way the delayed message carries the generation it was scheduled in,
+//! and the model checks it's still current before acting on it.
+
+use std::collections::{HashMap, HashSet};
+use euca::app::Commands;
+use crate::{Cmd, Msg};
+use crate::handle::Shared;
+
+/// What a timer is for, each is debounced or throttled apart from the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Timer {
+    /// Saving the count to the server once it stops changing.
+    Save,
+    /// Filtering the history once the search stops being typed.
+    Search,
+    /// Reading the count aloud as it changes.
+    Speech,
+    /// Stepping the count over and over while a step button is held down.
+    Hold,
+}
+
+/// The state of every timer.
+#[derive(Debug, Default)]
+pub struct Timers {
+    /// The latest generation of each timer, messages from older ones are dropped.
+    generations: HashMap<Timer, u32>,
+    /// When each throttled timer last let something through, in milliseconds.
+    passed: HashMap<Timer, f64>,
+    /// The throttled timers holding a message back until their interval is up.
+    held: HashSet<Timer>,
+}
+
+impl Timers {
+    /// The latest generation of `timer`.
+    pub fn generation(&self, timer: Timer) -> u32 {
+        self.generations.get(&timer).copied().unwrap_or(0)
+    }
+
+    /// True if a message scheduled with `timer` in `generation` hasn't been superseded.
+    pub fn is_current(&self, timer: Timer, generation: u32) -> bool {
+        generation == self.generation(timer)
+    }
+
+    /// Drop any message scheduled with `timer`, returning the new generation.
+    pub fn cancel(&mut self, timer: Timer) -> u32 {
+        self.held.remove(&timer);
+        let generation = self.generations.entry(timer).or_insert(0);
+        *generation = generation.wrapping_add(1);
+        *generation
+    }
+
+    /// Send the message `msg` makes of a new generation of `timer` after `ms` milliseconds,
+    /// superseding any scheduled with it before.
+    pub fn debounce(
+        &mut self,
+        cmds: &mut Commands<Cmd>,
+        shared: &Shared,
+        timer: Timer,
+        ms: u32,
+        msg: impl FnOnce(u32) -> Msg,
+    ) {
+        let generation = self.cancel(timer);
+        cmds.push(Cmd::Delay(shared.clone(), ms, msg(generation)));
+    }
+
+    /// True if something can go through `timer` at `now`, at most once every `ms` milliseconds.
+    ///
+    /// If not, the message `msg` makes of the timer's generation is sent once the interval is up,
+    /// unless one already will be, so the latest of what was held back can try again then.
+    pub fn throttle(
+        &mut self,
+        cmds: &mut Commands<Cmd>,
+        shared: &Shared,
+        timer: Timer,
+        ms: u32,
+        now: f64,
+        msg: impl FnOnce(u32) -> Msg,
+    ) -> bool {
+        let since = self.passed.get(&timer).map_or(f64::INFINITY, |&passed| now - passed);
+        if since >= f64::from(ms) {
+            self.passed.insert(timer, now);
+            // anything held back is going through now
+            self.cancel(timer);
+            return true;
+        }
+
+        if self.held.insert(timer) {
+            let wait = (f64::from(ms) - since).ceil() as u32;
+            cmds.push(Cmd::Delay(shared.clone(), wait, msg(self.generation(timer))));
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The messages delayed by the given commands, with how long they're delayed for.
+    fn delayed(cmds: &Commands<Cmd>) -> Vec<(u32, Msg)> {
+        cmds.immediate.iter()
+            .filter_map(|cmd| match cmd {
+                Cmd::Delay(_, ms, msg) => Some((*ms, msg.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn debounced() {
+        let (mut timers, shared) = (Timers::default(), Shared::default());
+        let mut cmds = Commands::default();
+        for _ in 0..3 {
+            timers.debounce(&mut cmds, &shared, Timer::Save, 500, Msg::SaveDue);
+        }
+        assert_eq!(delayed(&cmds), vec![
+            (500, Msg::SaveDue(1)),
+            (500, Msg::SaveDue(2)),
+            (500, Msg::SaveDue(3)),
+        ]);
+
+        // only the last is acted on, other timers are kept apart
+        assert!(!timers.is_current(Timer::Save, 2));
+        assert!(timers.is_current(Timer::Save, 3));
+        assert!(timers.is_current(Timer::Search, 0));
+
+        timers.cancel(Timer::Save);
+        assert!(!timers.is_current(Timer::Save, 3));
+    }
+
+    #[test]
+    fn throttled() {
+        let (mut timers, shared) = (Timers::default(), Shared::default());
+        let mut throttle = |now: f64, cmds: &mut Commands<Cmd>| {
+            timers.throttle(cmds, &shared, Timer::Speech, 1000, now, Msg::SpeakDue)
+        };
+
+        // the first goes through, then one is held back until the interval is up
+        let mut cmds = Commands::default();
+        assert!(throttle(0.0, &mut cmds));
+        assert!(!throttle(200.0, &mut cmds));
+        assert!(!throttle(700.0, &mut cmds));
+        assert_eq!(delayed(&cmds), vec![(800, Msg::SpeakDue(1))]);
+
+        // trying again when it's up goes through
+        let mut cmds = Commands::default();
+        assert!(throttle(1000.0, &mut cmds));
+        assert!(delayed(&cmds).is_empty());
+        assert!(!throttle(1500.5, &mut cmds));
+        assert_eq!(delayed(&cmds), vec![(500, Msg::SpeakDue(2))]);
+        assert!(throttle(2500.0, &mut cmds));
+    }
+}
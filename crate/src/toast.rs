@@ -0,0 +1,99 @@
+//! Short messages stacked over the counter for a few seconds, saying how something went.
+//!
+//! Each toast goes away by itself once its time is up, the app schedules that when it pushes one.
+//! Only the newest few are kept, older ones make way for them.
+
+/// The most toasts shown at once.
+pub const MAX_TOASTS: usize = 3;
+
+/// Whether a toast is good news or bad.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Success,
+    Error,
+}
+
+impl Level {
+    /// The class the toast's element gets, to style it by.
+    pub fn class(self) -> &'static str {
+        match self {
+            Level::Success => "toast success",
+            Level::Error => "toast error",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Toast {
+    /// Tells the toast apart from the others for dismissing it.
+    pub id: u32,
+    pub level: Level,
+    pub text: String,
+}
+
+/// The toasts shown, oldest first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    /// The id of the next toast pushed.
+    next: u32,
+}
+
+impl Toasts {
+    /// Show a toast, dropping the oldest if there are too many. Returns its id.
+    pub fn push(&mut self, level: Level, text: String) -> u32 {
+        let id = self.next;
+        self.next = self.next.wrapping_add(1);
+        self.toasts.push(Toast { id, level, text });
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        id
+    }
+
+    /// Stop showing the toast with the given id, if it's still shown.
+    pub fn dismiss(&mut self, id: u32) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Toast> {
+        self.toasts.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(toasts: &Toasts) -> Vec<&str> {
+        toasts.iter().map(|toast| toast.text.as_str()).collect()
+    }
+
+    #[test]
+    fn stacked() {
+        let mut toasts = Toasts::default();
+        let first = toasts.push(Level::Success, "one".to_owned());
+        let second = toasts.push(Level::Error, "two".to_owned());
+        assert_ne!(first, second);
+        assert_eq!(texts(&toasts), vec!["one", "two"]);
+
+        toasts.dismiss(first);
+        assert_eq!(texts(&toasts), vec!["two"]);
+        // dismissing twice is fine, the timer and the user can both do it
+        toasts.dismiss(first);
+        assert_eq!(texts(&toasts), vec!["two"]);
+    }
+
+    #[test]
+    fn oldest_dropped() {
+        let mut toasts = Toasts::default();
+        for text in &["one", "two", "three", "four"] {
+            toasts.push(Level::Success, (*text).to_owned());
+        }
+        assert_eq!(texts(&toasts), vec!["two", "three", "four"]);
+    }
+}
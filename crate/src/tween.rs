@@ -0,0 +1,69 @@
+//! Counting through the numbers in between instead of jumping to a new count.
+//!
+//! The model keeps the true count, a tween keeps the count displayed on the way to it. Each
+//! animation frame moves the displayed count along, it catches up after `DURATION`.
+
+use crate::Count;
+
+/// Milliseconds to count up or down to a new count over.
+pub const DURATION: f64 = 200.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tween {
+    from: Count,
+    to: Count,
+    /// The time of the first frame, from `requestAnimationFrame`.
+    start: Option<f64>,
+    /// The count to display for now.
+    pub displayed: Count,
+}
+
+impl Tween {
+    pub fn new(from: Count, to: Count) -> Self {
+        Tween { from, to, start: None, displayed: from }
+    }
+
+    /// Move the displayed count along for a frame at `time`, false once it reached the true count.
+    pub fn frame(&mut self, time: f64) -> bool {
+        let start = *self.start.get_or_insert(time);
+        let progress = ((time - start) / DURATION).clamp(0.0, 1.0);
+        // ease out, slowing down on the way in
+        let eased = 1.0 - (1.0 - progress).powi(3);
+        let distance = self.to as f64 - self.from as f64;
+        // floats can't hold every wide count, so land on the count exactly
+        self.displayed = match progress < 1.0 {
+            true => (self.from as f64 + distance * eased).round() as Count,
+            false => self.to,
+        };
+        progress < 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames() {
+        let mut tween = Tween::new(97, 98);
+        assert!(tween.frame(1000.0));
+        assert_eq!(tween.displayed, 97);
+        assert!(tween.frame(1100.0));
+        assert_eq!(tween.displayed, 98);
+        assert!(!tween.frame(1200.0));
+        assert_eq!(tween.displayed, 98);
+
+        let mut tween = Tween::new(0, -1000);
+        tween.frame(0.0);
+        tween.frame(50.0);
+        assert_eq!(tween.displayed, -578);
+        // frames can come late
+        assert!(!tween.frame(500.0));
+        assert_eq!(tween.displayed, -1000);
+
+        let mut tween = Tween::new(Count::MIN, Count::MAX);
+        tween.frame(0.0);
+        assert!(!tween.frame(DURATION));
+        assert_eq!(tween.displayed, Count::MAX);
+    }
+}
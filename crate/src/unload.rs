@@ -0,0 +1,28 @@
+//! Asking before the page is closed with changes that haven't been saved yet.
+
+use wasm_bindgen::JsCast;
+use log::error;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+
+/// Have the browser ask whether to leave the page when it's closed or navigated away from.
+///
+/// The listener is kept until `Shared::unwatch_unload()` is called.
+pub fn watch(shared: Shared) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+
+    let listener = EventListener::new(&window, "beforeunload", |event| {
+        // browsers show their own message, older ones want something returned
+        event.prevent_default();
+        if let Some(event) = event.dyn_ref::<web_sys::BeforeUnloadEvent>() {
+            event.set_return_value("unsaved");
+        }
+    });
+    match listener {
+        Ok(listener) => shared.watch_unload(listener),
+        Err(e) => error!("error listening for the page closing: {:?}", e),
+    }
+}
@@ -0,0 +1,46 @@
+//! Reading files picked with a file input.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use log::error;
+use crate::Msg;
+use crate::handle::Shared;
+use crate::snapshot::{ImportError, Snapshot};
+
+/// Files bigger than this aren't read, no state the counter exports comes close.
+const MAX_SIZE: f64 = 4.0 * 1024.0 * 1024.0;
+
+/// Read exported state from `file`, sending `Msg::StateImported` to the app.
+pub fn read_state(shared: Shared, file: web_sys::File) {
+    if file.size() > MAX_SIZE {
+        shared.dispatch(Msg::StateImported(Err(ImportError::TooBig(file.name()))));
+        return;
+    }
+
+    let reader = match web_sys::FileReader::new() {
+        Ok(reader) => reader,
+        Err(e) => {
+            error!("error creating a file reader: {:?}", e);
+            shared.dispatch(Msg::StateImported(Err(ImportError::Unreadable(file.name()))));
+            return;
+        }
+    };
+
+    // fires once the file is read, or once reading it fails
+    let name = file.name();
+    let failed = shared.clone();
+    let loaded = reader.clone();
+    let onloadend = Closure::once_into_js(move || {
+        let result = match loaded.result().ok().and_then(|text| text.as_string()) {
+            Some(json) => Snapshot::parse(&json),
+            None => Err(ImportError::Unreadable(name)),
+        };
+        shared.dispatch(Msg::StateImported(result));
+    });
+    reader.set_onloadend(Some(onloadend.unchecked_ref()));
+
+    if let Err(e) = reader.read_as_text(&file) {
+        error!("error reading {}: {:?}", file.name(), e);
+        failed.dispatch(Msg::StateImported(Err(ImportError::Unreadable(file.name()))));
+    }
+}
@@ -0,0 +1,193 @@
+//! Checking what's typed into the counter's text boxes.
+//!
+//! Each rule takes the text as typed and either gives back the value it stands for or says what's
+//! wrong with it, so the view can say so next to the text box. Numbers can have as many decimal
+//! places as the count does, and stand for the units of the count at that many places.
+
+use std::convert::TryFrom;
+use crate::Count;
+use crate::locale::Locale;
+use crate::preferences::MAX_STEP;
+
+/// Why what was typed can't be used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Invalid {
+    NotANumber,
+    /// More decimal places than the count has.
+    TooPrecise,
+    /// Further from zero than the given limit.
+    TooLarge(Count),
+    /// Below zero where only zero and up make sense.
+    Negative,
+    Zero,
+}
+
+impl Invalid {
+    /// What's wrong, for showing next to the text box, with the count at `places` decimal places.
+    pub fn message(self, places: u32, locale: &Locale) -> String {
+        match (self, places) {
+            (Invalid::NotANumber, 0) | (Invalid::TooPrecise, 0) => {
+                locale.t("invalid_number").to_owned()
+            }
+            (Invalid::NotANumber, _) => locale.t("invalid_decimal").to_owned(),
+            (Invalid::TooPrecise, 1) => locale.t("invalid_place").to_owned(),
+            (Invalid::TooPrecise, places) => locale.t1("invalid_places", &places.to_string()),
+            (Invalid::TooLarge(limit), places) => {
+                let shown = |limit| locale.decimal(limit, places);
+                locale.t2("invalid_too_large", &shown(-limit), &shown(limit))
+            }
+            (Invalid::Negative, _) => locale.t("invalid_negative").to_owned(),
+            (Invalid::Zero, _) => locale.t("invalid_zero").to_owned(),
+        }
+    }
+}
+
+/// The text boxes that are checked.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+    Count,
+    Step,
+    Goal,
+}
+
+/// The units of a number with up to `places` decimal places, with its sign and surrounding space
+/// but no separators.
+fn number(text: &str, places: u32) -> Result<i128, Invalid> {
+    let text = text.trim();
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let is_digits = |text: &str| text.bytes().all(|b| b.is_ascii_digit());
+    if whole.len() + fraction.len() == 0 || !is_digits(whole) || !is_digits(fraction) {
+        return Err(Invalid::NotANumber);
+    }
+
+    // 1.50 is as precise as 1.5
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > places as usize {
+        return Err(Invalid::TooPrecise);
+    }
+
+    // the digits of the units are the whole digits followed by the fraction's, padded out
+    let units = format!("{}0{}{:0<width$}", sign, whole, fraction, width = places as usize);
+    // all digits, so the only way to fail is to overflow
+    units.parse().map_err(|_| Invalid::TooLarge(Count::MAX))
+}
+
+/// A count at `places` decimal places, which can't go below zero in a countdown.
+pub fn count(text: &str, countdown: bool, places: u32) -> Result<Count, Invalid> {
+    let count = number(text, places)?;
+    let count = Count::try_from(count).map_err(|_| Invalid::TooLarge(Count::MAX))?;
+    if countdown && count < 0 {
+        return Err(Invalid::Negative);
+    }
+    Ok(count)
+}
+
+/// A step to change the count by, in units at `places` decimal places.
+pub fn step(text: &str, places: u32) -> Result<i32, Invalid> {
+    match number(text, places)? {
+        0 => Err(Invalid::Zero),
+        step if step.abs() > MAX_STEP as i128 => Err(Invalid::TooLarge(Count::from(MAX_STEP))),
+        step => Ok(step as i32),
+    }
+}
+
+/// Check `text` typed into `field`, with the count at `places` decimal places.
+pub fn field(field: Field, text: &str, countdown: bool, places: u32) -> Result<Count, Invalid> {
+    match field {
+        Field::Count => count(text, countdown, places),
+        Field::Step => step(text, places).map(Count::from),
+        // the goal is never below zero, whether or not the count is counting down
+        Field::Goal => count(text, false, places),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(count(" 42 ", false, 0), Ok(42));
+        assert_eq!(count("-7", false, 0), Ok(-7));
+        assert_eq!(count("+7", false, 0), Ok(7));
+        assert_eq!(count("", false, 0), Err(Invalid::NotANumber));
+        assert_eq!(count("-", false, 0), Err(Invalid::NotANumber));
+        assert_eq!(count("4.5", false, 0), Err(Invalid::TooPrecise));
+        assert_eq!(count("1,000", false, 0), Err(Invalid::NotANumber));
+        assert_eq!(count("forty", false, 0), Err(Invalid::NotANumber));
+    }
+
+    #[cfg(not(feature = "wide"))]
+    #[test]
+    fn too_large() {
+        let max = i32::MAX;
+        assert_eq!(count("2147483647", false, 0), Ok(i32::MAX));
+        assert_eq!(count("2147483648", false, 0), Err(Invalid::TooLarge(max)));
+        assert_eq!(count("-2147483648", false, 0), Ok(i32::MIN));
+        assert_eq!(count("-2147483649", false, 0), Err(Invalid::TooLarge(max)));
+        assert_eq!(count("99999999999999999999999", false, 0), Err(Invalid::TooLarge(max)));
+    }
+
+    #[cfg(feature = "wide")]
+    #[test]
+    fn wide() {
+        assert_eq!(count("2147483648", false, 0), Ok(2_147_483_648));
+        let min = -99_999_999_999_999_999_999_999;
+        assert_eq!(count("-99999999999999999999999", false, 0), Ok(min));
+        let max = "170141183460469231731687303715884105727";
+        assert_eq!(count(max, false, 0), Ok(Count::MAX));
+        let over = "170141183460469231731687303715884105728";
+        assert_eq!(count(over, false, 0), Err(Invalid::TooLarge(Count::MAX)));
+    }
+
+    #[test]
+    fn decimals() {
+        assert_eq!(count("1.25", false, 2), Ok(125));
+        assert_eq!(count("-0.5", false, 2), Ok(-50));
+        assert_eq!(count(".5", false, 1), Ok(5));
+        assert_eq!(count("3.", false, 1), Ok(30));
+        assert_eq!(count("3", false, 3), Ok(3000));
+        assert_eq!(count("1.500", false, 1), Ok(15));
+        assert_eq!(count("1.25", false, 1), Err(Invalid::TooPrecise));
+        assert_eq!(count(".", false, 1), Err(Invalid::NotANumber));
+        assert_eq!(count("1.2.3", false, 3), Err(Invalid::NotANumber));
+        assert_eq!(count("1,5", false, 1), Err(Invalid::NotANumber));
+        assert_eq!(step("0.25", 2), Ok(25));
+        assert_eq!(step("0.00", 2), Err(Invalid::Zero));
+    }
+
+    #[test]
+    fn counting_down() {
+        assert_eq!(count("0", true, 0), Ok(0));
+        assert_eq!(count("-1", true, 0), Err(Invalid::Negative));
+    }
+
+    #[test]
+    fn steps() {
+        assert_eq!(step("-3", 0), Ok(-3));
+        assert_eq!(step("0", 0), Err(Invalid::Zero));
+        let max = Count::from(MAX_STEP);
+        assert_eq!(step("1000001", 0), Err(Invalid::TooLarge(max)));
+        assert_eq!(step("-1000001", 0), Err(Invalid::TooLarge(max)));
+        assert_eq!(step("one", 0), Err(Invalid::NotANumber));
+        assert_eq!(field(Field::Step, "2", true, 0), Ok(2));
+    }
+
+    #[test]
+    fn messages() {
+        let locale = &crate::locale::LOCALES[0];
+        let message = Invalid::TooLarge(1_000_000).message(0, locale);
+        assert_eq!(message, "Enter a number from -1,000,000 to 1,000,000.");
+        let message = Invalid::TooLarge(1_000_000).message(2, locale);
+        assert_eq!(message, "Enter a number from -10,000.00 to 10,000.00.");
+        assert_eq!(Invalid::NotANumber.message(0, locale), "Enter a whole number.");
+        assert_eq!(Invalid::TooPrecise.message(0, locale), "Enter a whole number.");
+        assert_eq!(Invalid::NotANumber.message(2, locale), "Enter a number.");
+        assert_eq!(Invalid::TooPrecise.message(1, locale), "Use at most one decimal place.");
+        assert_eq!(Invalid::TooPrecise.message(3, locale), "Use at most 3 decimal places.");
+    }
+}
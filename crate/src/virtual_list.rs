@@ -0,0 +1,68 @@
+//! Rendering only the rows of a long list that are scrolled into view.
+//!
+//! The rows that aren't rendered are made up for with padding above and below the ones that are,
+//! so the list keeps its full height and the scroll bar stays where it was. Every row has to be
+//! the same height for that to work.
+
+use std::ops::Range;
+
+/// Rows rendered past either end of the ones in view, so scrolling doesn't uncover a gap before
+/// the next render.
+const OVERSCAN: usize = 5;
+
+/// Where a list is scrolled to and how much of it can be seen, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub scroll_top: f64,
+    pub height: f64,
+    /// How tall each row is, as measured.
+    pub row_height: f64,
+}
+
+impl Default for Viewport {
+    /// A guess at a list that hasn't been scrolled yet, good enough for the first render.
+    fn default() -> Self {
+        Viewport { scroll_top: 0.0, height: 200.0, row_height: 24.0 }
+    }
+}
+
+impl Viewport {
+    /// Which of `total` rows to render.
+    pub fn rows(&self, total: usize) -> Range<usize> {
+        let row_height = self.row_height.max(1.0);
+        let first = (self.scroll_top / row_height).floor().max(0.0) as usize;
+        let visible = (self.height / row_height).ceil().max(0.0) as usize + 1;
+
+        let start = first.saturating_sub(OVERSCAN).min(total);
+        let end = first.saturating_add(visible + OVERSCAN).min(total);
+        start..end
+    }
+
+    /// The space taken by the rows above and below `rows`, out of `total` rows.
+    pub fn padding(&self, rows: &Range<usize>, total: usize) -> (f64, f64) {
+        let height = |rows: usize| rows as f64 * self.row_height;
+        (height(rows.start), height(total - rows.end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_in_view() {
+        let viewport = Viewport { scroll_top: 0.0, height: 100.0, row_height: 20.0 };
+        assert_eq!(viewport.rows(1000), 0..11);
+        assert_eq!(viewport.rows(3), 0..3);
+        assert_eq!(viewport.padding(&(0..11), 1000), (0.0, 989.0 * 20.0));
+
+        let viewport = Viewport { scroll_top: 1010.0, ..viewport };
+        assert_eq!(viewport.rows(1000), 45..61);
+        assert_eq!(viewport.padding(&(45..61), 1000), (900.0, 939.0 * 20.0));
+
+        // scrolled past the end, the list shrank since
+        let viewport = Viewport { scroll_top: 100_000.0, ..viewport };
+        assert_eq!(viewport.rows(10), 10..10);
+        assert_eq!(viewport.padding(&(10..10), 10), (200.0, 0.0));
+    }
+}
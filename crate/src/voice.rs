@@ -0,0 +1,163 @@
+//! Voice commands through the Web Speech API.
+//!
+//! While listening, saying "up" increments the count, "down" decrements it, and "reset" sets it
+//! back to zero. Chrome only provides the API with a `webkit` prefix, either is used.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{SpeechRecognition, SpeechRecognitionError, SpeechRecognitionErrorCode};
+use web_sys::SpeechRecognitionEvent;
+use log::error;
+use crate::Msg;
+use crate::handle::Shared;
+use crate::listener::EventListener;
+
+/// What the view shows about voice commands.
+#[derive(Debug, Default)]
+pub struct Voice {
+    /// True in browsers that can recognize speech.
+    pub supported: bool,
+    pub listening: bool,
+    /// Why listening stopped, if it wasn't asked to.
+    pub error: Option<String>,
+}
+
+/// A running speech recognizer, stopped when dropped.
+pub struct Recognizer {
+    recognition: SpeechRecognition,
+    _listeners: Vec<EventListener>,
+}
+
+impl Recognizer {
+    /// Stop listening, the recognizer sends `Msg::VoiceStopped` once it has.
+    pub fn stop(&self) {
+        self.recognition.stop();
+    }
+}
+
+impl Drop for Recognizer {
+    fn drop(&mut self) {
+        self.recognition.abort();
+    }
+}
+
+/// The speech recognition constructor, if the browser has one.
+fn constructor() -> Option<js_sys::Function> {
+    let window = web_sys::window()?;
+    ["SpeechRecognition", "webkitSpeechRecognition"].iter()
+        .filter_map(|name| js_sys::Reflect::get(&window, &(*name).into()).ok())
+        .find_map(|constructor| constructor.dyn_into().ok())
+}
+
+/// Whether the browser can recognize speech.
+pub fn supported() -> bool {
+    constructor().is_some()
+}
+
+/// The messages for the commands in something the user said.
+pub fn commands(transcript: &str) -> Vec<Msg> {
+    transcript.split_whitespace()
+        .filter_map(|word| match word.to_lowercase().trim_matches(|c: char| !c.is_alphanumeric()) {
+            "up" => Some(Msg::Increment),
+            "down" => Some(Msg::Decrement),
+            "reset" => Some(Msg::SetCount(0)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Start listening for voice commands, sending `Msg::VoiceStarted`, `Msg::VoiceFailed`, and
+/// `Msg::VoiceStopped` to the app as things happen, along with the messages for the commands
+/// heard.
+pub fn listen(shared: Shared) {
+    if let Err(e) = try_listen(&shared) {
+        error!("error starting speech recognition: {:?}", e);
+        shared.dispatch(Msg::VoiceFailed("Couldn't start listening".to_owned()));
+        shared.dispatch(Msg::VoiceStopped);
+    }
+}
+
+fn try_listen(shared: &Shared) -> Result<(), JsValue> {
+    let recognition: SpeechRecognition = js_sys::Reflect::construct(
+        &constructor().ok_or("speech recognition is not supported")?,
+        &js_sys::Array::new(),
+    )?.unchecked_into();
+    recognition.set_continuous(true)?;
+
+    let on_start = {
+        let shared = shared.clone();
+        EventListener::new(&recognition, "start", move |_| shared.dispatch(Msg::VoiceStarted))?
+    };
+
+    let on_result = {
+        let shared = shared.clone();
+        EventListener::new(&recognition, "result", move |event| {
+            let event: SpeechRecognitionEvent = event.unchecked_into();
+            let results = match event.results() {
+                Some(results) => results,
+                None => return,
+            };
+
+            // only the results that changed, and only once the recognizer is sure of them
+            for i in event.result_index()..results.length() {
+                let transcript = results.get(i)
+                    .filter(|result| result.is_final())
+                    .and_then(|result| result.get(0))
+                    .map(|alternative| alternative.transcript());
+
+                for msg in transcript.as_deref().map(commands).unwrap_or_default() {
+                    shared.dispatch(msg);
+                }
+            }
+        })?
+    };
+
+    let on_error = {
+        let shared = shared.clone();
+        EventListener::new(&recognition, "error", move |event| {
+            let event: SpeechRecognitionError = event.unchecked_into();
+            let e = match event.error() {
+                // running out of things to hear isn't a problem
+                SpeechRecognitionErrorCode::NoSpeech | SpeechRecognitionErrorCode::Aborted => {
+                    return;
+                }
+                SpeechRecognitionErrorCode::NotAllowed
+                | SpeechRecognitionErrorCode::ServiceNotAllowed => {
+                    "Microphone access was denied".to_owned()
+                }
+                code => format!("Speech recognition failed: {:?}", code),
+            };
+            shared.dispatch(Msg::VoiceFailed(e));
+        })?
+    };
+
+    let on_end = {
+        let shared = shared.clone();
+        EventListener::new(&recognition, "end", move |_| shared.dispatch(Msg::VoiceStopped))?
+    };
+
+    recognition.start()?;
+    shared.set_recognizer(Recognizer {
+        recognition,
+        _listeners: vec![on_start, on_result, on_error, on_end],
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voice_commands() {
+        assert_eq!(commands("up"), vec![Msg::Increment]);
+        assert_eq!(commands(" Down."), vec![Msg::Decrement]);
+        assert_eq!(commands("up up and reset"), vec![
+            Msg::Increment,
+            Msg::Increment,
+            Msg::SetCount(0),
+        ]);
+        assert_eq!(commands("hello there"), vec![]);
+    }
+}
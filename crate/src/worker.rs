@@ -0,0 +1,191 @@
+//! Running the counter's update loop in a Web Worker, leaving the page to render.
+//!
+//! The page runs a thin app that forwards clicks to the worker and renders the counts the worker
+//! sends back, while the worker runs the counter's real `update`. euca's patches hold on to dom
+//! nodes, so they can't be sent between threads. Instead the worker sends the state to render and
+//! the page diffs and patches it as usual. Side effects that need the page (storage, syncing,
+//! handles) aren't available in the worker and are dropped.
+//!
+//! ```js
+//! // worker.js
+//! import { worker_main } from "counter";
+//! worker_main();
+//!
+//! // main.js
+//! import { run_in_worker } from "counter";
+//! run_in_worker("main", new Worker(new URL("./worker.js", import.meta.url), { type: "module" }));
+//! ```
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use euca::app::*;
+use euca::dom::*;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::{Count, Model, Msg};
+
+/// A user event forwarded from the page to the worker.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Input {
+    Increment,
+    Decrement,
+}
+
+/// The state the worker sends the page to render.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct View {
+    count: Count,
+}
+
+/// Apply an input to the worker's model and return the new state to render.
+fn respond(model: &mut Model, input: Input) -> View {
+    let msg = match input {
+        Input::Increment => Msg::Increment,
+        Input::Decrement => Msg::Decrement,
+    };
+
+    // the commands need a page to run on, the worker only keeps the count
+    model.update(msg, &mut Commands::default());
+
+    View { count: model.count }
+}
+
+fn encode<T: Serialize>(value: &T) -> JsValue {
+    let json = serde_json::to_string(value)
+        .expect("error serializing worker message");
+
+    JsValue::from_str(&json)
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(event: &web_sys::MessageEvent) -> Option<T> {
+    let decoded = event.data()
+        .as_string()
+        .and_then(|data| serde_json::from_str(&data).ok());
+
+    if decoded.is_none() {
+        log::warn!("ignoring unknown worker message: {:?}", event.data());
+    }
+
+    decoded
+}
+
+/// Run the counter's update loop, call this from inside a worker.
+#[wasm_bindgen]
+pub fn worker_main() -> Result<(), JsValue> {
+    // there's no dom in a worker to show a crash on, just report it
+    crate::set_panic_hook();
+    crate::init_log();
+
+    let scope: web_sys::DedicatedWorkerGlobalScope = js_sys::global().dyn_into()?;
+    let model = RefCell::new(Model::new());
+
+    let reply = scope.clone();
+    let on_message = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+        if let Some(input) = decode(&event) {
+            let view = respond(&mut model.borrow_mut(), input);
+            if let Err(e) = reply.post_message(&encode(&view)) {
+                log::error!("error posting to page: {:?}", e);
+            }
+        }
+    }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // the worker runs the counter for as long as it lives
+    on_message.forget();
+
+    scope.post_message(&encode(&View { count: 0 }))
+}
+
+/// The page's side of the counter, rendering what the worker sends.
+struct Remote {
+    count: Count,
+    worker: web_sys::Worker,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum RemoteMsg {
+    Input(Input),
+    Render(View),
+}
+
+#[derive(Debug)]
+enum RemoteCmd {
+    /// Send an input to the worker.
+    Forward(web_sys::Worker, Input),
+}
+
+impl SideEffect<RemoteMsg> for RemoteCmd {
+    fn process(self, _: &Dispatcher<RemoteMsg, Self>) {
+        match self {
+            RemoteCmd::Forward(worker, input) => {
+                if let Err(e) = worker.post_message(&encode(&input)) {
+                    log::error!("error posting to worker: {:?}", e);
+                }
+            }
+        }
+    }
+}
+
+impl Update<RemoteMsg, RemoteCmd> for Remote {
+    fn update(&mut self, msg: RemoteMsg, cmds: &mut Commands<RemoteCmd>) {
+        match msg {
+            RemoteMsg::Input(input) => cmds.push(RemoteCmd::Forward(self.worker.clone(), input)),
+            RemoteMsg::Render(view) => self.count = view.count,
+        }
+    }
+}
+
+impl Render<DomVec<RemoteMsg, RemoteCmd>> for Remote {
+    fn render(&self) -> DomVec<RemoteMsg, RemoteCmd> {
+        let button = |text, input| Dom::elem("button")
+            .event("click", RemoteMsg::Input(input))
+            .push(Dom::text(text));
+
+        vec![
+            button("+", Input::Increment),
+            Dom::elem("div").push(text!("{}", self.count)),
+            button("-", Input::Decrement),
+        ].into()
+    }
+}
+
+/// Attach a counter to the first element matching the given CSS selector, with its update loop
+/// running in `worker`. The worker should call `worker_main()`.
+#[wasm_bindgen]
+pub fn run_in_worker(selector: &str, worker: web_sys::Worker) -> Result<(), JsValue> {
+    crate::setup()?;
+
+    let parent = crate::document()?
+        .query_selector(selector)
+        .map_err(|e| format!("error querying for {}: {:?}", selector, e))?
+        .ok_or_else(|| format!("no element matches {}", selector))?;
+
+    let app = AppBuilder::default()
+        .attach(parent, Remote { count: 0, worker: worker.clone() });
+
+    let dispatcher = Dispatcher::from(&app);
+    let on_message = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+        if let Some(view) = decode(&event) {
+            dispatcher.dispatch(RemoteMsg::Render(view));
+        }
+    }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+
+    worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // the app is never detached
+    on_message.forget();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worker_updates() {
+        let mut model = Model::new();
+        model.preferences.step = 2;
+        assert_eq!(respond(&mut model, Input::Increment), View { count: 2 });
+        assert_eq!(respond(&mut model, Input::Decrement), View { count: 0 });
+    }
+}
@@ -0,0 +1,28 @@
+//! Helpers shared by the browser tests.
+
+use wasm_bindgen_futures::JsFuture;
+use counter::CounterHandle;
+
+/// Mount a counter on a fresh element, returning the element and the counter's handle.
+pub fn mount(id: &str) -> (web_sys::Element, CounterHandle) {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let main = document.create_element("main").unwrap();
+    main.set_id(id);
+    document.body().unwrap().append_child(&main).unwrap();
+
+    let handle = counter::run_with_selector(&format!("#{}", id)).unwrap();
+    (main, handle)
+}
+
+/// Wait for the next animation frame, by which time the counter has rendered its changes.
+#[allow(dead_code)]
+pub async fn next_frame() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .request_animation_frame(&resolve)
+            .unwrap();
+    });
+
+    JsFuture::from(promise).await.unwrap();
+}
@@ -0,0 +1,40 @@
+//! Parts of the view coming and going, run in a browser with `wasm-pack test --headless --firefox`.
+
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+mod common;
+use common::{mount, next_frame};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn statistics_come_and_go() {
+    let (main, _handle) = mount("conditional");
+    next_frame().await;
+
+    let toggle: web_sys::HtmlElement = main.query_selector("button[aria-expanded]")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    let stats = || main.query_selector("dl.stats").unwrap();
+    assert!(stats().is_none());
+
+    // present, absent, and present again, with the toggle before it patched rather than replaced
+    for present in [true, false, true] {
+        toggle.click();
+        next_frame().await;
+        assert!(toggle.is_connected());
+        match stats() {
+            Some(stats) => {
+                assert!(present);
+                let toggle = web_sys::Element::from(toggle.clone());
+                assert_eq!(stats.previous_element_sibling(), Some(toggle));
+            }
+            None => assert!(!present),
+        }
+    }
+    assert_eq!(toggle.get_attribute("aria-expanded").as_deref(), Some("true"));
+}
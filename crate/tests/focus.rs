@@ -0,0 +1,37 @@
+//! Keeping focus across renders, run in a browser with `wasm-pack test --headless --firefox`.
+
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+mod common;
+use common::{mount, next_frame};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn focus_follows_a_replaced_input() {
+    let (main, _handle) = mount("focus");
+    next_frame().await;
+
+    let input: web_sys::HtmlInputElement = main.query_selector("input[aria-label='Set count']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    input.focus().unwrap();
+    input.set_selection_range(0, 1).unwrap();
+
+    // opening the context menu renders it where the input was, replacing the input
+    let count = main.query_selector("[role=spinbutton]").unwrap().unwrap();
+    let event = web_sys::MouseEvent::new("contextmenu").unwrap();
+    count.dispatch_event(&event).unwrap();
+    next_frame().await;
+
+    let document = web_sys::window().unwrap().document().unwrap();
+    let active: web_sys::HtmlInputElement = document.active_element().unwrap().unchecked_into();
+    assert!(!input.is_connected());
+    assert_eq!(active.get_attribute("aria-label").as_deref(), Some("Set count"));
+    assert_eq!(active.selection_start().unwrap(), Some(0));
+    assert_eq!(active.selection_end().unwrap(), Some(1));
+}
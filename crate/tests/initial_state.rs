@@ -0,0 +1,34 @@
+//! Initial state embedded in the page, run in a browser with `wasm-pack test --headless --firefox`.
+
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Add a counter's mount element and the script element with its initial state to the page.
+fn add(document: &web_sys::Document, id: &str, json: &str) {
+    let body = document.body().unwrap();
+
+    let state = document.create_element("script").unwrap();
+    state.set_id(&format!("{}-state", id));
+    state.set_attribute("type", "application/json").unwrap();
+    state.set_text_content(Some(json));
+    body.append_child(&state).unwrap();
+
+    let main = document.create_element("main").unwrap();
+    main.set_class_name("initial-state");
+    main.set_attribute("data-state", &format!("{}-state", id)).unwrap();
+    body.append_child(&main).unwrap();
+}
+
+#[wasm_bindgen_test]
+fn each_mount_gets_its_own_state() {
+    let document = web_sys::window().unwrap().document().unwrap();
+    add(&document, "first", r#"{ "count": 42 }"#);
+    add(&document, "second", r#"{ "count": 7, "step": 2 }"#);
+
+    let handles = counter::run_all(".initial-state").unwrap();
+    let counts: Vec<_> = handles.iter().map(|handle| handle.count()).collect();
+    assert_eq!(counts, vec![42, 7]);
+}
@@ -0,0 +1,73 @@
+//! Driving the counter with the keyboard, run in a browser with `wasm-pack test --headless
+//! --firefox`.
+
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+mod common;
+use common::mount;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn press(target: &web_sys::Element, key: &str) -> web_sys::KeyboardEvent {
+    let init = web_sys::KeyboardEventInit::new();
+    init.set_key(key);
+    init.set_bubbles(true);
+    init.set_cancelable(true);
+
+    let event = web_sys::KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &init)
+        .unwrap();
+    target.dispatch_event(&event).unwrap();
+    event
+}
+
+#[wasm_bindgen_test]
+fn arrow_keys_change_the_count() {
+    let (main, handle) = mount("arrow-keys");
+    let count = main.query_selector("[role=spinbutton]").unwrap().unwrap();
+    assert_eq!(count.get_attribute("tabindex").as_deref(), Some("0"));
+
+    let event = press(&count, "ArrowUp");
+    assert!(event.default_prevented());
+    assert_eq!(handle.count(), 1);
+
+    press(&count, "ArrowDown");
+    press(&count, "ArrowDown");
+    assert_eq!(handle.count(), -1);
+
+    // other keys are left alone
+    let event = press(&count, "a");
+    assert!(!event.default_prevented());
+    assert_eq!(handle.count(), -1);
+}
+
+#[wasm_bindgen_test]
+fn buttons_are_labelled() {
+    let (main, handle) = mount("labelled-buttons");
+    let increment = main.query_selector("button[aria-label=Increment]").unwrap().unwrap();
+    assert!(main.query_selector("button[aria-label=Decrement]").unwrap().is_some());
+
+    // Enter and Space on a focused button click it, which is what the browser does natively
+    increment.unchecked_into::<web_sys::HtmlElement>().click();
+    assert_eq!(handle.count(), 1);
+}
+
+#[wasm_bindgen_test]
+fn arrow_keys_move_between_tabs() {
+    let (main, _handle) = mount("arrow-tabs");
+    let counter = main.query_selector("[role=tab][aria-selected=true]").unwrap().unwrap();
+    assert_eq!(counter.get_attribute("tabindex").as_deref(), Some("0"));
+
+    let event = press(&counter, "ArrowLeft");
+    assert!(event.default_prevented());
+
+    // wrapped around to the last tab, which is focused and shows its panel
+    let selected = main.query_selector("[role=tab][aria-selected=true]").unwrap().unwrap();
+    assert_eq!(selected.text_content().as_deref(), Some("Settings"));
+    let document = web_sys::window().unwrap().document().unwrap();
+    assert_eq!(document.active_element(), Some(selected));
+    assert!(main.query_selector("[role=tabpanel] fieldset").unwrap().is_some());
+    assert!(main.query_selector("[role=spinbutton]").unwrap().is_none());
+}
@@ -0,0 +1,30 @@
+//! Announcing the count to screen readers, run in a browser with `wasm-pack test --headless
+//! --firefox`.
+
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+mod common;
+use common::{mount, next_frame};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn live_region_follows_the_count() {
+    let (main, handle) = mount("live-region");
+    next_frame().await;
+
+    let region = main.query_selector("[aria-live=polite]").unwrap().unwrap();
+    assert_eq!(region.text_content().as_deref(), Some("Count: 0"));
+
+    // every change is patched into the same region, so screen readers announce it
+    for count in 1..=3 {
+        handle.increment();
+        next_frame().await;
+
+        let patched = main.query_selector("[aria-live=polite]").unwrap().unwrap();
+        assert_eq!(patched, region);
+        assert_eq!(region.text_content().as_deref(), Some(format!("Count: {}", count).as_str()));
+    }
+}
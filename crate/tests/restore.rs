@@ -0,0 +1,67 @@
+//! Restoring what the counter kept in storage, run in a browser with `wasm-pack test --headless
+//! --firefox`.
+
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn negative_goal_ignored() {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    window.local_storage().unwrap().unwrap().set_item("negative-goal:goal", "-5").unwrap();
+
+    let main = document.create_element("main").unwrap();
+    main.set_id("negative-goal");
+    main.set_attribute("data-storage-key", "negative-goal").unwrap();
+    document.body().unwrap().append_child(&main).unwrap();
+    counter::run_with_selector("#negative-goal").unwrap();
+
+    // there's no goal to show progress towards
+    assert!(main.query_selector("progress").unwrap().is_none());
+    let input = main.query_selector("[data-focus=goal-input]").unwrap().unwrap();
+    assert_eq!(input.get_attribute("value").as_deref(), Some(""));
+}
+
+#[wasm_bindgen_test]
+fn theme_and_locale_moved_to_preferences() {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let storage = window.local_storage().unwrap().unwrap();
+    storage.set_item("old-preferences:theme", "Large").unwrap();
+    storage.set_item("old-preferences:locale", "de-DE").unwrap();
+
+    let main = document.create_element("main").unwrap();
+    main.set_id("old-preferences");
+    main.set_attribute("data-storage-key", "old-preferences").unwrap();
+    document.body().unwrap().append_child(&main).unwrap();
+    counter::run_with_selector("#old-preferences").unwrap();
+
+    // only what was picked is kept, and the old keys are gone
+    let kept = storage.get_item("old-preferences:preferences").unwrap();
+    let json = r#"{"value":{"locale":"de-DE","theme":"Large"},"version":1}"#;
+    assert_eq!(kept.as_deref(), Some(json));
+    assert_eq!(storage.get_item("old-preferences:theme").unwrap(), None);
+    assert_eq!(storage.get_item("old-preferences:locale").unwrap(), None);
+}
+
+#[wasm_bindgen_test]
+fn kept_before_it_was_versioned() {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    window.session_storage().unwrap().unwrap().set_item("unversioned", "42").unwrap();
+    let storage = window.local_storage().unwrap().unwrap();
+    storage.set_item("unversioned:preferences", r#"{ "step": 3 }"#).unwrap();
+
+    let main = document.create_element("main").unwrap();
+    main.set_id("unversioned");
+    main.set_attribute("data-storage-key", "unversioned").unwrap();
+    document.body().unwrap().append_child(&main).unwrap();
+    let handle = counter::run_with_selector("#unversioned").unwrap();
+
+    assert_eq!(handle.count(), 42);
+    handle.increment();
+    assert_eq!(handle.count(), 45);
+}
@@ -0,0 +1,30 @@
+// The counter's service worker, registered by builds with the `pwa` feature.
+//
+// Requests for the page and everything on it go to the network first, and a copy of each response
+// is cached, so the counter still opens when the network isn't there. A new version of this script
+// waits until the page asks it to take over, see `crate/src/pwa.rs`.
+
+const CACHE = 'counter';
+
+self.addEventListener('fetch', event => {
+  const request = event.request;
+  if (request.method !== 'GET' || new URL(request.url).origin !== self.location.origin) {
+    return;
+  }
+
+  event.respondWith(
+    fetch(request)
+      .then(response => {
+        const copy = response.clone();
+        event.waitUntil(caches.open(CACHE).then(cache => cache.put(request, copy)));
+        return response;
+      })
+      .catch(() => caches.match(request).then(cached => cached || Response.error()))
+  );
+});
+
+self.addEventListener('message', event => {
+  if (event.data === 'skipWaiting') {
+    self.skipWaiting();
+  }
+});